@@ -0,0 +1,86 @@
+use std::{env, fmt::Write, fs, path::Path};
+
+#[path = "src/move_generator/slider_attacks.rs"]
+mod slider_attacks;
+
+use slider_attacks::{bishop_blocker_mask, compute_bishop_attacks, compute_rook_attacks,
+    generate_blocker_permutations, rook_blocker_mask, BISHOP_MAGICS, ROOK_MAGICS};
+
+
+fn build_attack_table(
+    blocker_mask: fn(usize) -> u64,
+    magics: &[u64; 64],
+    compute_attacks: fn(usize, u64) -> u64) -> Vec<Vec<u64>> {
+    let mut table = vec![vec![]; 64];
+
+    for square in 0..64 {
+        let mask = blocker_mask(square);
+        let relevant_bits = mask.count_ones();
+        let shift = 64 - relevant_bits;
+        let mut attacks = vec![0; 1 << relevant_bits];
+
+        for blockers in generate_blocker_permutations(mask) {
+            let index = (blockers.wrapping_mul(magics[square])) >> shift;
+            attacks[index as usize] = compute_attacks(square, blockers);
+        }
+
+        table[square] = attacks;
+    }
+
+    table
+}
+
+// PEXT packs the masked blockers into the low bits in mask order, which is
+// exactly the order generate_blocker_permutations enumerates them in
+fn build_pext_table(
+    blocker_mask: fn(usize) -> u64,
+    compute_attacks: fn(usize, u64) -> u64) -> Vec<Vec<u64>> {
+    (0..64)
+        .map(|square| {
+            generate_blocker_permutations(blocker_mask(square))
+                .into_iter()
+                .map(|blockers| compute_attacks(square, blockers))
+                .collect()
+        })
+        .collect()
+}
+
+// Every square's attacks go into one slice, one after another, with the
+// offset each square's entries start at
+fn write_table(out: &mut String, name: &str, table: &[Vec<u64>]) {
+    writeln!(out, "pub static {name}: SliderTable = SliderTable {{").unwrap();
+    out.push_str("    offsets: [");
+    let mut offset = 0;
+    for attacks in table {
+        write!(out, "{offset},").unwrap();
+        offset += attacks.len();
+    }
+    out.push_str("],\n    attacks: &[\n");
+    for attacks in table {
+        out.push_str("        ");
+        for attack in attacks {
+            write!(out, "{attack:#x},").unwrap();
+        }
+        out.push('\n');
+    }
+    out.push_str("    ],\n};\n\n");
+}
+
+fn main() {
+    println!("cargo:rerun-if-changed=build.rs");
+    println!("cargo:rerun-if-changed=src/move_generator/slider_attacks.rs");
+
+    let rook_table = build_attack_table(rook_blocker_mask, &ROOK_MAGICS, compute_rook_attacks);
+    let bishop_table = build_attack_table(bishop_blocker_mask, &BISHOP_MAGICS, compute_bishop_attacks);
+    let rook_pext_table = build_pext_table(rook_blocker_mask, compute_rook_attacks);
+    let bishop_pext_table = build_pext_table(bishop_blocker_mask, compute_bishop_attacks);
+
+    let mut out = String::new();
+    write_table(&mut out, "ROOK_ATTACK_TABLE", &rook_table);
+    write_table(&mut out, "BISHOP_ATTACK_TABLE", &bishop_table);
+    write_table(&mut out, "ROOK_PEXT_TABLE", &rook_pext_table);
+    write_table(&mut out, "BISHOP_PEXT_TABLE", &bishop_pext_table);
+
+    let out_path = Path::new(&env::var("OUT_DIR").unwrap()).join("attack_tables.rs");
+    fs::write(out_path, out).unwrap();
+}
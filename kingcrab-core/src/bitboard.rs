@@ -0,0 +1,191 @@
+use crate::definitions::{Bitboard, FILE_BITBOARDS, RANK_BITBOARDS, SQUARE_BITBOARDS,
+    NrOf, Side, Square};
+
+
+pub const EMPTY: Bitboard = 0;
+pub const FULL: Bitboard = !0;
+
+pub const NOT_FILE_A: Bitboard = !FILE_BITBOARDS[0];
+pub const NOT_FILE_H: Bitboard = !FILE_BITBOARDS[NrOf::FILES - 1];
+
+pub const LIGHT_SQUARES: Bitboard = 0x55AA_55AA_55AA_55AA;
+pub const DARK_SQUARES: Bitboard = !LIGHT_SQUARES;
+
+
+pub fn file_mask(file: usize) -> Bitboard {
+    FILE_BITBOARDS[file]
+}
+
+pub fn rank_mask(rank: usize) -> Bitboard {
+    RANK_BITBOARDS[rank]
+}
+
+pub fn relative_rank_mask(side: Side, rank: usize) -> Bitboard {
+    match side {
+        Side::White => RANK_BITBOARDS[rank],
+        Side::Black => RANK_BITBOARDS[NrOf::RANKS - 1 - rank],
+    }
+}
+
+pub fn adjacent_files_mask(file: usize) -> Bitboard {
+    let file_bitboard = FILE_BITBOARDS[file];
+    ((file_bitboard & NOT_FILE_H) << 1) | ((file_bitboard & NOT_FILE_A) >> 1)
+}
+
+
+pub fn north(bitboard: Bitboard) -> Bitboard {
+    bitboard << 8
+}
+
+pub fn south(bitboard: Bitboard) -> Bitboard {
+    bitboard >> 8
+}
+
+pub fn east(bitboard: Bitboard) -> Bitboard {
+    (bitboard & NOT_FILE_H) << 1
+}
+
+pub fn west(bitboard: Bitboard) -> Bitboard {
+    (bitboard & NOT_FILE_A) >> 1
+}
+
+pub fn north_east(bitboard: Bitboard) -> Bitboard {
+    (bitboard & NOT_FILE_H) << 9
+}
+
+pub fn north_west(bitboard: Bitboard) -> Bitboard {
+    (bitboard & NOT_FILE_A) << 7
+}
+
+pub fn south_east(bitboard: Bitboard) -> Bitboard {
+    (bitboard & NOT_FILE_H) >> 7
+}
+
+pub fn south_west(bitboard: Bitboard) -> Bitboard {
+    (bitboard & NOT_FILE_A) >> 9
+}
+
+pub fn forward(bitboard: Bitboard, side: Side) -> Bitboard {
+    match side {
+        Side::White => north(bitboard),
+        Side::Black => south(bitboard),
+    }
+}
+
+pub fn pawn_attack_set(pawns: Bitboard, side: Side) -> Bitboard {
+    match side {
+        Side::White => north_east(pawns) | north_west(pawns),
+        Side::Black => south_east(pawns) | south_west(pawns),
+    }
+}
+
+
+pub fn north_fill(mut bitboard: Bitboard) -> Bitboard {
+    bitboard |= bitboard << 8;
+    bitboard |= bitboard << 16;
+    bitboard |= bitboard << 32;
+    bitboard
+}
+
+pub fn south_fill(mut bitboard: Bitboard) -> Bitboard {
+    bitboard |= bitboard >> 8;
+    bitboard |= bitboard >> 16;
+    bitboard |= bitboard >> 32;
+    bitboard
+}
+
+pub fn file_fill(bitboard: Bitboard) -> Bitboard {
+    north_fill(bitboard) | south_fill(bitboard)
+}
+
+pub fn forward_fill(bitboard: Bitboard, side: Side) -> Bitboard {
+    match side {
+        Side::White => north_fill(bitboard),
+        Side::Black => south_fill(bitboard),
+    }
+}
+
+// Swaps rank 1 with rank 8, rank 2 with rank 7 and so on
+pub fn flip_vertical(bitboard: Bitboard) -> Bitboard {
+    bitboard.swap_bytes()
+}
+
+// Swaps the a-file with the h-file, the b-file with the g-file and so on
+pub fn flip_horizontal(bitboard: Bitboard) -> Bitboard {
+    bitboard.reverse_bits().swap_bytes()
+}
+
+
+pub struct SquareIter {
+    bitboard: Bitboard,
+}
+
+impl Iterator for SquareIter {
+    type Item = Square;
+
+    fn next(&mut self) -> Option<Square> {
+        if self.bitboard == 0 {
+            return None;
+        }
+        let square = self.bitboard.trailing_zeros() as usize;
+        self.bitboard &= self.bitboard - 1;
+        Some(Square::try_from(square).unwrap())
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        let count = self.bitboard.count_ones() as usize;
+        (count, Some(count))
+    }
+}
+
+impl ExactSizeIterator for SquareIter {}
+
+
+// Bitboard is a plain u64 alias, so the helpers are added through a trait
+pub trait BitboardExt {
+    fn squares(self) -> SquareIter;
+    fn popcount(self) -> u32;
+    fn lsb(self) -> Option<Square>;
+    fn msb(self) -> Option<Square>;
+    fn pop_lsb(&mut self) -> Option<Square>;
+    fn contains_square(self, square: Square) -> bool;
+    fn more_than_one(self) -> bool;
+}
+
+impl BitboardExt for Bitboard {
+    fn squares(self) -> SquareIter {
+        SquareIter { bitboard: self }
+    }
+
+    fn popcount(self) -> u32 {
+        self.count_ones()
+    }
+
+    fn lsb(self) -> Option<Square> {
+        match self {
+            0 => None,
+            _ => Some(Square::try_from(self.trailing_zeros() as usize).unwrap()),
+        }
+    }
+
+    fn msb(self) -> Option<Square> {
+        match self {
+            0 => None,
+            _ => Some(Square::try_from(63 - self.leading_zeros() as usize).unwrap()),
+        }
+    }
+
+    fn pop_lsb(&mut self) -> Option<Square> {
+        let square = self.lsb();
+        *self &= self.wrapping_sub(1);
+        square
+    }
+
+    fn contains_square(self, square: Square) -> bool {
+        self & SQUARE_BITBOARDS[square as usize] != 0
+    }
+
+    fn more_than_one(self) -> bool {
+        self & self.wrapping_sub(1) != 0
+    }
+}
@@ -0,0 +1,1282 @@
+use std::sync::Arc;
+use std::fmt::{self, Display, Formatter};
+use std::str::FromStr;
+
+
+use crate::bitboard::{flip_horizontal, flip_vertical, BitboardExt,
+    DARK_SQUARES, LIGHT_SQUARES};
+use crate::move_generator::{attacks, chess_move::ChessMove};
+use crate::definitions::{Castling, FEN_STARTING_POSITION, HALF_MOVE_MAX,
+    HALF_MOVE_AUTOMATIC_DRAW,
+    BETWEEN_BITBOARDS, CASTLING_PERMS, LINE_BITBOARDS, PIECE_VALUES, RANK_BITBOARDS, SQUARE_BITBOARDS,
+    Bitboard, NrOf, Piece, Rank, Side, Square, ZobristKey};
+use crate::move_generator::{move_generator::MoveGenerator,
+    notation::{self, NotationError}};
+use crate::piece_square_tables::{piece_square_values, taper,
+    MAX_PHASE, PHASE_WEIGHTS};
+use super::{fen::{write_fen, FenError, FenParser}, game_history::{RecordedMove, GameHistory},
+    game_result::{DrawRules, GameResult, InsufficientMaterialRules}, game_state::GameState,
+    polyglot::PolyglotKeys, position_key::PositionKey, zobrist::ZobristKeys};
+
+
+#[derive(Clone)]
+pub struct Board {
+    pub sides: [Bitboard; NrOf::SIDES],
+    pub pieces: [[Bitboard; NrOf::PIECE_TYPES]; NrOf::SIDES],
+    pub piece_list: [Piece; NrOf::SQUARES],
+    pub game_state: GameState,
+    pub game_history: GameHistory,
+    pub zobrist_keys: Arc<ZobristKeys>,
+    // Keys of positions played before the current game history began
+    pub prior_positions: Vec<ZobristKey>,
+    pub material: [i32; NrOf::SIDES],
+    pub non_pawn_material: [i32; NrOf::SIDES],
+    pub phase: i32,
+}
+
+
+
+impl Board {
+    pub fn new() -> Self {
+        Board {
+            sides: [0; NrOf::SIDES],
+            pieces: [[0; NrOf::PIECE_TYPES]; NrOf::SIDES],
+            piece_list: [Piece::None; NrOf::SQUARES],
+            game_state: GameState::new(),
+            game_history: GameHistory::new(),
+            zobrist_keys: Arc::new(ZobristKeys::new()),
+            prior_positions: Vec::new(),
+            material: [0; NrOf::SIDES],
+            non_pawn_material: [0; NrOf::SIDES],
+            phase: 0,
+        }
+    }
+
+    pub fn reset(&mut self) {
+        self.sides = [0; NrOf::SIDES];
+        self.pieces = [[0; NrOf::PIECE_TYPES]; NrOf::SIDES];
+        self.piece_list = [Piece::None; NrOf::SQUARES];
+        self.prior_positions.clear();
+        self.material = [0; NrOf::SIDES];
+        self.non_pawn_material = [0; NrOf::SIDES];
+        self.phase = 0;
+        self.game_state.clear();
+        self.game_history.clear();
+    }
+
+
+    pub fn get_pieces(&self, side: Side, piece: Piece) -> Bitboard {
+        self.pieces[side as usize][piece as usize]
+    }
+
+    pub fn get_bitboards(&self, side: Side) -> &[Bitboard; NrOf::PIECE_TYPES] {
+        &self.pieces[side as usize]
+    }
+
+    pub fn piece_on(&self, square: Square) -> Option<(Side, Piece)> {
+        let piece = self.piece_list[square as usize];
+        if piece == Piece::None {
+            return None;
+        }
+
+        match self.sides[Side::White as usize] & SQUARE_BITBOARDS[square as usize] {
+            0 => Some((Side::Black, piece)),
+            _ => Some((Side::White, piece)),
+        }
+    }
+
+    pub fn get_side_occupancy(&self, side: Side) -> Bitboard {
+        self.sides[side as usize]
+    }
+
+    pub fn get_full_occupancy(&self) -> Bitboard {
+        self.sides[Side::White as usize] | self.sides[Side::Black as usize]
+    }
+
+    pub fn get_active_side(&self) -> Side {
+        self.game_state.active_side
+    }
+
+    pub fn get_opponent(&self) -> Side {
+        self.game_state.active_side.opponent()
+    }
+
+    pub fn material(&self, side: Side) -> i32 {
+        self.material[side as usize]
+    }
+
+    pub fn non_pawn_material(&self, side: Side) -> i32 {
+        self.non_pawn_material[side as usize]
+    }
+
+    pub fn position_key(&self) -> PositionKey {
+        PositionKey::new(self)
+    }
+
+    // Hash compatible with Polyglot opening books, given PolyglotKeys::standard()
+    pub fn polyglot_key(&self, keys: &PolyglotKeys) -> ZobristKey {
+        keys.hash(self)
+    }
+
+    pub fn get_pawn_key(&self) -> ZobristKey {
+        self.game_state.pawn_key
+    }
+
+    // White-relative middlegame and endgame piece-square sums
+    pub fn pst_scores(&self) -> (i32, i32) {
+        (self.game_state.pst_mg, self.game_state.pst_eg)
+    }
+
+    // Tapered piece-square evaluation from the side to move's point of view
+    pub fn pst_evaluation(&self) -> i32 {
+        let score = taper(self.game_state.pst_mg, self.game_state.pst_eg, self.game_phase());
+        match self.get_active_side() {
+            Side::White => score,
+            Side::Black => -score,
+        }
+    }
+
+    // 24 with all minor and major pieces on the board, 0 with none; extra
+    // promoted pieces do not push it past 24
+    pub fn game_phase(&self) -> i32 {
+        self.phase.min(MAX_PHASE)
+    }
+
+    pub fn get_king_square(&self, side: Side) -> Square {
+        let king_square = self.pieces[side as usize][Piece::King as usize]
+            .trailing_zeros() as usize;
+        Square::try_from(king_square).unwrap()
+
+    }
+
+    pub fn attackers_to(&self, square: Square, occupancy: Bitboard) -> Bitboard {
+        let white = &self.pieces[Side::White as usize];
+        let black = &self.pieces[Side::Black as usize];
+
+        let knights = white[Piece::Knight as usize] | black[Piece::Knight as usize];
+        let kings = white[Piece::King as usize] | black[Piece::King as usize];
+        let rook_like = white[Piece::Rook as usize] | black[Piece::Rook as usize]
+            | white[Piece::Queen as usize] | black[Piece::Queen as usize];
+        let bishop_like = white[Piece::Bishop as usize] | black[Piece::Bishop as usize]
+            | white[Piece::Queen as usize] | black[Piece::Queen as usize];
+
+        // A white pawn attacks the square if a black pawn standing on it
+        // would attack the white pawn, and vice versa
+        (attacks::pawn_attacks(square, Side::Black) & white[Piece::Pawn as usize])
+            | (attacks::pawn_attacks(square, Side::White) & black[Piece::Pawn as usize])
+            | (attacks::knight_attacks(square) & knights)
+            | (attacks::king_attacks(square) & kings)
+            | (attacks::rook_attacks(square, occupancy) & rook_like)
+            | (attacks::bishop_attacks(square, occupancy) & bishop_like)
+    }
+
+    // Pieces giving check to the side to move, cached when a move is made
+    pub fn checkers(&self) -> Bitboard {
+        self.game_state.checkers
+    }
+
+    pub fn in_check(&self) -> bool {
+        self.game_state.checkers != 0
+    }
+
+    fn compute_checkers(&self) -> Bitboard {
+        let king = self.get_pieces(self.get_active_side(), Piece::King);
+        if king == 0 {
+            return 0;
+        }
+
+        let king_square = self.get_king_square(self.get_active_side());
+        self.attackers_to(king_square, self.get_full_occupancy())
+            & self.get_side_occupancy(self.get_opponent())
+    }
+
+    pub fn blockers_for_king(&self, side: Side) -> Bitboard {
+        let king_square = self.get_king_square(side);
+        let enemy = &self.pieces[side.opponent() as usize];
+        let occupancy = self.get_full_occupancy();
+
+        // Enemy sliders that would attack the king on an empty board
+        let rook_snipers = attacks::rook_attacks(king_square, 0)
+            & (enemy[Piece::Rook as usize] | enemy[Piece::Queen as usize]);
+        let bishop_snipers = attacks::bishop_attacks(king_square, 0)
+            & (enemy[Piece::Bishop as usize] | enemy[Piece::Queen as usize]);
+
+        let mut blockers = 0;
+
+        for sniper_square in (rook_snipers | bishop_snipers).squares() {
+            // A single piece on the ray is the only thing stopping the attack
+            let pieces_between = BETWEEN_BITBOARDS[king_square as usize][sniper_square as usize]
+                & occupancy;
+            if pieces_between.popcount() == 1 {
+                blockers |= pieces_between;
+            }
+        }
+
+        blockers
+    }
+
+    pub fn pinned(&self, side: Side) -> Bitboard {
+        self.blockers_for_king(side) & self.get_side_occupancy(side)
+    }
+
+    pub fn is_pseudo_legal(&self, mv: ChessMove) -> bool {
+        let side = self.get_active_side();
+        let own_pieces = self.get_side_occupancy(side);
+        let enemy_pieces = self.get_side_occupancy(self.get_opponent());
+        let occupancy = own_pieces | enemy_pieces;
+
+        let from_bitboard = SQUARE_BITBOARDS[mv.from as usize];
+        let to_bitboard = SQUARE_BITBOARDS[mv.to as usize];
+
+        if own_pieces & from_bitboard == 0 || self.piece_list[mv.from as usize] != mv.piece {
+            return false;
+        }
+
+        if mv.is_king_castling() || mv.is_queen_castling() {
+            return self.is_castling_pseudo_legal(mv);
+        }
+
+        // Kings can never be captured, and the capture flag must match the target
+        let enemy_king = self.get_pieces(self.get_opponent(), Piece::King);
+        if own_pieces & to_bitboard != 0 || enemy_king & to_bitboard != 0 {
+            return false;
+        }
+        if !mv.is_en_passant() && mv.is_capture() != (enemy_pieces & to_bitboard != 0) {
+            return false;
+        }
+
+        if mv.piece != Piece::Pawn {
+            if mv.is_promotion() || mv.is_double_pawn_push() || mv.is_en_passant() {
+                return false;
+            }
+            return attacks::piece_attacks(mv.piece, side, mv.from, occupancy) & to_bitboard != 0;
+        }
+
+        let (push_offset, start_rank, promotion_rank) = match side {
+            Side::White => (8, 1, 7),
+            Side::Black => (-8, 6, 0),
+        };
+        let from_rank = mv.from.rank() as usize;
+        let to_rank = mv.to.rank() as usize;
+
+        if mv.is_promotion() != (to_rank == promotion_rank) {
+            return false;
+        }
+        if mv.promotion.is_some_and(|piece|
+            !matches!(piece, Piece::Queen | Piece::Rook | Piece::Bishop | Piece::Knight)) {
+            return false;
+        }
+
+        let single_push = mv.from as isize + push_offset;
+
+        if mv.is_en_passant() {
+            return self.get_ep_square() == Some(mv.to)
+                && attacks::pawn_attacks(mv.from, side) & to_bitboard != 0;
+        }
+
+        if mv.is_capture() {
+            return attacks::pawn_attacks(mv.from, side) & to_bitboard != 0;
+        }
+
+        if mv.is_double_pawn_push() {
+            let between = SQUARE_BITBOARDS[single_push as usize];
+            return from_rank == start_rank
+                && mv.to as isize == single_push + push_offset
+                && occupancy & (between | to_bitboard) == 0;
+        }
+
+        mv.to as isize == single_push && occupancy & to_bitboard == 0
+    }
+
+    // Checks a single move without generating the move list, using the
+    // cached checkers and the pins against the side to move's king
+    pub fn is_legal(&self, mv: ChessMove) -> bool {
+        if mv.is_null() || !self.is_pseudo_legal(mv) {
+            return false;
+        }
+
+        // Castling through or out of check is already rejected above
+        if mv.is_king_castling() || mv.is_queen_castling() {
+            return true;
+        }
+
+        let side = self.get_active_side();
+        let enemy_pieces = self.get_side_occupancy(self.get_opponent());
+        let from_bitboard = SQUARE_BITBOARDS[mv.from as usize];
+        let to_bitboard = SQUARE_BITBOARDS[mv.to as usize];
+
+        if mv.piece == Piece::King {
+            let occupancy = self.get_full_occupancy() ^ from_bitboard;
+            return self.attackers_to(mv.to, occupancy) & enemy_pieces & !to_bitboard == 0;
+        }
+
+        let king_square = self.get_king_square(side);
+
+        // Removing two pawns from the same rank can uncover a slider, so the
+        // position after the capture is checked directly
+        if mv.is_en_passant() {
+            let captured = SQUARE_BITBOARDS[mv.to.forward(side.opponent()).unwrap() as usize];
+            let occupancy = (self.get_full_occupancy() ^ from_bitboard ^ captured) | to_bitboard;
+            return self.attackers_to(king_square, occupancy) & enemy_pieces & !captured == 0;
+        }
+
+        let checkers = self.checkers();
+        if checkers.more_than_one() {
+            return false;
+        }
+        if let Some(checker) = checkers.lsb() {
+            let evasions = BETWEEN_BITBOARDS[king_square as usize][checker as usize] | checkers;
+            if evasions & to_bitboard == 0 {
+                return false;
+            }
+        }
+
+        // A pinned piece may only move along the line through its king
+        self.pinned(side) & from_bitboard == 0
+            || LINE_BITBOARDS[king_square as usize][mv.from as usize] & to_bitboard != 0
+    }
+
+    fn is_castling_pseudo_legal(&self, mv: ChessMove) -> bool {
+        let (right, king_from, path, rook_square) = match (mv.to, mv.is_king_castling()) {
+            (Square::G1, true) => (Castling::WhiteKing, Square::E1,
+                [Square::F1, Square::G1, Square::G1], Square::H1),
+            (Square::C1, false) => (Castling::WhiteQueen, Square::E1,
+                [Square::D1, Square::C1, Square::B1], Square::A1),
+            (Square::G8, true) => (Castling::BlackKing, Square::E8,
+                [Square::F8, Square::G8, Square::G8], Square::H8),
+            (Square::C8, false) => (Castling::BlackQueen, Square::E8,
+                [Square::D8, Square::C8, Square::B8], Square::A8),
+            _ => return false,
+        };
+
+        if self.game_state.castling & right as u8 == 0 || mv.from != king_from {
+            return false;
+        }
+
+        let own_rooks = self.get_pieces(self.get_active_side(), Piece::Rook);
+        if own_rooks & SQUARE_BITBOARDS[rook_square as usize] == 0 {
+            return false;
+        }
+
+        let occupancy = self.get_full_occupancy();
+        if path.iter().any(|square| occupancy & SQUARE_BITBOARDS[*square as usize] != 0) {
+            return false;
+        }
+
+        // The king may not castle out of, through, or into check
+        let enemy_pieces = self.get_side_occupancy(self.get_opponent());
+        [king_from, path[0], path[1]].iter()
+            .all(|square| self.attackers_to(*square, occupancy) & enemy_pieces == 0)
+    }
+
+    pub fn get_ep_square(&self) -> Option<Square> {
+        match self.game_state.en_passant {
+            Some(square) => Some(Square::try_from(square as usize).unwrap()),
+            None => None,
+        }
+    }
+
+    pub fn init(&mut self) {
+        let pieces_per_side_bitboards = self.init_pieces_per_side_bitboards();
+        self.sides[Side::White as usize] = pieces_per_side_bitboards.0;
+        self.sides[Side::Black as usize] = pieces_per_side_bitboards.1;
+
+
+        self.init_piece_list();
+        self.init_material();
+        self.init_pst_scores();
+        self.init_zobrist_key();
+        self.game_state.checkers = self.compute_checkers();
+    }
+
+    pub fn try_from_fen(fen: &str) -> Result<Self, FenError> {
+        let mut board = Board::new();
+        board.from_fen(Some(fen))?;
+        Ok(board)
+    }
+
+    pub fn to_fen(&self) -> String {
+        write_fen(self)
+    }
+
+    pub fn from_fen(&mut self, fen: Option<&str>) -> Result<(), FenError> {
+        let fen_string = fen.unwrap_or(FEN_STARTING_POSITION);
+
+        self.reset();
+
+        let mut fen_parser = FenParser::new(
+                                            fen_string.to_string(),
+                                            self);
+
+        fen_parser.parse()?;
+
+
+        self.init();
+
+        self.validate()
+    }
+
+    // Rejects positions that cannot arise in a legal game
+    pub fn validate(&self) -> Result<(), FenError> {
+        for side in [Side::White, Side::Black] {
+            let kings = self.get_pieces(side, Piece::King).popcount();
+            if kings != 1 {
+                return Err(FenError::KingCountError(format!(
+                    "{side:?} has {kings} kings, expected 1"
+                )));
+            }
+        }
+
+        let back_ranks = RANK_BITBOARDS[Rank::R1 as usize] | RANK_BITBOARDS[Rank::R8 as usize];
+        let pawns = self.get_pieces(Side::White, Piece::Pawn) | self.get_pieces(Side::Black, Piece::Pawn);
+        if let Some(square) = (pawns & back_ranks).lsb() {
+            return Err(FenError::PawnOnBackRankError(format!(
+                "Pawn on back rank square {square}"
+            )));
+        }
+
+        let opponent = self.get_opponent();
+        let opponent_king = self.get_king_square(opponent);
+        if self.attackers_to(opponent_king, self.get_full_occupancy())
+                & self.get_side_occupancy(self.get_active_side()) != 0 {
+            return Err(FenError::OpponentInCheckError);
+        }
+
+        self.validate_en_passant()?;
+        self.validate_castling()
+    }
+
+    fn validate_en_passant(&self) -> Result<(), FenError> {
+        let ep_square = match self.get_ep_square() {
+            Some(square) => square,
+            None => return Ok(()),
+        };
+
+        let expected_rank = match self.get_active_side() {
+            Side::White => Rank::R6,
+            Side::Black => Rank::R3,
+        };
+
+        // Checked first: off that rank the squares below may be off the board
+        if RANK_BITBOARDS[expected_rank as usize] & SQUARE_BITBOARDS[ep_square as usize] == 0 {
+            return Err(FenError::EnPassantStateError(format!(
+                "En passant square {ep_square} is on the wrong rank for the side to move"
+            )));
+        }
+
+        // The double-pushed pawn sits one rank behind the ep square from the
+        // mover's point of view, and the two squares it passed are empty
+        let (pawn_square, origin_square) = match self.get_active_side() {
+            Side::White => (ep_square as usize - 8, ep_square as usize + 8),
+            Side::Black => (ep_square as usize + 8, ep_square as usize - 8),
+        };
+
+        let full_occupancy = self.get_full_occupancy();
+        if (SQUARE_BITBOARDS[ep_square as usize] | SQUARE_BITBOARDS[origin_square]) & full_occupancy != 0 {
+            return Err(FenError::EnPassantStateError(format!(
+                "En passant square {ep_square} or the pawn's origin square is occupied"
+            )));
+        }
+
+        if self.get_pieces(self.get_opponent(), Piece::Pawn) & SQUARE_BITBOARDS[pawn_square] == 0 {
+            return Err(FenError::EnPassantStateError(format!(
+                "No double-pushed pawn in front of en passant square {ep_square}"
+            )));
+        }
+
+        Ok(())
+    }
+
+    fn validate_castling(&self) -> Result<(), FenError> {
+        const CASTLING_SQUARES: [(u8, &str, Side, Square, Square); 4] = [
+            (Castling::WhiteKing as u8, "K", Side::White, Square::E1, Square::H1),
+            (Castling::WhiteQueen as u8, "Q", Side::White, Square::E1, Square::A1),
+            (Castling::BlackKing as u8, "k", Side::Black, Square::E8, Square::H8),
+            (Castling::BlackQueen as u8, "q", Side::Black, Square::E8, Square::A8),
+        ];
+
+        for (right, name, side, king_square, rook_square) in CASTLING_SQUARES {
+            if self.game_state.castling & right == 0 {
+                continue;
+            }
+
+            if self.get_pieces(side, Piece::King) & SQUARE_BITBOARDS[king_square as usize] == 0 {
+                return Err(FenError::CastlingStateError(format!(
+                    "Castling right {name} without a king on {king_square}"
+                )));
+            }
+
+            if self.get_pieces(side, Piece::Rook) & SQUARE_BITBOARDS[rook_square as usize] == 0 {
+                return Err(FenError::CastlingStateError(format!(
+                    "Castling right {name} without a rook on {rook_square}"
+                )));
+            }
+        }
+
+        Ok(())
+    }
+
+
+    // Ranks are mirrored but colors kept. Castling and en passant rights no
+    // longer make sense on the mirrored board, so they are dropped
+    pub fn mirrored_vertical(&self) -> Board {
+        self.transformed(flip_vertical, false, 0, None)
+    }
+
+    // Files are mirrored; the kings leave the e-file, so castling is dropped
+    pub fn flipped_horizontal(&self) -> Board {
+        let ep_square = self.get_ep_square()
+            .map(|square| Square::try_from(square as usize ^ 7).unwrap());
+        self.transformed(flip_horizontal, false, 0, ep_square)
+    }
+
+    // Ranks are mirrored and colors exchanged, giving the same position seen
+    // from the other side: an evaluator should return the negated score
+    pub fn color_swapped(&self) -> Board {
+        let castling = self.game_state.castling;
+        let swapped_castling = ((castling & 0b0011) << 2) | (castling >> 2);
+        let ep_square = self.get_ep_square()
+            .map(|square| square.flip_vertical());
+        self.transformed(flip_vertical, true, swapped_castling, ep_square)
+    }
+
+    fn transformed(&self, flip: fn(Bitboard) -> Bitboard, swap_colors: bool,
+        castling: u8, ep_square: Option<Square>) -> Board {
+        let mut board = Board::new();
+        board.zobrist_keys = Arc::clone(&self.zobrist_keys);
+
+        for side in [Side::White, Side::Black] {
+            let target_side = match swap_colors {
+                true => side.opponent() as usize,
+                false => side as usize,
+            };
+            for (piece, bitboard) in self.get_bitboards(side).iter().enumerate() {
+                board.pieces[target_side][piece] = flip(*bitboard);
+            }
+        }
+
+        board.game_state = self.game_state;
+        board.game_state.active_side = match swap_colors {
+            true => self.get_opponent(),
+            false => self.get_active_side(),
+        };
+        board.game_state.castling = castling;
+        board.game_state.en_passant = ep_square.map(|square| square as u8);
+
+        board.init();
+        board
+    }
+
+    fn init_pieces_per_side_bitboards(&self) -> (Bitboard, Bitboard) {
+        let mut bitboard_white: Bitboard = 0;
+        let mut bitboard_black: Bitboard = 0;
+
+        for (bb_w, bb_b) in self.pieces[Side::White as usize]
+            .iter()
+            .zip(self.pieces[Side::Black as usize].iter())
+        {
+            bitboard_white |= *bb_w;
+            bitboard_black |= *bb_b;
+        }
+
+        (bitboard_white, bitboard_black)
+    }
+
+
+    fn init_material(&mut self) {
+        self.phase = 0;
+
+        for side in [Side::White, Side::Black] {
+            let mut material = 0;
+            for (piece, bitboard) in self.pieces[side as usize].iter().enumerate() {
+                material += PIECE_VALUES[piece] * bitboard.popcount() as i32;
+                self.phase += PHASE_WEIGHTS[piece] * bitboard.popcount() as i32;
+            }
+
+            let pawns = self.pieces[side as usize][Piece::Pawn as usize].popcount() as i32;
+            self.material[side as usize] = material;
+            self.non_pawn_material[side as usize] = material - pawns * PIECE_VALUES[Piece::Pawn as usize];
+        }
+    }
+
+
+    fn init_pst_scores(&mut self) {
+        self.game_state.pst_mg = 0;
+        self.game_state.pst_eg = 0;
+
+        for side in [Side::White, Side::Black] {
+            for (piece_type, bitboard) in self.pieces[side as usize].into_iter().enumerate() {
+                let piece = Piece::try_from(piece_type).unwrap();
+                for square in bitboard.squares() {
+                    self.update_pst_scores(side, piece, square, 1);
+                }
+            }
+        }
+    }
+
+    fn update_pst_scores(&mut self, side: Side, piece: Piece, square: Square, sign: i32) {
+        let (mg, eg) = piece_square_values(side, piece, square);
+        let sign = match side {
+            Side::White => sign,
+            Side::Black => -sign,
+        };
+        self.game_state.pst_mg += sign * mg;
+        self.game_state.pst_eg += sign * eg;
+    }
+
+
+    fn init_zobrist_key(&mut self) {
+
+        self.game_state.zobrist_key = 0;
+        self.game_state.pawn_key = 0;
+
+        let bitboards_white: &[Bitboard] = &self.pieces[Side::White as usize];
+        let bitboards_black: &[Bitboard] = &self.pieces[Side::Black as usize];
+
+
+        for (piece_type, (white, black)) in bitboards_white
+            .iter()
+            .zip(bitboards_black.iter()).enumerate() {
+            // Assume the first iteration; piece_type will be 0 (KING). The
+            // following two statements will thus get all the pieces of
+            // type "KING" for white and black. (This will obviously only
+            // be one king, but with rooks, there will be two in the
+            // starting position.)
+            let piece = Piece::try_from(piece_type).unwrap();
+
+            // Iterate through all the piece locations of the current piece
+            // type. Get the square the piece is on, and then hash that
+            // square/piece combination into the zobrist key.
+            for square in white.squares() {
+                self.game_state.zobrist_key ^= self.zobrist_keys
+                                                    .piece(Side::White, piece, square);
+            }
+
+            for square in black.squares() {
+                self.game_state.zobrist_key ^= self.zobrist_keys
+                                                    .piece(Side::Black, piece, square);
+            }
+
+            if piece == Piece::Pawn || piece == Piece::King {
+                for square in white.squares() {
+                    self.game_state.pawn_key ^= self.zobrist_keys
+                                                    .piece(Side::White, piece, square);
+                }
+
+                for square in black.squares() {
+                    self.game_state.pawn_key ^= self.zobrist_keys
+                                                    .piece(Side::Black, piece, square);
+                }
+            }
+        }
+
+        // Hash the castling, active color, and en-passant state into the key.
+        self.game_state.zobrist_key ^= self.zobrist_keys
+                                        .castling(self.game_state.castling);
+        self.game_state.zobrist_key ^= self.zobrist_keys
+                                        .side(self.game_state.active_side);
+        self.game_state.zobrist_key ^= self.zobrist_keys
+                                        .en_passant(self.game_state.en_passant);
+    }
+
+
+
+    fn init_piece_list(&mut self) {
+        let bitboards_white: &[Bitboard] = &self.pieces[Side::White as usize];
+        let bitboards_black: &[Bitboard] = &self.pieces[Side::Black as usize];
+
+        self.piece_list = [Piece::None; NrOf::SQUARES];
+
+        for (piece_type, (white, black)) 
+            in bitboards_white.iter().zip(bitboards_black.iter()).enumerate() {
+
+            for square in (white | black).squares() {
+                self.piece_list[square as usize] = Piece::try_from(piece_type).unwrap();
+            }
+        }
+    }
+
+    pub fn make_move(&mut self, chess_move: ChessMove) {
+        let prev_state = self.game_state.clone();
+        let mut captured_piece = Piece::None;
+
+        if chess_move.is_quiet() {
+            let piece = self.piece_list[chess_move.from as usize];
+            if piece != Piece::Pawn {
+                self.game_state.half_move_clock += 1;
+            } else {
+                self.game_state.half_move_clock = 0;
+            }
+            self.move_piece(self.get_active_side(),
+                     self.piece_list[chess_move.from as usize],
+                     chess_move.from, chess_move.to);
+            self.clear_ep_square();
+
+        } else if chess_move.is_double_pawn_push() {
+            self.move_piece(self.get_active_side(),
+                self.piece_list[chess_move.from as usize],
+                chess_move.from, chess_move.to);
+
+            self.set_ep_square(chess_move.to.forward(self.get_opponent()).unwrap());
+
+            self.game_state.half_move_clock = 0;
+
+        } else if chess_move.is_king_castling() || chess_move.is_queen_castling() {
+
+            let (rook_pos, rook_dest) = match chess_move.to {
+                Square::G1 => (Square::H1, Square::F1),
+                Square::C1 => (Square::A1, Square::D1),
+                Square::G8 => (Square::H8, Square::F8),
+                Square::C8 => (Square::A8, Square::D8),
+                _ => unreachable!()    
+            };
+
+            // Move the king
+            self.move_piece(self.get_active_side(),
+                self.piece_list[chess_move.from as usize],
+                chess_move.from, chess_move.to);
+
+            // Move the rook
+            self.move_piece(self.get_active_side(),
+                self.piece_list[rook_pos as usize],
+                rook_pos, rook_dest);
+
+            self.game_state.half_move_clock += 1;
+            self.clear_ep_square();
+
+        } else if chess_move.is_capture() {
+            captured_piece = self.piece_list[chess_move.to as usize];
+
+            match chess_move.is_en_passant() {
+                true => {
+                    let ep_square = self.game_state.en_passant.unwrap() as usize;
+                    let piece_square = match self.get_active_side() {
+                        Side::White => ep_square - 8,
+                        Side::Black => ep_square + 8,
+                    };
+
+                    captured_piece = self.piece_list[piece_square];
+                    self.remove_piece(self.get_opponent(),
+                    captured_piece, Square::try_from(piece_square).unwrap());
+                },
+                false => {
+                    self.remove_piece(self.get_opponent(),
+                    captured_piece, chess_move.to);
+                }
+            }
+            
+            match chess_move.is_promotion() {
+                true => {
+                    self.remove_piece(self.get_active_side(),
+                    self.piece_list[chess_move.from as usize],
+                    chess_move.from);
+                    self.place_piece(self.get_active_side(),
+                    chess_move.promotion.unwrap(), chess_move.to);
+                },
+                false => {
+                    self.move_piece(self.get_active_side(),
+                self.piece_list[chess_move.from as usize],
+                    chess_move.from, chess_move.to);
+                }
+            }
+
+            self.game_state.half_move_clock = 0;
+            self.clear_ep_square();
+
+        } else if chess_move.is_promotion() {
+            self.remove_piece(self.get_active_side(),
+                self.piece_list[chess_move.from as usize],
+                chess_move.from);
+            self.place_piece(self.get_active_side(),
+                chess_move.promotion.unwrap(), chess_move.to);
+            self.game_state.half_move_clock = 0;
+            self.clear_ep_square();
+        } 
+
+        if self.get_active_side() == Side::Black {
+            self.game_state.full_move_number += 1;
+        }
+        
+        let captured = if captured_piece != Piece::None {
+            let mut captured_square = chess_move.to;
+            if chess_move.is_en_passant() {
+                captured_square = chess_move.to.forward(self.get_opponent()).unwrap();
+            }
+            Some((captured_piece, self.get_opponent(), captured_square))
+        } else {
+            None
+        };
+
+        // Moving from or to a king or rook start square drops the rights tied to it
+        let new_rights = self.game_state.castling
+            & CASTLING_PERMS[chess_move.from as usize]
+            & CASTLING_PERMS[chess_move.to as usize];
+        if new_rights != self.game_state.castling {
+            self.set_castling_rights(new_rights);
+        }
+
+        self.game_history.push(
+            RecordedMove::new(chess_move, prev_state, captured));
+        self.switch_active_side();
+        self.game_state.checkers = self.compute_checkers();
+    }
+
+    // Plays a move given in coordinate notation if it is legal here
+    pub fn push_uci(&mut self, move_generator: &MoveGenerator,
+        uci: &str) -> Result<ChessMove, NotationError> {
+        let mv = notation::move_from_uci(self, move_generator, uci)?;
+        self.make_move(mv);
+        Ok(mv)
+    }
+
+    // Plays a move on a copy of the position and leaves this board untouched.
+    // The copy's history holds only that move, so it is cheap to create for
+    // each node or thread, but it cannot see repetitions before the move
+    pub fn make_move_copy(&self, chess_move: ChessMove) -> Board {
+        let mut board = Board {
+            sides: self.sides,
+            pieces: self.pieces,
+            piece_list: self.piece_list,
+            game_state: self.game_state,
+            game_history: GameHistory::with_capacity(1),
+            zobrist_keys: Arc::clone(&self.zobrist_keys),
+            prior_positions: Vec::new(),
+            material: self.material,
+            non_pawn_material: self.non_pawn_material,
+            phase: self.phase,
+        };
+        board.make_move(chess_move);
+        board
+    }
+
+    pub fn make_null_move(&mut self) {
+        let prev_state = self.game_state;
+
+        self.clear_ep_square();
+        self.game_state.half_move_clock += 1;
+
+        if self.get_active_side() == Side::Black {
+            self.game_state.full_move_number += 1;
+        }
+
+        self.game_history.push(
+            RecordedMove::new(ChessMove::null(), prev_state, None));
+        self.switch_active_side();
+        self.game_state.checkers = self.compute_checkers();
+    }
+
+    pub fn undo_null_move(&mut self) {
+        if let Some(last_move) = self.game_history.pop() {
+            debug_assert!(last_move.mv.is_null());
+            self.game_state = last_move.prev_state;
+        }
+    }
+
+    pub fn undo_move(&mut self) {
+        if let Some(last_move) = self.game_history.pop() {
+            let prev_state = last_move.prev_state;
+
+            // Nothing moved on the board, restoring the state is enough
+            if last_move.mv.is_null() {
+                self.game_state = prev_state;
+                return;
+            }
+
+            let prev_moved_piece = self.piece_list[last_move.mv.to as usize];
+
+            if last_move.mv.is_promotion() {
+                self.remove_piece(prev_state.active_side, prev_moved_piece, last_move.mv.to);
+                self.place_piece(prev_state.active_side, Piece::Pawn, last_move.mv.from);
+            } else {
+                self.move_piece(prev_state.active_side,
+                    prev_moved_piece, last_move.mv.to, last_move.mv.from);
+            }
+
+            if last_move.mv.is_queen_castling() || last_move.mv.is_king_castling() {
+                let (rook_pos, rook_dest) = match last_move.mv.to {
+                    Square::G1 => (Square::H1, Square::F1),
+                    Square::C1 => (Square::A1, Square::D1),
+                    Square::G8 => (Square::H8, Square::F8),
+                    Square::C8 => (Square::A8, Square::D8),
+                    _ => unreachable!()
+                };
+                self.move_piece(prev_state.active_side,
+                    self.piece_list[rook_dest as usize], rook_dest, rook_pos);
+            }
+            if let Some((piece, side, square)) = last_move.captured_piece {
+                self.place_piece(side, piece, square);
+            }
+            self.game_state = prev_state;
+        }
+    }
+
+    pub fn remove_piece(&mut self, side: Side, piece: Piece, square: Square) {
+        self.pieces[side as usize][piece as usize] ^= SQUARE_BITBOARDS[square as usize];
+        self.sides[side as usize] ^= SQUARE_BITBOARDS[square as usize];
+        self.piece_list[square as usize] = Piece::None;
+        self.game_state.zobrist_key ^= self.zobrist_keys
+            .piece(side, piece, square);
+        if piece == Piece::Pawn || piece == Piece::King {
+            self.game_state.pawn_key ^= self.zobrist_keys
+                .piece(side, piece, square);
+        }
+
+        self.update_pst_scores(side, piece, square, -1);
+
+        self.material[side as usize] -= PIECE_VALUES[piece as usize];
+        self.phase -= PHASE_WEIGHTS[piece as usize];
+        if piece != Piece::Pawn {
+            self.non_pawn_material[side as usize] -= PIECE_VALUES[piece as usize];
+        }
+    }
+
+    pub fn place_piece(&mut self, side: Side, piece: Piece, square: Square) {
+        self.pieces[side as usize][piece as usize] |= SQUARE_BITBOARDS[square as usize];
+        self.sides[side as usize] |= SQUARE_BITBOARDS[square as usize];
+        self.piece_list[square as usize] = piece;
+        self.game_state.zobrist_key ^= self.zobrist_keys
+            .piece(side, piece, square);
+        if piece == Piece::Pawn || piece == Piece::King {
+            self.game_state.pawn_key ^= self.zobrist_keys
+                .piece(side, piece, square);
+        }
+
+        self.update_pst_scores(side, piece, square, 1);
+
+        self.material[side as usize] += PIECE_VALUES[piece as usize];
+        self.phase += PHASE_WEIGHTS[piece as usize];
+        if piece != Piece::Pawn {
+            self.non_pawn_material[side as usize] += PIECE_VALUES[piece as usize];
+        }
+    }
+
+    pub fn move_piece(&mut self, side: Side, piece: Piece, from: Square, to: Square) {
+        self.remove_piece(side, piece, from);
+        self.place_piece(side, piece, to);
+    }
+
+    pub fn set_ep_square(&mut self, square: Square) {
+        self.game_state.zobrist_key ^= self.zobrist_keys
+                .en_passant(self.game_state.en_passant);
+
+        self.game_state.en_passant = Some(square as u8);
+
+        self.game_state.zobrist_key ^= self.zobrist_keys
+                .en_passant(self.game_state.en_passant);
+    }
+
+    pub fn clear_ep_square(&mut self) {
+        self.game_state.zobrist_key ^= self.zobrist_keys
+                .en_passant(self.game_state.en_passant);
+
+        self.game_state.en_passant = None;
+
+        self.game_state.zobrist_key ^= self.zobrist_keys
+                .en_passant(self.game_state.en_passant);
+    }
+
+    pub fn switch_active_side(&mut self) {
+        self.game_state.zobrist_key ^= self.zobrist_keys
+                .side(self.game_state.active_side);
+
+        self.game_state.active_side = self.get_opponent();
+
+        self.game_state.zobrist_key ^= self.zobrist_keys
+                .side(self.game_state.active_side);
+    }
+
+    pub fn set_castling_rights(&mut self, new_rights: u8) {
+        self.game_state.zobrist_key ^= self.zobrist_keys
+                                        .castling(self.game_state.castling);
+        self.game_state.castling = new_rights;
+        self.game_state.zobrist_key ^= self.zobrist_keys
+                                        .castling(self.game_state.castling);
+    }
+
+    pub fn game_result(&mut self, move_generator: &MoveGenerator) -> GameResult {
+        self.game_result_with_rules(move_generator, DrawRules::Claimable)
+    }
+
+    pub fn game_result_with_rules(&mut self, move_generator: &MoveGenerator,
+        rules: DrawRules) -> GameResult {
+        // Mate and stalemate take precedence over the move-count draws
+        if !move_generator.exist_legal_moves(self) {
+            return match self.in_check() {
+                false => GameResult::Stalemate,
+                true => GameResult::Checkmate(self.get_opponent()),
+            };
+        }
+
+        let (repetition, move_rule) = self.repetition_and_move_rule(rules);
+
+        if self.draw_by_insufficient_material() {
+            GameResult::DrawInsufficientMaterial
+        } else if repetition {
+            GameResult::DrawRepetition
+        } else if move_rule {
+            GameResult::DrawFiftyMove
+        } else {
+            GameResult::Ongoing
+        }
+    }
+
+    // Draw by rule, without the mate/stalemate check that needs move generation
+    pub fn draw_by_rules(&self, rules: DrawRules) -> bool {
+        let (repetition, move_rule) = self.repetition_and_move_rule(rules);
+        repetition || move_rule || self.draw_by_insufficient_material()
+    }
+
+    fn repetition_and_move_rule(&self, rules: DrawRules) -> (bool, bool) {
+        match rules {
+            DrawRules::Claimable => (self.draw_by_threefold_repetition(),
+                self.draw_by_fifty_move_rule()),
+            DrawRules::Automatic => (self.draw_by_fivefold_repetition(),
+                self.draw_by_seventyfive_move_rule()),
+        }
+    }
+
+    // The moves played on this board as SAN movetext, e.g. "1. e4 e5 2. Nf3"
+    pub fn movetext(&self, move_generator: &MoveGenerator) -> String {
+        let mut board = self.clone();
+        while !board.game_history.is_empty() {
+            board.undo_move();
+        }
+
+        let mut movetext = String::new();
+        for (index, (mv, state)) in self.game_history.moves().enumerate() {
+            if state.active_side == Side::White {
+                movetext.push_str(&format!("{}. ", state.full_move_number));
+            } else if index == 0 {
+                movetext.push_str(&format!("{}... ", state.full_move_number));
+            }
+
+            if mv.is_null() {
+                movetext.push_str("--");
+                board.make_null_move();
+            } else {
+                movetext.push_str(&notation::san(&mut board, move_generator, mv));
+                board.make_move(mv);
+            }
+            movetext.push(' ');
+        }
+
+        movetext.trim_end().to_string()
+    }
+
+    pub fn draw_by_fifty_move_rule(&self) -> bool {
+        self.game_state.half_move_clock >= HALF_MOVE_MAX
+    }
+
+    // Positions that occurred before the board was set up, oldest first, e.g.
+    // the game a GUI played up to the search root
+    pub fn set_prior_positions(&mut self, keys: &[ZobristKey]) {
+        self.prior_positions.clear();
+        self.prior_positions.extend_from_slice(keys);
+    }
+
+    pub fn draw_by_seventyfive_move_rule(&self) -> bool {
+        self.game_state.half_move_clock >= HALF_MOVE_AUTOMATIC_DRAW
+    }
+
+    pub fn draw_by_threefold_repetition(&self) -> bool {
+        self.repetition_count() >= 2
+    }
+
+    pub fn draw_by_fivefold_repetition(&self) -> bool {
+        self.repetition_count() >= 4
+    }
+
+    // Earlier occurrences of the current position since the last irreversible move
+    fn repetition_count(&self) -> usize {
+        let mut count = 0;
+        for recorded_move in self.game_history.iter().rev() {
+            let previous_state = recorded_move.prev_state;
+            if previous_state.zobrist_key == self.game_state.zobrist_key {
+                count += 1;
+            }
+
+            if previous_state.half_move_clock == 0 {
+                return count;
+            }
+        }
+
+        // No irreversible move since the start of the history; only the
+        // prior positions within the starting half-move clock can repeat
+        let start_clock = match self.game_history.iter().next() {
+            Some(first_move) => first_move.prev_state.half_move_clock,
+            None => self.game_state.half_move_clock,
+        };
+        count += self.prior_positions.iter().rev()
+            .take(start_clock as usize)
+            .filter(|key| **key == self.game_state.zobrist_key)
+            .count();
+
+        count
+    }
+
+    pub fn draw_by_insufficient_material(&self) -> bool {
+        self.draw_by_insufficient_material_with(InsufficientMaterialRules::Fide)
+    }
+
+    pub fn draw_by_insufficient_material_with(&self, rules: InsufficientMaterialRules) -> bool {
+        let white = self.get_bitboards(Side::White);
+        let black = self.get_bitboards(Side::Black);
+
+        // Check for mating material: queens, rooks and pawns
+        let has_mating_material = [Piece::Queen, Piece::Rook, Piece::Pawn].iter()
+            .any(|piece| white[*piece as usize] | black[*piece as usize] != 0);
+
+        if has_mating_material {
+            return false;
+        }
+
+        let white_knights = white[Piece::Knight as usize].popcount();
+        let black_knights = black[Piece::Knight as usize].popcount();
+        let white_minors = white_knights + white[Piece::Bishop as usize].popcount();
+        let black_minors = black_knights + black[Piece::Bishop as usize].popcount();
+
+        // King vs. King, or a single minor piece on the board
+        if white_minors + black_minors <= 1 {
+            return true;
+        }
+
+        // Only bishops left, all on squares of one color
+        let bishops = white[Piece::Bishop as usize] | black[Piece::Bishop as usize];
+        if white_knights + black_knights == 0
+            && (bishops & LIGHT_SQUARES == 0 || bishops & DARK_SQUARES == 0) {
+            return true;
+        }
+
+        // Two knights cannot force mate against a bare king
+        match rules {
+            InsufficientMaterialRules::Fide => false,
+            InsufficientMaterialRules::Uscf => {
+                (white_knights == 2 && white_minors == 2 && black_minors == 0)
+                    || (black_knights == 2 && black_minors == 2 && white_minors == 0)
+            }
+        }
+    }
+}
+
+
+impl FromStr for Board {
+    type Err = FenError;
+
+    fn from_str(fen: &str) -> Result<Self, Self::Err> {
+        Board::try_from_fen(fen)
+    }
+}
+
+
+impl Display for Board {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        writeln!(f, "Board Position:")?;
+        for rank in (0..8).rev() {
+            write!(f, "{} ", rank + 1)?;
+            for file in 0..8 {
+                let square_index = rank * 8 + file;
+                let piece = self.piece_list[square_index];
+                let symbol = match piece {
+                    Piece::Pawn => {
+                        if self.sides[Side::White as usize] & (1 << square_index) != 0 {
+                            '♟'
+                        } else {
+                            '♙'
+                        }
+                    }
+                    Piece::Knight => {
+                        if self.sides[Side::White as usize] & (1 << square_index) != 0 {
+                            '♞'
+                        } else {
+                            '♘'
+                        }
+                    }
+                    Piece::Bishop => {
+                        if self.sides[Side::White as usize] & (1 << square_index) != 0 {
+                            '♝'
+                        } else {
+                            '♗'
+                        }
+                    }
+                    Piece::Rook => {
+                        if self.sides[Side::White as usize] & (1 << square_index) != 0 {
+                            '♜'
+                        } else {
+                            '♖'
+                        }
+                    }
+                    Piece::Queen => {
+                        if self.sides[Side::White as usize] & (1 << square_index) != 0 {
+                            '♛'
+                        } else {
+                            '♕'
+                        }
+                    }
+                    Piece::King => {
+                        if self.sides[Side::White as usize] & (1 << square_index) != 0 {
+                            '♚'
+                        } else {
+                            '♔'
+                        }
+                    }
+                    Piece::None => {
+                        if (rank + file) % 2 == 0 {
+                            '░'
+                        } else {
+                            '▓'
+                        }
+                    }
+                };
+                write!(f, "{} ", symbol)?;
+            }
+            writeln!(f)?;
+        }
+
+        writeln!(f, "  a b c d e f g h")?;
+
+        writeln!(f, "\nWhite occupancy:")?;
+        for rank in (0..8).rev() {
+            for file in 0..8 {
+                let square_index = rank * 8 + file;
+                if self.sides[Side::White as usize] & (1 << square_index) != 0 {
+                    write!(f, "⬜")?;
+                } else {
+                    write!(f, "⬛")?;
+                }
+            }
+            writeln!(f)?;
+        }
+
+        writeln!(f, "\nBlack occupancy:")?;
+        for rank in (0..8).rev() {
+            for file in 0..8 {
+                let square_index = rank * 8 + file;
+                if self.sides[Side::Black as usize] & (1 << square_index) != 0 {
+                    write!(f, "⬜")?;
+                } else {
+                    write!(f, "⬛")?;
+                }
+            }
+            writeln!(f)?;
+        }
+
+        writeln!(f, "\nPiece list:")?;
+        for rank in (0..8).rev() {
+            for file in 0..8 {
+                let square_index = rank * 8 + file;
+                let piece = self.piece_list[square_index];
+                if piece != Piece::None {
+                    writeln!(f, "Rank {}, file {}: {:?}", rank + 1, file + 1, piece)?;
+                }
+            }
+        }
+        writeln!(f, "\nSide to move: {:?}", self.get_active_side())?;
+        writeln!(f, "Castling rights: {:?}", self.game_state.castling)?;
+        writeln!(f, "En passant square: {:?}", self.game_state.en_passant)?;
+        writeln!(f, "Halfmove clock: {}", self.game_state.half_move_clock)?;
+        writeln!(f, "Fullmove number: {}", self.game_state.full_move_number)?;
+        writeln!(f, "Zobrist key: {:016x}", self.game_state.zobrist_key)?;
+
+        Ok(())
+    }
+}
\ No newline at end of file
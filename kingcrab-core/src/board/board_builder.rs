@@ -0,0 +1,104 @@
+use crate::definitions::{Bitboard, NrOf, Piece, Side, Square, SQUARE_BITBOARDS};
+use super::{board::Board, fen::FenError};
+
+
+#[derive(Clone, Copy)]
+pub struct BoardBuilder {
+    pieces: [[Bitboard; NrOf::PIECE_TYPES]; NrOf::SIDES],
+    active_side: Side,
+    castling: u8,
+    en_passant: Option<Square>,
+    half_move_clock: u8,
+    full_move_number: u16,
+}
+
+
+impl BoardBuilder {
+    pub fn new() -> Self {
+        BoardBuilder {
+            pieces: [[0; NrOf::PIECE_TYPES]; NrOf::SIDES],
+            active_side: Side::White,
+            castling: 0,
+            en_passant: None,
+            half_move_clock: 0,
+            full_move_number: 1,
+        }
+    }
+
+    // Starts from an existing position, e.g. for editing it in a GUI
+    pub fn from_board(board: &Board) -> Self {
+        BoardBuilder {
+            pieces: board.pieces,
+            active_side: board.get_active_side(),
+            castling: board.game_state.castling,
+            en_passant: board.get_ep_square(),
+            half_move_clock: board.game_state.half_move_clock,
+            full_move_number: board.game_state.full_move_number,
+        }
+    }
+
+    // Places a piece, replacing whatever stood on the square
+    pub fn piece(mut self, side: Side, piece: Piece, square: Square) -> Self {
+        self = self.clear(square);
+        self.pieces[side as usize][piece as usize] |= SQUARE_BITBOARDS[square as usize];
+        self
+    }
+
+    pub fn clear(mut self, square: Square) -> Self {
+        for side_pieces in self.pieces.iter_mut() {
+            for bitboard in side_pieces.iter_mut() {
+                *bitboard &= !SQUARE_BITBOARDS[square as usize];
+            }
+        }
+        self
+    }
+
+    // Takes a combination of `Castling` flags
+    pub fn castling(mut self, rights: u8) -> Self {
+        self.castling = rights;
+        self
+    }
+
+    pub fn side_to_move(mut self, side: Side) -> Self {
+        self.active_side = side;
+        self
+    }
+
+    pub fn en_passant(mut self, square: Option<Square>) -> Self {
+        self.en_passant = square;
+        self
+    }
+
+    pub fn half_move_clock(mut self, half_move_clock: u8) -> Self {
+        self.half_move_clock = half_move_clock;
+        self
+    }
+
+    pub fn full_move_number(mut self, full_move_number: u16) -> Self {
+        self.full_move_number = full_move_number;
+        self
+    }
+
+    pub fn build(&self) -> Result<Board, FenError> {
+        let mut board = Board::new();
+
+        board.pieces = self.pieces;
+        board.game_state.active_side = self.active_side;
+        board.game_state.castling = self.castling;
+        board.game_state.en_passant = self.en_passant.map(|square| square as u8);
+        board.game_state.half_move_clock = self.half_move_clock;
+        board.game_state.full_move_number = self.full_move_number;
+
+        board.init();
+        board.validate()?;
+
+        Ok(board)
+    }
+}
+
+
+impl Default for BoardBuilder {
+    fn default() -> Self {
+        Self::new()
+    }
+}
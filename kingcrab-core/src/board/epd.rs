@@ -0,0 +1,232 @@
+use std::{fmt::{self, Display}, str::FromStr};
+
+use super::{board::Board, fen::FenError};
+use crate::move_generator::{chess_move::ChessMove,
+    move_generator::MoveGenerator, notation::{self, NotationError}};
+
+
+// EPD keeps the first four FEN fields; the clocks travel as hmvc/fmvn
+const EPD_POSITION_FIELDS: usize = 4;
+
+
+#[derive(Debug)]
+pub enum EpdError {
+    MissingFieldsError,
+    PositionError(FenError),
+    OperationError(String),
+}
+
+impl Display for EpdError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::MissingFieldsError =>
+                    write!(f, "Error in EPD string: Must have at least 4 fields"),
+            Self::PositionError(error) =>
+                    write!(f, "Error in EPD position: {error}"),
+            Self::OperationError(message) =>
+                    write!(f, "Error in EPD operation: {message}"),
+        }
+    }
+}
+
+
+#[derive(Clone)]
+pub struct Epd {
+    pub board: Board,
+    // Opcodes in the order they were read, with unquoted operands
+    pub operations: Vec<(String, Vec<String>)>,
+}
+
+impl Epd {
+    pub fn new(board: &Board) -> Self {
+        let mut board = board.clone();
+        board.game_history.clear();
+        Self {
+            board,
+            operations: Vec::new(),
+        }
+    }
+
+    pub fn get(&self, opcode: &str) -> Option<&[String]> {
+        self.operations.iter()
+            .find(|(name, _)| name == opcode)
+            .map(|(_, operands)| operands.as_slice())
+    }
+
+    pub fn set(&mut self, opcode: &str, operands: &[&str]) {
+        let operands = operands.iter().map(|operand| operand.to_string()).collect();
+        match self.operations.iter_mut().find(|(name, _)| name == opcode) {
+            Some((_, old_operands)) => *old_operands = operands,
+            None => self.operations.push((opcode.to_string(), operands)),
+        }
+    }
+
+    pub fn remove(&mut self, opcode: &str) {
+        self.operations.retain(|(name, _)| name != opcode);
+    }
+
+    pub fn id(&self) -> Option<&str> {
+        self.get("id")
+            .and_then(|operands| operands.first())
+            .map(|id| id.as_str())
+    }
+
+    // Centipawn evaluation from the side to move's point of view
+    pub fn centipawn_eval(&self) -> Option<i32> {
+        self.get("ce")
+            .and_then(|operands| operands.first())
+            .and_then(|ce| ce.parse().ok())
+    }
+
+    pub fn best_moves(&self, move_generator: &MoveGenerator)
+        -> Result<Vec<ChessMove>, NotationError> {
+        self.san_moves("bm", move_generator)
+    }
+
+    pub fn avoid_moves(&self, move_generator: &MoveGenerator)
+        -> Result<Vec<ChessMove>, NotationError> {
+        self.san_moves("am", move_generator)
+    }
+
+    // The pv moves are played one after another from the position
+    pub fn principal_variation(&self, move_generator: &MoveGenerator)
+        -> Result<Vec<ChessMove>, NotationError> {
+        let mut board = self.board.clone();
+        let mut moves = Vec::new();
+        for san in self.get("pv").unwrap_or_default() {
+            let mv = notation::move_from_san(&mut board, move_generator, san)?;
+            board.make_move(mv);
+            moves.push(mv);
+        }
+        Ok(moves)
+    }
+
+    // bm and am list alternatives, all played from the position itself
+    fn san_moves(&self, opcode: &str, move_generator: &MoveGenerator)
+        -> Result<Vec<ChessMove>, NotationError> {
+        let mut board = self.board.clone();
+        self.get(opcode).unwrap_or_default().iter()
+            .map(|san| notation::move_from_san(&mut board, move_generator, san))
+            .collect()
+    }
+
+    fn split_operations(text: &str) -> Result<Vec<(String, Vec<String>)>, EpdError> {
+        let mut operations = Vec::new();
+        let mut words = Vec::new();
+        let mut word = String::new();
+        let mut chars = text.chars();
+        let mut quoted = false;
+
+        while let Some(c) = chars.next() {
+            match c {
+                '"' if quoted => quoted = false,
+                '"' => quoted = true,
+                '\\' if quoted => word.extend(chars.next()),
+                ';' if !quoted => {
+                    if !word.is_empty() {
+                        words.push(std::mem::take(&mut word));
+                    }
+                    if words.is_empty() {
+                        return Err(EpdError::OperationError(
+                            String::from("Empty operation")
+                        ));
+                    }
+                    let opcode = words.remove(0);
+                    operations.push((opcode, std::mem::take(&mut words)));
+                }
+                _ if c.is_whitespace() && !quoted => {
+                    if !word.is_empty() {
+                        words.push(std::mem::take(&mut word));
+                    }
+                }
+                _ => word.push(c),
+            }
+        }
+
+        if quoted {
+            return Err(EpdError::OperationError(
+                String::from("Unterminated string operand")
+            ));
+        }
+        if !word.is_empty() || !words.is_empty() {
+            return Err(EpdError::OperationError(format!(
+                "Operation not terminated by ';': {}", text.trim()
+            )));
+        }
+
+        Ok(operations)
+    }
+}
+
+impl FromStr for Epd {
+    type Err = EpdError;
+
+    fn from_str(epd: &str) -> Result<Self, Self::Err> {
+        let epd = epd.trim();
+        let mut position = Vec::with_capacity(EPD_POSITION_FIELDS);
+        let mut rest = epd;
+        for _ in 0..EPD_POSITION_FIELDS {
+            let field_end = rest.find(char::is_whitespace).unwrap_or(rest.len());
+            if field_end == 0 {
+                return Err(EpdError::MissingFieldsError);
+            }
+            position.push(&rest[..field_end]);
+            rest = rest[field_end..].trim_start();
+        }
+
+        let operations = Epd::split_operations(rest)?;
+
+        let clock = |opcode: &str, default: &str| operations.iter()
+            .find(|(name, _)| name == opcode)
+            .and_then(|(_, operands)| operands.first().cloned())
+            .unwrap_or(default.to_string());
+        let fen = format!("{} {} {}", position.join(" "), clock("hmvc", "0"), clock("fmvn", "1"));
+
+        let board = Board::try_from_fen(&fen).map_err(EpdError::PositionError)?;
+
+        Ok(Epd {
+            board,
+            operations,
+        })
+    }
+}
+
+impl Display for Epd {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let fen = self.board.to_fen();
+        let position: Vec<&str> = fen.split(' ').take(EPD_POSITION_FIELDS).collect();
+        write!(f, "{}", position.join(" "))?;
+
+        for (opcode, operands) in &self.operations {
+            write!(f, " {opcode}")?;
+            for operand in operands {
+                if is_string_opcode(opcode)
+                    || operand.contains(|c: char| c.is_whitespace() || c == ';') {
+                    write!(f, " \"{}\"", operand.replace('\\', "\\\\").replace('"', "\\\""))?;
+                } else {
+                    write!(f, " {operand}")?;
+                }
+            }
+            write!(f, ";")?;
+        }
+
+        Ok(())
+    }
+}
+
+
+// Ids and the c0-c9 comments are strings even without whitespace in them
+fn is_string_opcode(opcode: &str) -> bool {
+    let bytes = opcode.as_bytes();
+    opcode == "id" || (bytes.len() == 2 && bytes[0] == b'c' && bytes[1].is_ascii_digit())
+}
+
+// Reads one record per line, skipping blank lines and # comments, as
+// test suites are usually distributed
+pub fn parse_epd_records(text: &str) -> Result<Vec<Epd>, EpdError> {
+    text.lines()
+        .map(str::trim)
+        .filter(|line| !line.is_empty() && !line.starts_with('#'))
+        .map(Epd::from_str)
+        .collect()
+}
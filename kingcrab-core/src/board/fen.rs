@@ -3,8 +3,8 @@ use std::{fmt::Display, str::FromStr};
 use if_chain::if_chain;
 
 use super::board::Board;
-use crate::engine::definitions::{Castling, File, Piece, Rank, Side, Square,
-    HALF_MOVE_MAX, MAX_GAME_MOVES, SQUARE_BITBOARDS};
+use crate::definitions::{Castling, File, Piece, Rank, Side, Square,
+    HALF_MOVE_AUTOMATIC_DRAW, MAX_GAME_MOVES, SQUARE_BITBOARDS};
 
 
 // Constants for FEN parsing
@@ -41,6 +41,11 @@ pub enum FenError {
     EnPassantPartError(String),
     HalfMovePartError(String),
     FullMovePartError(String),
+    KingCountError(String),
+    PawnOnBackRankError(String),
+    OpponentInCheckError,
+    EnPassantStateError(String),
+    CastlingStateError(String),
 }
 
 impl Display for FenError {
@@ -60,6 +65,16 @@ impl Display for FenError {
                     write!(f, "Error in FEN half-move part: {message}"),
             Self::FullMovePartError(message) =>
                     write!(f, "Error in FEN full-move part: {message}"),
+            Self::KingCountError(message) =>
+                    write!(f, "Illegal position: {message}"),
+            Self::PawnOnBackRankError(message) =>
+                    write!(f, "Illegal position: {message}"),
+            Self::OpponentInCheckError =>
+                    write!(f, "Illegal position: side not to move is in check"),
+            Self::EnPassantStateError(message) =>
+                    write!(f, "Illegal position: {message}"),
+            Self::CastlingStateError(message) =>
+                    write!(f, "Illegal position: {message}"),
         }
     }
 }
@@ -129,6 +144,12 @@ impl<'board_lifetime> FenParser<'board_lifetime> {
         let mut file = File::A as u8;
     
         for c in part.chars() {
+            if PIECE_TYPES.contains(c) && file >= 8 {
+                return Err(FenError::PieceSquarePartError(format!(
+                    "Too many squares on rank {}, expected 8", rank + 1
+                )));
+            }
+
             let square = ((rank * 8) + file) as usize;
             match c {
                 'k' => board.pieces[Side::Black as usize][Piece::King as usize] 
@@ -166,6 +187,11 @@ impl<'board_lifetime> FenParser<'board_lifetime> {
                             "Invalid file count: {file}, expected 8"
                         )));
                     }
+                    if rank == Rank::R1 as u8 {
+                        return Err(FenError::PieceSquarePartError(
+                            String::from("Too many ranks, expected 8")
+                        ));
+                    }
                     rank -= 1;
                     file = 0;
                 }
@@ -177,6 +203,18 @@ impl<'board_lifetime> FenParser<'board_lifetime> {
             if PIECE_TYPES.contains(c) {
                 file += 1;
             }
+
+            if file > 8 {
+                return Err(FenError::PieceSquarePartError(format!(
+                    "Invalid file count: {file}, expected 8"
+                )));
+            }
+        }
+
+        if rank != Rank::R1 as u8 || file != 8 {
+            return Err(FenError::PieceSquarePartError(
+                String::from("Incomplete board, expected 8 ranks of 8 files")
+            ));
         }
     
         Ok(())
@@ -258,7 +296,7 @@ impl<'board_lifetime> FenParser<'board_lifetime> {
         if_chain! {
             if (1..=3).contains(&part.len());
             if let Ok(x) = part.parse::<u8>();
-            if x <= HALF_MOVE_MAX;
+            if x <= HALF_MOVE_AUTOMATIC_DRAW;
             then {
                 board.game_state.half_move_clock = x;
                 return Ok(());
@@ -285,4 +323,59 @@ impl<'board_lifetime> FenParser<'board_lifetime> {
             "Invalid full-move number part: {part}"
         )))
     }
-}
\ No newline at end of file
+}
+
+// Serializes the board into the six FEN fields
+pub fn write_fen(board: &Board) -> String {
+    let mut pieces = String::new();
+    for rank in (0..8).rev() {
+        let mut empty = 0;
+        for file in 0..8 {
+            let square = Square::try_from(rank * 8 + file).unwrap();
+            match board.piece_on(square) {
+                Some((side, piece)) => {
+                    if empty > 0 {
+                        pieces.push_str(&empty.to_string());
+                        empty = 0;
+                    }
+                    let symbol = piece.to_string();
+                    match side {
+                        Side::White => pieces.push_str(&symbol),
+                        Side::Black => pieces.push_str(&symbol.to_ascii_lowercase()),
+                    }
+                }
+                None => empty += 1,
+            }
+        }
+        if empty > 0 {
+            pieces.push_str(&empty.to_string());
+        }
+        if rank > 0 {
+            pieces.push(SPLITTER);
+        }
+    }
+
+    let color = match board.get_active_side() {
+        Side::White => 'w',
+        Side::Black => 'b',
+    };
+
+    let mut castling: String = [
+        (Castling::WhiteKing, 'K'), (Castling::WhiteQueen, 'Q'),
+        (Castling::BlackKing, 'k'), (Castling::BlackQueen, 'q'),
+    ].iter()
+        .filter(|(right, _)| board.game_state.castling & (*right as u8) != 0)
+        .map(|(_, symbol)| *symbol)
+        .collect();
+    if castling.is_empty() {
+        castling.push(DASH);
+    }
+
+    let en_passant = match board.get_ep_square() {
+        Some(square) => square.to_string(),
+        None => DASH.to_string(),
+    };
+
+    format!("{pieces} {color} {castling} {en_passant} {} {}",
+        board.game_state.half_move_clock, board.game_state.full_move_number)
+}
@@ -0,0 +1,98 @@
+use super::{game_state::GameState};
+use crate::move_generator::chess_move::ChessMove;
+use crate::definitions::{Square, Piece, Side, MAX_GAME_MOVES};
+
+
+#[derive(Clone, Copy)]
+pub struct RecordedMove {
+    pub mv: ChessMove,
+    pub prev_state: GameState,
+    pub captured_piece: Option<(Piece, Side, Square)>,
+}
+
+impl RecordedMove {
+    pub fn new(mv: ChessMove, prev_state: GameState,
+        captured_piece: Option<(Piece, Side, Square)>) -> Self {
+        RecordedMove {
+            mv,
+            prev_state,
+            captured_piece,
+        }
+    }
+}
+
+
+#[derive(Clone)]
+pub struct GameHistory {
+    // Cleared rather than reallocated, so capacity is reused across games
+    list: Vec<RecordedMove>,
+}
+
+
+impl GameHistory {
+    pub fn new() -> Self {
+        GameHistory {
+            list: Vec::with_capacity(MAX_GAME_MOVES),
+        }
+    }
+
+
+    pub fn with_capacity(capacity: usize) -> Self {
+        GameHistory {
+            list: Vec::with_capacity(capacity),
+        }
+    }
+
+
+    pub fn push(&mut self, new_recorded_move: RecordedMove) {
+        self.list.push(new_recorded_move);
+    }
+
+
+    pub fn pop(&mut self) -> Option<RecordedMove> {
+        self.list.pop()
+    }
+
+
+    pub fn get_ref(&self, index: usize) -> &RecordedMove {
+        &self.list[index]
+    }
+
+
+    pub fn last(&self) -> Option<&RecordedMove> {
+        self.list.last()
+    }
+
+
+    pub fn iter(&self) -> std::slice::Iter<'_, RecordedMove> {
+        self.list.iter()
+    }
+
+
+    // Each move paired with the state of the game before it was played
+    pub fn moves(&self) -> impl Iterator<Item = (ChessMove, GameState)> + '_ {
+        self.list.iter().map(|recorded_move| (recorded_move.mv, recorded_move.prev_state))
+    }
+
+
+    pub fn len(&self) -> usize {
+        self.list.len()
+    }
+
+
+    pub fn is_empty(&self) -> bool {
+        self.list.is_empty()
+    }
+
+
+    pub fn clear(&mut self) {
+        self.list.clear();
+    }
+}
+
+
+impl Default for GameHistory {
+    fn default() -> Self {
+        Self::new()
+    }
+}
@@ -0,0 +1,54 @@
+use crate::definitions::Side;
+
+
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub enum GameResult {
+    // Holds the winning side
+    Checkmate(Side),
+    // A win a variant's rules add, e.g. a king reaching the centre in King
+    // of the Hill. Holds the winning side
+    VariantWin(Side),
+    Stalemate,
+    DrawFiftyMove,
+    DrawRepetition,
+    DrawInsufficientMaterial,
+    Ongoing,
+}
+
+
+// Claimable draws (50-move rule, threefold repetition) as a player or engine
+// would claim them, or only the automatic ones (75-move rule, fivefold)
+// that an arbiter applies
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub enum DrawRules {
+    Claimable,
+    Automatic,
+}
+
+
+// FIDE only declares dead positions drawn, where no sequence of legal moves
+// mates. USCF also draws positions where mate cannot be forced, such as two
+// knights against a bare king
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub enum InsufficientMaterialRules {
+    Fide,
+    Uscf,
+}
+
+
+impl GameResult {
+    pub fn is_over(&self) -> bool {
+        *self != GameResult::Ongoing
+    }
+
+    pub fn is_draw(&self) -> bool {
+        !matches!(self, GameResult::Checkmate(_) | GameResult::VariantWin(_) | GameResult::Ongoing)
+    }
+
+    pub fn winner(&self) -> Option<Side> {
+        match self {
+            GameResult::Checkmate(side) | GameResult::VariantWin(side) => Some(*side),
+            _ => None,
+        }
+    }
+}
@@ -1,4 +1,4 @@
-use crate::engine::definitions::{Side, ZobristKey};
+use crate::definitions::{Bitboard, Side, ZobristKey};
 
 
 #[derive(Clone, Copy)]
@@ -9,6 +9,12 @@ pub struct GameState {
     pub en_passant: Option<u8>,
     pub full_move_number: u16,
     pub zobrist_key: ZobristKey,
+    // Covers only pawns and kings, for pawn structure and king safety caches
+    pub pawn_key: ZobristKey,
+    pub checkers: Bitboard,
+    // Piece-square sums, White minus Black
+    pub pst_mg: i32,
+    pub pst_eg: i32,
 }
 
 
@@ -21,6 +27,10 @@ impl GameState {
             half_move_clock: 0,
             full_move_number: 0,
             zobrist_key: 0,
+            pawn_key: 0,
+            checkers: 0,
+            pst_mg: 0,
+            pst_eg: 0,
         }
     }
 
@@ -32,5 +42,15 @@ impl GameState {
         self.half_move_clock = 0;
         self.full_move_number = 0;
         self.zobrist_key = 0;
+        self.pawn_key = 0;
+        self.checkers = 0;
+        self.pst_mg = 0;
+        self.pst_eg = 0;
     }
-}
\ No newline at end of file
+}
+
+impl Default for GameState {
+    fn default() -> Self {
+        Self::new()
+    }
+}
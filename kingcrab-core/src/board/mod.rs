@@ -0,0 +1,22 @@
+pub mod board;
+
+pub mod zobrist;
+
+pub mod game_state;
+
+pub mod game_history;
+
+pub mod fen;
+
+pub mod game_result;
+
+pub mod board_builder;
+
+pub mod position_key;
+
+pub mod position_command;
+
+pub mod polyglot;
+
+pub mod polyglot_randoms;
+pub mod epd;
\ No newline at end of file
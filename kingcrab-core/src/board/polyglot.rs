@@ -0,0 +1,142 @@
+use std::fs;
+
+use crate::{bitboard::BitboardExt, move_generator::attacks,
+    definitions::{Castling, Piece, Side, ZobristKey}};
+use super::{board::Board, polyglot_randoms::POLYGLOT_RANDOMS};
+
+
+// Entries of the Polyglot random array: 12 * 64 piece-square keys, then 4
+// castling keys, 8 en passant file keys and the side to move key
+pub const POLYGLOT_RANDOM_COUNT: usize = 781;
+
+const CASTLING_OFFSET: usize = 768;
+const EN_PASSANT_OFFSET: usize = 772;
+const TURN_OFFSET: usize = 780;
+
+// Positions and their keys from the Polyglot book format description. The
+// en passant one checks that the file is only hashed when a pawn can take
+const REFERENCE_KEYS: [(&str, ZobristKey); 2] = [
+    ("rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBNR w KQkq - 0 1", 0x463b96181691fc9c),
+    ("rnbqkbnr/ppp1p1pp/8/3pPp2/8/8/PPPP1PPP/RNBQKBNR w KQkq f6 0 3", 0x22a48b5a8e47ff78),
+];
+
+
+// Hashes positions the way Polyglot opening books do. standard() uses the
+// random array published with Polyglot, which books are made with. Other
+// arrays can be given as 781 words or big-endian bytes (the layout of the
+// array in book tools); those that do not reproduce the reference keys of
+// the format are refused, as they would hash every position wrongly without
+// any other sign
+pub struct PolyglotKeys {
+    randoms: Vec<ZobristKey>,
+}
+
+
+impl PolyglotKeys {
+    pub fn standard() -> Self {
+        PolyglotKeys { randoms: POLYGLOT_RANDOMS.to_vec() }
+    }
+
+    pub fn from_bytes(bytes: &[u8]) -> Result<Self, String> {
+        if bytes.len() != POLYGLOT_RANDOM_COUNT * 8 {
+            return Err(format!("Expected {} bytes of Polyglot randoms, found {}",
+                POLYGLOT_RANDOM_COUNT * 8, bytes.len()));
+        }
+
+        let randoms: Vec<ZobristKey> = bytes.chunks_exact(8)
+            .map(|chunk| ZobristKey::from_be_bytes(chunk.try_into().unwrap()))
+            .collect();
+
+        Self::from_randoms(&randoms)
+    }
+
+    pub fn from_file(path: &str) -> Result<Self, String> {
+        let bytes = fs::read(path)
+            .map_err(|e| format!("Failed to read Polyglot randoms from {path}: {e}"))?;
+        Self::from_bytes(&bytes)
+    }
+
+    pub fn from_randoms(randoms: &[ZobristKey]) -> Result<Self, String> {
+        if randoms.len() != POLYGLOT_RANDOM_COUNT {
+            return Err(format!("Expected {} Polyglot randoms, found {}",
+                POLYGLOT_RANDOM_COUNT, randoms.len()));
+        }
+
+        let keys = PolyglotKeys { randoms: randoms.to_vec() };
+        keys.check_reference_keys()?;
+        Ok(keys)
+    }
+
+    fn check_reference_keys(&self) -> Result<(), String> {
+        for (fen, expected) in REFERENCE_KEYS {
+            let board = Board::try_from_fen(fen).map_err(|e| e.to_string())?;
+            let key = self.hash(&board);
+            if key != expected {
+                return Err(format!("The randoms are not the standard Polyglot array: \
+                    {fen} hashes to {key:016x} instead of {expected:016x}"));
+            }
+        }
+        Ok(())
+    }
+
+    pub fn hash(&self, board: &Board) -> ZobristKey {
+        let mut key = 0;
+
+        for side in [Side::White, Side::Black] {
+            for (piece_type, bitboard) in board.get_bitboards(side).iter().enumerate() {
+                let kind = Self::piece_kind(side, Piece::try_from(piece_type).unwrap());
+                for square in bitboard.squares() {
+                    key ^= self.randoms[64 * kind + square as usize];
+                }
+            }
+        }
+
+        let castling_rights = [Castling::WhiteKing, Castling::WhiteQueen,
+            Castling::BlackKing, Castling::BlackQueen];
+        for (index, right) in castling_rights.iter().enumerate() {
+            if board.game_state.castling & *right as u8 != 0 {
+                key ^= self.randoms[CASTLING_OFFSET + index];
+            }
+        }
+
+        // Polyglot only hashes the en passant file when a pawn can capture
+        if let Some(ep_square) = board.get_ep_square() {
+            let side = board.get_active_side();
+            let capturers = attacks::pawn_attacks(ep_square, side.opponent())
+                & board.get_pieces(side, Piece::Pawn);
+            if capturers != 0 {
+                key ^= self.randoms[EN_PASSANT_OFFSET + ep_square.file() as usize];
+            }
+        }
+
+        if board.get_active_side() == Side::White {
+            key ^= self.randoms[TURN_OFFSET];
+        }
+
+        key
+    }
+
+    // Polyglot orders pieces as black pawn, white pawn, black knight, ...
+    fn piece_kind(side: Side, piece: Piece) -> usize {
+        let kind = match piece {
+            Piece::Pawn => 0,
+            Piece::Knight => 1,
+            Piece::Bishop => 2,
+            Piece::Rook => 3,
+            Piece::Queen => 4,
+            Piece::King => 5,
+            Piece::None => unreachable!(),
+        };
+
+        match side {
+            Side::White => 2 * kind + 1,
+            Side::Black => 2 * kind,
+        }
+    }
+}
+
+impl Default for PolyglotKeys {
+    fn default() -> Self {
+        Self::standard()
+    }
+}
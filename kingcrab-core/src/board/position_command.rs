@@ -0,0 +1,77 @@
+use core::fmt;
+use std::fmt::Display;
+
+use crate::definitions::FEN_STARTING_POSITION;
+use crate::move_generator::{move_generator::MoveGenerator, notation::NotationError};
+use super::{board::Board, fen::FenError};
+
+
+#[derive(Debug)]
+pub enum PositionCommandError {
+    MalformedCommand(String),
+    InvalidFen(FenError),
+    InvalidMove(NotationError),
+}
+
+impl Display for PositionCommandError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::MalformedCommand(message) =>
+                    write!(f, "Malformed position command: {message}"),
+            Self::InvalidFen(error) => write!(f, "{error}"),
+            Self::InvalidMove(error) => write!(f, "{error}"),
+        }
+    }
+}
+
+
+// Builds a board from a UCI command such as
+// "position startpos moves e2e4 e7e5" or "position fen <fen> moves ...".
+// The moves are played on the board, so they are part of its game history
+pub fn parse_position_command(command: &str,
+    move_generator: &MoveGenerator) -> Result<Board, PositionCommandError> {
+    let mut tokens = command.split_whitespace().peekable();
+
+    if tokens.peek() == Some(&"position") {
+        tokens.next();
+    }
+
+    let fen = match tokens.next() {
+        Some("startpos") => FEN_STARTING_POSITION.to_string(),
+        Some("fen") => {
+            let mut fen_parts = Vec::new();
+            while let Some(token) = tokens.peek() {
+                if *token == "moves" {
+                    break;
+                }
+                fen_parts.push(*token);
+                tokens.next();
+            }
+            fen_parts.join(" ")
+        },
+        Some(token) => return Err(PositionCommandError::MalformedCommand(format!(
+            "expected startpos or fen, found {token}"
+        ))),
+        None => return Err(PositionCommandError::MalformedCommand(
+            String::from("missing position")
+        )),
+    };
+
+    let mut board = Board::try_from_fen(&fen)
+        .map_err(PositionCommandError::InvalidFen)?;
+
+    match tokens.next() {
+        None => return Ok(board),
+        Some("moves") => (),
+        Some(token) => return Err(PositionCommandError::MalformedCommand(format!(
+            "expected moves, found {token}"
+        ))),
+    }
+
+    for uci in tokens {
+        board.push_uci(move_generator, uci)
+            .map_err(PositionCommandError::InvalidMove)?;
+    }
+
+    Ok(board)
+}
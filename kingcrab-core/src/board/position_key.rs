@@ -0,0 +1,52 @@
+use std::hash::{Hash, Hasher};
+
+use crate::definitions::{Bitboard, NrOf, Side, Square, ZobristKey};
+use super::board::Board;
+
+
+// Identity of a position independent of how it was reached: move counters
+// and history are left out
+#[derive(Clone, Copy, Debug)]
+pub struct PositionKey {
+    pub pieces: [[Bitboard; NrOf::PIECE_TYPES]; NrOf::SIDES],
+    pub active_side: Side,
+    pub castling: u8,
+    pub en_passant: Option<Square>,
+    zobrist_key: ZobristKey,
+}
+
+
+impl PositionKey {
+    pub fn new(board: &Board) -> Self {
+        PositionKey {
+            pieces: board.pieces,
+            active_side: board.get_active_side(),
+            castling: board.game_state.castling,
+            en_passant: board.get_ep_square(),
+            zobrist_key: board.game_state.zobrist_key,
+        }
+    }
+
+    pub fn zobrist_key(&self) -> ZobristKey {
+        self.zobrist_key
+    }
+}
+
+
+impl PartialEq for PositionKey {
+    fn eq(&self, other: &Self) -> bool {
+        self.pieces == other.pieces
+            && self.active_side == other.active_side
+            && self.castling == other.castling
+            && self.en_passant == other.en_passant
+    }
+}
+
+impl Eq for PositionKey {}
+
+// Equal positions always share a zobrist key, so it is enough to hash that
+impl Hash for PositionKey {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        self.zobrist_key.hash(state);
+    }
+}
@@ -1,7 +1,7 @@
 use rand::{Rng, SeedableRng};
 use rand_chacha::ChaChaRng;
 
-use crate::engine::definitions::{NrOf, Piece, Side, Square, ZobristKey};
+use crate::definitions::{NrOf, Piece, Side, Square, ZobristKey};
 
 
 type PieceKeys = [[[ZobristKey; NrOf::SQUARES]; NrOf::PIECE_TYPES]; NrOf::SIDES];
@@ -9,7 +9,10 @@ type CastlingKeys = [ZobristKey; NrOf::CASTLING_PERMISSIONS];
 type SideKeys = [ZobristKey; NrOf::SIDES];
 type EnPassantKeys = [ZobristKey; NrOf::SQUARES + 1];
 
-const RNG_SEED: [u8; 32] = [125; 32];
+// Every board draws its keys from this seed, so hashes, and the search
+// that depends on them, are the same from run to run and machine to
+// machine
+pub const ZOBRIST_SEED: [u8; 32] = [125; 32];
 
 
 pub struct ZobristKeys {
@@ -22,7 +25,7 @@ pub struct ZobristKeys {
 
 impl ZobristKeys {
     pub fn new() -> ZobristKeys {
-        let mut rng: ChaChaRng = ChaChaRng::from_seed(RNG_SEED);
+        let mut rng: ChaChaRng = ChaChaRng::from_seed(ZOBRIST_SEED);
 
         let mut piece_keys: PieceKeys = [[[0; NrOf::SQUARES]; NrOf::PIECE_TYPES]; NrOf::SIDES];
         let mut castling_keys: CastlingKeys = [0; NrOf::CASTLING_PERMISSIONS];
@@ -85,4 +88,10 @@ impl ZobristKeys {
             None => self.en_passant_keys[NrOf::SQUARES],
         }
     }
-}
\ No newline at end of file
+}
+
+impl Default for ZobristKeys {
+    fn default() -> Self {
+        Self::new()
+    }
+}
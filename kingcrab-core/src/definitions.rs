@@ -15,11 +15,24 @@ pub const RANK_BITBOARDS: [Bitboard; NrOf::RANKS] = init_rank_bitboards();
 
 pub const SQUARE_BITBOARDS: [Bitboard; NrOf::SQUARES] = init_square_bitboards();
 
+// Squares strictly between two aligned squares, and the full line through
+// them from edge to edge; both are empty for squares that are not aligned
+pub static BETWEEN_BITBOARDS: [[Bitboard; NrOf::SQUARES]; NrOf::SQUARES] = init_between_bitboards();
+pub static LINE_BITBOARDS: [[Bitboard; NrOf::SQUARES]; NrOf::SQUARES] = init_line_bitboards();
+
+// Castling rights kept when a piece moves from or to each square
+pub const CASTLING_PERMS: [u8; NrOf::SQUARES] = init_castling_perms();
+
 pub const MAX_GAME_MOVES: usize = 1024;
 pub const HALF_MOVE_MAX: u8 = 100;
+// The 75-move rule ends the game without a claim
+pub const HALF_MOVE_AUTOMATIC_DRAW: u8 = 150;
 
 pub const FEN_STARTING_POSITION: &str = "rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBNR w KQkq - 0 1";
 
+// Material values in centipawns, indexed by Piece; the king is not counted
+pub const PIECE_VALUES: [i32; NrOf::PIECE_TYPES] = [0, 900, 500, 325, 300, 100];
+
 pub const MAX_POSITION_SCORE: f32 = 100000.0;
 pub const MIN_POSITION_SCORE: f32 = -100000.0;
 
@@ -39,7 +52,7 @@ impl NrOf {
 
 
 #[repr(usize)]
-#[derive(Clone, Copy, PartialEq, Debug, TryFromPrimitive)]
+#[derive(Clone, Copy, PartialEq, Eq, Hash, Debug, TryFromPrimitive)]
 pub enum Side {
     White = 0,
     Black = 1
@@ -47,7 +60,7 @@ pub enum Side {
 
 
 #[repr(usize)]
-#[derive(Clone, Copy, PartialEq, Debug, TryFromPrimitive, Hash, Eq)]
+#[derive(Clone, Copy, PartialEq, Eq, Hash, Debug, TryFromPrimitive)]
 pub enum Piece {
     King = 0,
     Queen = 1,
@@ -60,7 +73,7 @@ pub enum Piece {
 
 
 #[repr(usize)]
-#[derive(Clone, Copy, PartialEq, TryFromPrimitive)]
+#[derive(Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord, Debug, TryFromPrimitive)]
 pub enum Square {
     A1, B1, C1, D1, E1, F1, G1, H1,
     A2, B2, C2, D2, E2, F2, G2, H2,
@@ -74,18 +87,35 @@ pub enum Square {
 
 
 #[repr(usize)]
+#[derive(Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord, Debug, TryFromPrimitive)]
 pub enum Rank {
+    R1 = 0,
+    R2 = 1,
+    R3 = 2,
+    R4 = 3,
+    R5 = 4,
+    R6 = 5,
+    R7 = 6,
     R8 = 7,
 }
 
 
 #[repr(usize)]
+#[derive(Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord, Debug, TryFromPrimitive)]
 pub enum File {
     A = 0,
+    B = 1,
+    C = 2,
+    D = 3,
+    E = 4,
+    F = 5,
+    G = 6,
+    H = 7,
 }
 
 
 #[repr(usize)]
+#[derive(Clone, Copy, PartialEq, Eq, Hash, Debug)]
 pub enum Castling {
     WhiteKing = 1,
     WhiteQueen = 2,
@@ -94,6 +124,54 @@ pub enum Castling {
 }
 
 
+impl Side {
+    pub fn opponent(self) -> Side {
+        match self {
+            Side::White => Side::Black,
+            Side::Black => Side::White,
+        }
+    }
+}
+
+
+impl Square {
+    pub fn from_file_rank(file: File, rank: Rank) -> Square {
+        Square::try_from(rank as usize * NrOf::FILES + file as usize).unwrap()
+    }
+
+    pub fn file(self) -> File {
+        File::try_from(self as usize % NrOf::FILES).unwrap()
+    }
+
+    pub fn rank(self) -> Rank {
+        Rank::try_from(self as usize / NrOf::FILES).unwrap()
+    }
+
+    // King-move distance between two squares
+    pub fn distance(a: Square, b: Square) -> u8 {
+        let file_distance = (a.file() as i8 - b.file() as i8).unsigned_abs();
+        let rank_distance = (a.rank() as i8 - b.rank() as i8).unsigned_abs();
+        file_distance.max(rank_distance)
+    }
+
+    // The square one rank ahead from the given side's point of view
+    pub fn forward(self, side: Side) -> Option<Square> {
+        let square = match side {
+            Side::White => self as usize + NrOf::FILES,
+            Side::Black => (self as usize).wrapping_sub(NrOf::FILES),
+        };
+        Square::try_from(square).ok()
+    }
+
+    pub fn flip_vertical(self) -> Square {
+        Square::try_from(self as usize ^ 56).unwrap()
+    }
+
+    pub fn is_light(self) -> bool {
+        (self.file() as usize + self.rank() as usize) % 2 == 1
+    }
+}
+
 impl FromStr for Square {
     type Err = ();
 
@@ -168,6 +246,19 @@ impl Display for Square {
 }
 
 
+impl Display for File {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", (b'a' + *self as u8) as char)
+    }
+}
+
+impl Display for Rank {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", (b'1' + *self as u8) as char)
+    }
+}
+
+
 impl Display for Piece {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         match self {
@@ -219,4 +310,95 @@ const fn init_square_bitboards() -> [Bitboard; NrOf::SQUARES] {
     }
 
     squares
-}
\ No newline at end of file
+}
+
+const fn direction_between(from: usize, to: usize) -> Option<(isize, isize)> {
+    let rank_diff = (to / 8) as isize - (from / 8) as isize;
+    let file_diff = (to % 8) as isize - (from % 8) as isize;
+
+    if from == to {
+        return None;
+    }
+
+    if rank_diff == 0 || file_diff == 0 || rank_diff == file_diff || rank_diff == -file_diff {
+        Some((rank_diff.signum(), file_diff.signum()))
+    } else {
+        None
+    }
+}
+
+const fn init_between_bitboards() -> [[Bitboard; NrOf::SQUARES]; NrOf::SQUARES] {
+    let mut between = [[0; NrOf::SQUARES]; NrOf::SQUARES];
+    let mut from = 0;
+
+    while from < NrOf::SQUARES {
+        let mut to = 0;
+        while to < NrOf::SQUARES {
+            if let Some((rank_step, file_step)) = direction_between(from, to) {
+                let mut rank = (from / 8) as isize + rank_step;
+                let mut file = (from % 8) as isize + file_step;
+                while (rank * 8 + file) as usize != to {
+                    between[from][to] |= 1u64 << (rank * 8 + file);
+                    rank += rank_step;
+                    file += file_step;
+                }
+            }
+            to += 1;
+        }
+        from += 1;
+    }
+
+    between
+}
+
+const fn init_line_bitboards() -> [[Bitboard; NrOf::SQUARES]; NrOf::SQUARES] {
+    let mut lines = [[0; NrOf::SQUARES]; NrOf::SQUARES];
+    let mut from = 0;
+
+    while from < NrOf::SQUARES {
+        let mut to = 0;
+        while to < NrOf::SQUARES {
+            if let Some((rank_step, file_step)) = direction_between(from, to) {
+                lines[from][to] = 1u64 << from;
+
+                // Walk from the first square to both edges of the board
+                let mut direction = 0;
+                while direction < 2 {
+                    let (rank_step, file_step) = match direction {
+                        0 => (rank_step, file_step),
+                        _ => (-rank_step, -file_step),
+                    };
+                    let mut rank = (from / 8) as isize + rank_step;
+                    let mut file = (from % 8) as isize + file_step;
+                    while rank >= 0 && rank < 8 && file >= 0 && file < 8 {
+                        lines[from][to] |= 1u64 << (rank * 8 + file);
+                        rank += rank_step;
+                        file += file_step;
+                    }
+                    direction += 1;
+                }
+            }
+            to += 1;
+        }
+        from += 1;
+    }
+
+    lines
+}
+
+
+const fn init_castling_perms() -> [u8; NrOf::SQUARES] {
+    const ALL: u8 = Castling::WhiteKing as u8 | Castling::WhiteQueen as u8
+        | Castling::BlackKing as u8 | Castling::BlackQueen as u8;
+
+    let mut perms = [ALL; NrOf::SQUARES];
+
+    perms[Square::A1 as usize] = ALL & !(Castling::WhiteQueen as u8);
+    perms[Square::H1 as usize] = ALL & !(Castling::WhiteKing as u8);
+    perms[Square::E1 as usize] = ALL & !(Castling::WhiteKing as u8 | Castling::WhiteQueen as u8);
+    perms[Square::A8 as usize] = ALL & !(Castling::BlackQueen as u8);
+    perms[Square::H8 as usize] = ALL & !(Castling::BlackKing as u8);
+    perms[Square::E8 as usize] = ALL & !(Castling::BlackKing as u8 | Castling::BlackQueen as u8);
+
+    perms
+}
@@ -0,0 +1,27 @@
+// The rules of chess: the board, FEN, EPD and Polyglot, move generation,
+// move notation, perft and the rules of variants, without the evaluators
+// and the search, for GUIs and tools that only need to know what is legal
+pub mod definitions;
+pub mod bitboard;
+
+pub mod board;
+pub mod move_generator;
+pub mod piece_square_tables;
+pub mod rules;
+
+pub use board::board::Board;
+pub use board::board_builder::BoardBuilder;
+pub use board::game_result::{DrawRules, GameResult, InsufficientMaterialRules};
+pub use board::position_key::PositionKey;
+pub use board::position_command::parse_position_command;
+pub use board::polyglot::PolyglotKeys;
+pub use board::zobrist::ZOBRIST_SEED;
+pub use board::epd::{parse_epd_records, Epd};
+pub use move_generator::chess_move::ChessMove;
+pub use move_generator::move_generator::MoveGenerator;
+pub use move_generator::move_formatter::{MoveFormatter, NotationStyle};
+pub use move_generator::perft::{divide, hashed_perft, parallel_perft,
+    perft, print_divide, PerftTable};
+pub use rules::rules::{Rules, StandardRules};
+pub use rules::king_of_the_hill::KingOfTheHill;
+pub use rules::variant::{Variant, UCI_VARIANT_OPTION};
@@ -0,0 +1,91 @@
+use std::sync::LazyLock;
+
+use crate::definitions::{Bitboard, Piece, Side, Square};
+use super::{pext::{fast_pext_available, pext},
+    magics::{bishop_attack_table, bishop_pext_table, rook_attack_table, rook_pext_table,
+        BISHOP_BLOCKER_MASKS, BISHOP_MAGICS, KING_BASE_ATTACKS,
+        KNIGHT_BASE_ATTACKS, PAWN_BLACK_ATTACKS, PAWN_WHITE_ATTACKS,
+        ROOK_BLOCKER_MASKS, ROOK_MAGICS}};
+
+
+// The attack tables are generated at build time. On CPUs with fast BMI2 the
+// sliders are looked up through PEXT indexed tables instead of magic
+// multiplication; the check is done once per process.
+static USE_PEXT: LazyLock<bool> = LazyLock::new(fast_pext_available);
+
+
+pub fn rook_attacks(square: Square, occupancy: Bitboard) -> Bitboard {
+    let sq = square as usize;
+    let rook_mask = ROOK_BLOCKER_MASKS[sq];
+    if *USE_PEXT {
+        return rook_pext_table().get(sq, pext(occupancy, rook_mask));
+    }
+    let rook_magic = ROOK_MAGICS[sq];
+    let rook_shift = 64 - rook_mask.count_ones();
+    let rook_index = ((occupancy & rook_mask)
+                            .wrapping_mul(rook_magic)) >> rook_shift;
+    rook_attack_table().get(sq, rook_index as usize)
+}
+
+pub fn bishop_attacks(square: Square, occupancy: Bitboard) -> Bitboard {
+    let sq = square as usize;
+    let bishop_mask = BISHOP_BLOCKER_MASKS[sq];
+    if *USE_PEXT {
+        return bishop_pext_table().get(sq, pext(occupancy, bishop_mask));
+    }
+    let bishop_magic = BISHOP_MAGICS[sq];
+    let bishop_shift = 64 - bishop_mask.count_ones();
+    let bishop_index = ((occupancy & bishop_mask)
+                            .wrapping_mul(bishop_magic)) >> bishop_shift;
+    bishop_attack_table().get(sq, bishop_index as usize)
+}
+
+pub fn queen_attacks(square: Square, occupancy: Bitboard) -> Bitboard {
+    rook_attacks(square, occupancy) | bishop_attacks(square, occupancy)
+}
+
+// Squares attacked only once the first blockers (restricted to `blockers`)
+// on each ray are removed, i.e. what the slider sees through them
+pub fn xray_rook_attacks(square: Square, occupancy: Bitboard, blockers: Bitboard) -> Bitboard {
+    let attacks = rook_attacks(square, occupancy);
+    let first_blockers = blockers & attacks;
+    attacks ^ rook_attacks(square, occupancy ^ first_blockers)
+}
+
+pub fn xray_bishop_attacks(square: Square, occupancy: Bitboard, blockers: Bitboard) -> Bitboard {
+    let attacks = bishop_attacks(square, occupancy);
+    let first_blockers = blockers & attacks;
+    attacks ^ bishop_attacks(square, occupancy ^ first_blockers)
+}
+
+pub fn xray_queen_attacks(square: Square, occupancy: Bitboard, blockers: Bitboard) -> Bitboard {
+    xray_rook_attacks(square, occupancy, blockers) | xray_bishop_attacks(square, occupancy, blockers)
+}
+
+pub fn knight_attacks(square: Square) -> Bitboard {
+    KNIGHT_BASE_ATTACKS[square as usize]
+}
+
+pub fn king_attacks(square: Square) -> Bitboard {
+    KING_BASE_ATTACKS[square as usize]
+}
+
+pub fn pawn_attacks(square: Square, side: Side) -> Bitboard {
+    match side {
+        Side::White => PAWN_WHITE_ATTACKS[square as usize],
+        Side::Black => PAWN_BLACK_ATTACKS[square as usize],
+    }
+}
+
+pub fn piece_attacks(piece: Piece, side: Side,
+    square: Square, occupancy: Bitboard) -> Bitboard {
+    match piece {
+        Piece::King => king_attacks(square),
+        Piece::Queen => queen_attacks(square, occupancy),
+        Piece::Rook => rook_attacks(square, occupancy),
+        Piece::Bishop => bishop_attacks(square, occupancy),
+        Piece::Knight => knight_attacks(square),
+        Piece::Pawn => pawn_attacks(square, side),
+        Piece::None => 0,
+    }
+}
@@ -1,12 +1,13 @@
-use std::fmt::Display;
+use std::{fmt::Display, hash::{Hash, Hasher}};
 
-use crate::engine::definitions::{Square, Piece};
+use crate::{board::board::Board, definitions::{Square, Piece, Rank}};
+use super::{move_generator::MoveGenerator, notation::{self, parse_uci, NotationError}};
 
 use bitflags::bitflags;
 
 
 bitflags! {
-    #[derive(Clone, Copy)]
+    #[derive(Clone, Copy, PartialEq, Eq, Hash, Debug)]
     pub struct ChessMoveFlags: u8 {
         const QUIET             = 1;
         const CAPTURE           = 2;
@@ -18,7 +19,7 @@ bitflags! {
     }
 }
 
-#[derive(Clone, Copy)]
+#[derive(Clone, Copy, Debug)]
 pub struct ChessMove {
     pub piece: Piece,
     pub from: Square,
@@ -114,6 +115,96 @@ impl ChessMove {
         }
     }
 
+    // A pass: no piece moves, only the side to move changes
+    pub fn null() -> Self {
+        Self {
+            piece: Piece::None,
+            from: Square::A1,
+            to: Square::A1,
+            promotion: None,
+            is_check: false,
+            is_checkmate: false,
+            flags: ChessMoveFlags::empty(),
+        }
+    }
+
+    pub fn is_null(&self) -> bool {
+        self.flags.is_empty()
+    }
+
+    // Builds a move from coordinate notation, taking the moving piece and the
+    // capture, en passant, castling and double push flags from the board.
+    // Only the shape of the move is checked, not its legality
+    pub fn from_uci(board: &Board, uci: &str) -> Result<Self, NotationError> {
+        if uci == "0000" {
+            return Ok(Self::null());
+        }
+
+        let (from, to, promotion) = parse_uci(uci)?;
+        let side = board.get_active_side();
+        let illegal = || NotationError::IllegalMove(uci.to_string());
+
+        let piece = match board.piece_on(from) {
+            Some((piece_side, piece)) if piece_side == side => piece,
+            _ => return Err(illegal()),
+        };
+        let is_capture = match board.piece_on(to) {
+            Some((target_side, _)) if target_side == side => return Err(illegal()),
+            Some(_) => true,
+            None => false,
+        };
+
+        let file_distance = (from.file() as i8 - to.file() as i8).abs();
+        let rank_distance = (from.rank() as i8 - to.rank() as i8).abs();
+        let last_rank = matches!(to.rank(), Rank::R1 | Rank::R8);
+
+        if piece != Piece::Pawn && promotion.is_some() {
+            return Err(illegal());
+        }
+
+        let mv = match piece {
+            Piece::King if file_distance == 2 && rank_distance == 0 =>
+                Self::castle(from, to, to.file() > from.file()),
+            Piece::Pawn if last_rank => match promotion {
+                Some(promotion) => Self::promotion(from, to, promotion, is_capture),
+                None => return Err(illegal()),
+            },
+            Piece::Pawn if promotion.is_some() => return Err(illegal()),
+            Piece::Pawn if file_distance == 1 && Some(to) == board.get_ep_square() =>
+                Self::en_passant(from, to),
+            Piece::Pawn if rank_distance == 2 => Self::double_pawn_push(from, to),
+            _ if is_capture => Self::capture(piece, from, to),
+            _ => Self::quiet(piece, from, to),
+        };
+
+        Ok(mv)
+    }
+
+    pub fn to_uci(&self) -> String {
+        if self.is_null() {
+            return "0000".to_string();
+        }
+
+        let mut uci = format!("{}{}", self.from, self.to);
+        if let Some(promotion) = self.promotion {
+            uci.push_str(&promotion.to_string().to_ascii_lowercase());
+        }
+        uci
+    }
+
+    // Standard algebraic notation as used in PGN, e.g. Nbd2, exd6, O-O, e8=Q+.
+    // En passant captures carry no "e.p." suffix, as PGN requires
+    pub fn to_san(&self, board: &Board, move_generator: &MoveGenerator) -> String {
+        let mut board = board.clone();
+        notation::san(&mut board, move_generator, *self)
+    }
+
+    pub fn from_san(board: &Board, move_generator: &MoveGenerator,
+        san: &str) -> Result<Self, NotationError> {
+        let mut board = board.clone();
+        notation::move_from_san(&mut board, move_generator, san)
+    }
+
     pub fn is_quiet(&self) -> bool {
         self.flags.contains(ChessMoveFlags::QUIET)
     }
@@ -143,10 +234,36 @@ impl ChessMove {
     }
 }
 
+// is_check and is_checkmate are annotations filled in by the generator,
+// so two moves are the same if they agree on everything else
+impl PartialEq for ChessMove {
+    fn eq(&self, other: &Self) -> bool {
+        self.piece == other.piece
+            && self.from == other.from
+            && self.to == other.to
+            && self.promotion == other.promotion
+            && self.flags == other.flags
+    }
+}
+
+impl Eq for ChessMove {}
+
+impl Hash for ChessMove {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        self.piece.hash(state);
+        self.from.hash(state);
+        self.to.hash(state);
+        self.promotion.hash(state);
+        self.flags.hash(state);
+    }
+}
+
 impl Display for ChessMove {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         let mut move_str= String::new();
-        if self.is_king_castling() {
+        if self.is_null() {
+            move_str = "0000".to_string();
+        } else if self.is_king_castling() {
             move_str = "0-0".to_string();
         } else if self.is_queen_castling() {
             move_str = "0-0-0".to_string();
@@ -0,0 +1,18 @@
+pub mod chess_move;
+
+pub mod move_generator;
+
+pub mod attacks;
+
+mod magics;
+
+mod slider_attacks;
+
+mod pext;
+
+mod move_sorter;
+
+pub mod perft;
+
+pub mod notation;
+pub mod move_formatter;
\ No newline at end of file
@@ -0,0 +1,88 @@
+use crate::board::board::Board;
+use super::{chess_move::ChessMove, move_generator::MoveGenerator, notation};
+
+
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum NotationStyle {
+    // Nf3, exd5, O-O
+    San,
+    // g1f3, e4d5, e1g1
+    Uci,
+    // Ng1-f3, e4xd5, O-O
+    LongAlgebraic,
+    // ♘f3, exd5, O-O
+    FigurineAlgebraic,
+}
+
+
+// Renders moves for display in whichever notation the consumer selected
+pub struct MoveFormatter<'a> {
+    move_generator: &'a MoveGenerator,
+    style: NotationStyle,
+}
+
+impl<'a> MoveFormatter<'a> {
+    pub fn new(move_generator: &'a MoveGenerator, style: NotationStyle) -> Self {
+        Self {
+            move_generator,
+            style,
+        }
+    }
+
+    pub fn get_style(&self) -> NotationStyle {
+        self.style
+    }
+
+    pub fn set_style(&mut self, style: NotationStyle) {
+        self.style = style;
+    }
+
+    // The move must be legal in the given position
+    pub fn format(&self, board: &Board, mv: ChessMove) -> String {
+        let mut board = board.clone();
+        self.format_on(&mut board, mv)
+    }
+
+    // Formats a sequence of moves, each played after the one before it
+    pub fn format_line(&self, board: &Board, moves: &[ChessMove]) -> Vec<String> {
+        let mut board = board.clone();
+        moves.iter()
+            .map(|mv| {
+                let text = self.format_on(&mut board, *mv);
+                if mv.is_null() {
+                    board.make_null_move();
+                } else {
+                    board.make_move(*mv);
+                }
+                text
+            })
+            .collect()
+    }
+
+    fn format_on(&self, board: &mut Board, mv: ChessMove) -> String {
+        match self.style {
+            NotationStyle::Uci => mv.to_uci(),
+            _ if mv.is_null() => String::from("--"),
+            NotationStyle::San => notation::san(board, self.move_generator, mv),
+            NotationStyle::LongAlgebraic =>
+                notation::long_algebraic(board, self.move_generator, mv),
+            NotationStyle::FigurineAlgebraic =>
+                figurine(&notation::san(board, self.move_generator, mv)),
+        }
+    }
+}
+
+
+// Piece letters only appear as upper case in SAN, files being lower case
+fn figurine(san: &str) -> String {
+    san.chars()
+        .map(|c| match c {
+            'K' => '♔',
+            'Q' => '♕',
+            'R' => '♖',
+            'B' => '♗',
+            'N' => '♘',
+            _ => c,
+        })
+        .collect()
+}
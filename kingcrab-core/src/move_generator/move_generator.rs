@@ -0,0 +1,464 @@
+use crate::{bitboard::{forward, north_east, north_west, pawn_attack_set,
+        rank_mask, south_east, south_west, BitboardExt}, board::board::Board,
+    definitions::{Bitboard, Castling, NrOf, SQUARE_BITBOARDS}};
+use super::{attacks, chess_move::ChessMove, move_sorter::MoveSorter,
+    magics::{KING_BASE_ATTACKS, KNIGHT_BASE_ATTACKS,
+        PAWN_BLACK_ATTACKS, PAWN_WHITE_ATTACKS}};
+use crate::definitions::{Side, Square, Piece};
+
+
+pub struct MoveGenerator {
+    move_sorter: MoveSorter,
+}
+
+impl MoveGenerator {
+
+    pub fn new() -> Self {
+        let move_sorter = MoveSorter::new();
+        MoveGenerator {
+            move_sorter,
+        }
+    }
+
+    pub fn generate_moves(&self, board: &mut Board) -> Vec<ChessMove> {
+        let mut moves = Vec::new();
+        self.generate_moves_into(board, &mut moves);
+        moves
+    }
+
+    // generate_moves into a buffer the caller keeps between calls, e.g. one
+    // per search ply, so that no node allocates once the buffers have grown
+    pub fn generate_moves_into(&self, board: &mut Board, moves: &mut Vec<ChessMove>) {
+        self.generate_legal_moves_into(board, moves);
+        for mv in moves.iter_mut() {
+            board.make_move(*mv);
+            mv.is_check = board.in_check();
+            mv.is_checkmate = if mv.is_check {
+                !self.exist_legal_moves(board)
+            } else {
+                false
+            };
+            board.undo_move();
+        }
+        self.move_sorter.sort_moves(board, moves);
+    }
+
+    pub fn generate_legal_moves(&self, board: &mut Board) -> Vec<ChessMove> {
+        let mut moves = Vec::new();
+        self.generate_legal_moves_into(board, &mut moves);
+        moves
+    }
+
+    // Replaces the buffer's contents
+    pub fn generate_legal_moves_into(&self, board: &mut Board, moves: &mut Vec<ChessMove>) {
+        moves.clear();
+        self.generate_pseudo_legal_moves(board, moves);
+        moves.retain(|mv| self.is_legal_move(board, *mv));
+    }
+
+    pub fn exist_legal_moves(&self, board: &mut Board) -> bool {
+        let mut pseudo_moves = Vec::new();
+        self.generate_pseudo_legal_moves(board, &mut pseudo_moves);
+        pseudo_moves
+            .into_iter()
+            .any(|mv| self.is_legal_move(board, mv))
+    }
+
+    fn is_legal_move(&self, board: &mut Board, mv: ChessMove) -> bool {
+        board.make_move(mv);
+        let result = !self.is_king_in_check(&board, board.get_opponent());
+        board.undo_move();
+
+        result
+    }
+
+    pub fn is_king_in_check(&self, board: &Board, side: Side) -> bool {
+        if side == board.get_active_side() {
+            return board.in_check();
+        }
+
+        let king_square = board.get_king_square(side);
+        let opposing_side = side.opponent();
+        self.is_square_attacked(board, king_square, opposing_side)
+    }
+
+    pub fn gives_check(&self, board: &Board, mv: ChessMove) -> bool {
+        let side = board.get_active_side();
+        let opponent = board.get_opponent();
+        let king_square = board.get_king_square(opponent);
+
+        let from_bb = SQUARE_BITBOARDS[mv.from as usize];
+        let to_bb = SQUARE_BITBOARDS[mv.to as usize];
+        let moved_piece = mv.promotion.unwrap_or(board.piece_list[mv.from as usize]);
+
+        // Own pieces and occupancy as they will be after the move is played
+        let mut own = *board.get_bitboards(side);
+        own[board.piece_list[mv.from as usize] as usize] &= !from_bb;
+        own[moved_piece as usize] |= to_bb;
+
+        let mut occupancy = (board.get_full_occupancy() & !from_bb) | to_bb;
+
+        if mv.is_en_passant() {
+            let captured_square = match side {
+                Side::White => mv.to as usize - 8,
+                Side::Black => mv.to as usize + 8,
+            };
+            occupancy &= !SQUARE_BITBOARDS[captured_square];
+        }
+
+        if mv.is_king_castling() || mv.is_queen_castling() {
+            let (rook_pos, rook_dest) = match mv.to {
+                Square::G1 => (Square::H1, Square::F1),
+                Square::C1 => (Square::A1, Square::D1),
+                Square::G8 => (Square::H8, Square::F8),
+                Square::C8 => (Square::A8, Square::D8),
+                _ => unreachable!()
+            };
+            let rook_move = SQUARE_BITBOARDS[rook_pos as usize]
+                                | SQUARE_BITBOARDS[rook_dest as usize];
+            own[Piece::Rook as usize] ^= rook_move;
+            occupancy ^= rook_move;
+        }
+
+        // Looking outwards from the enemy king covers both direct checks
+        // by the moved piece and discovered checks by the pieces behind it
+        if own[Piece::Pawn as usize] & self.pawn_attacks(king_square, opponent) != 0 {
+            return true;
+        }
+
+        if own[Piece::Knight as usize] & self.knight_attacks(king_square) != 0 {
+            return true;
+        }
+
+        let rook_like = own[Piece::Rook as usize] | own[Piece::Queen as usize];
+        if self.rook_attacks(king_square, occupancy) & rook_like != 0 {
+            return true;
+        }
+
+        let bishop_like = own[Piece::Bishop as usize] | own[Piece::Queen as usize];
+        if self.bishop_attacks(king_square, occupancy) & bishop_like != 0 {
+            return true;
+        }
+
+        false
+    }
+
+    pub fn rook_attacks(&self, square: Square, occupancy: Bitboard) -> Bitboard {
+        attacks::rook_attacks(square, occupancy)
+    }
+
+    pub fn bishop_attacks(&self, square: Square, occupancy: Bitboard) -> Bitboard {
+        attacks::bishop_attacks(square, occupancy)
+    }
+
+    pub fn queen_attacks(&self, square: Square, occupancy: Bitboard) -> Bitboard {
+        attacks::queen_attacks(square, occupancy)
+    }
+
+    pub fn xray_rook_attacks(&self, square: Square,
+        occupancy: Bitboard, blockers: Bitboard) -> Bitboard {
+        attacks::xray_rook_attacks(square, occupancy, blockers)
+    }
+
+    pub fn xray_bishop_attacks(&self, square: Square,
+        occupancy: Bitboard, blockers: Bitboard) -> Bitboard {
+        attacks::xray_bishop_attacks(square, occupancy, blockers)
+    }
+
+    pub fn xray_queen_attacks(&self, square: Square,
+        occupancy: Bitboard, blockers: Bitboard) -> Bitboard {
+        attacks::xray_queen_attacks(square, occupancy, blockers)
+    }
+
+    pub fn knight_attacks(&self, square: Square) -> Bitboard {
+        attacks::knight_attacks(square)
+    }
+
+    pub fn king_attacks(&self, square: Square) -> Bitboard {
+        attacks::king_attacks(square)
+    }
+
+    pub fn pawn_attacks(&self, square: Square, side: Side) -> Bitboard {
+        attacks::pawn_attacks(square, side)
+    }
+
+    pub fn piece_attacks(&self, piece: Piece, side: Side,
+        square: Square, occupancy: Bitboard) -> Bitboard {
+        attacks::piece_attacks(piece, side, square, occupancy)
+    }
+
+    pub fn mobility(&self, board: &Board, side: Side) -> [u32; NrOf::PIECE_TYPES] {
+        let mut mobility = [0; NrOf::PIECE_TYPES];
+        let opponent = side.opponent();
+        let occupancy = board.get_full_occupancy();
+        let own_pieces = board.get_side_occupancy(side);
+        let enemy_pieces = board.get_side_occupancy(opponent);
+
+        // Squares covered by enemy pawns are not counted as safe
+        let enemy_pawns = board.get_pieces(opponent, Piece::Pawn);
+        let enemy_pawn_attacks = pawn_attack_set(enemy_pawns, opponent);
+        let safe = !own_pieces & !enemy_pawn_attacks;
+
+        for piece in [Piece::King, Piece::Queen, Piece::Rook, Piece::Bishop, Piece::Knight] {
+            for square in board.get_pieces(side, piece).squares() {
+                let attacks = self.piece_attacks(piece, side, square, occupancy);
+                mobility[piece as usize] += (attacks & safe).popcount();
+            }
+        }
+
+        // Pawns are credited with their pushes and captures instead
+        let pawns = board.get_pieces(side, Piece::Pawn);
+        let pushes = forward(pawns, side) & !occupancy;
+        let captures = pawn_attack_set(pawns, side) & enemy_pieces;
+        mobility[Piece::Pawn as usize] = pushes.popcount() + captures.popcount();
+
+        mobility
+    }
+
+    fn is_square_attacked(&self, board: &Board, square: Square, by_side: Side) -> bool {
+        let occupancy = board.get_full_occupancy();
+        let sq = square as usize;
+
+        let pawns = board.get_pieces(by_side, Piece::Pawn);
+        let rooks = board.get_pieces(by_side, Piece::Rook);
+        let knights = board.get_pieces(by_side, Piece::Knight);
+        let bishops = board.get_pieces(by_side, Piece::Bishop);
+        let queens = board.get_pieces(by_side, Piece::Queen);
+        let kings = board.get_pieces(by_side, Piece::King);
+
+
+        let pawn_attackers = match by_side {
+            Side::White => PAWN_BLACK_ATTACKS[sq],
+            Side::Black => PAWN_WHITE_ATTACKS[sq],
+        };
+
+        if pawns & pawn_attackers != 0 {
+            return true;
+        }
+
+
+        if knights & KNIGHT_BASE_ATTACKS[sq] != 0 {
+            return true;
+        }
+
+        if kings & KING_BASE_ATTACKS[sq] != 0 {
+            return true;
+        }
+
+        
+        let rook_like = rooks | queens;
+        let rook_attacks = self.rook_attacks(square, occupancy);
+        if rook_attacks & rook_like != 0 {
+            return true;
+        }
+
+        let bishop_like = bishops | queens;
+        let bishop_attacks = self.bishop_attacks(square, occupancy);
+        if bishop_attacks & bishop_like != 0 {
+            return true;
+        }
+
+        false
+    }
+
+
+    // Appends to moves
+    fn generate_pseudo_legal_moves(&self, board: &Board, moves: &mut Vec<ChessMove>) {
+        let side = board.game_state.active_side;
+        let full_occupancy = board.get_full_occupancy();
+        let own_pieces = board.get_side_occupancy(side);
+        let enemy_pieces = full_occupancy & !own_pieces;
+
+        // Pawns are generated for the whole side at once
+        self.generate_pawn_moves(moves, board, side, full_occupancy, enemy_pieces);
+
+        let pieces = own_pieces & !board.get_pieces(side, Piece::Pawn);
+
+        for from_square in pieces.squares() {
+            let i = from_square as usize;
+            let piece = board.piece_list[i];
+
+            match piece {
+                Piece::Knight => self.generate_knight_moves(moves, i, own_pieces, enemy_pieces),
+                Piece::Bishop => self.generate_bishop_moves(moves, i, full_occupancy,
+                                                            enemy_pieces, Piece::Bishop),
+                Piece::Rook => self.generate_rook_moves(moves, i, full_occupancy,
+                                                        enemy_pieces, Piece::Rook),
+                Piece::Queen => {
+                    self.generate_rook_moves(moves, i, full_occupancy, enemy_pieces, Piece::Queen);
+                    self.generate_bishop_moves(moves, i, full_occupancy, enemy_pieces, Piece::Queen);
+                },
+                Piece::King => self.generate_king_moves(moves, board, i, side,
+                                                        own_pieces, enemy_pieces),
+                Piece::Pawn | Piece::None => unreachable!(),
+            }
+        }
+    }
+
+    fn generate_pawn_moves(&self, pawn_moves: &mut Vec<ChessMove>, board: &Board, side: Side,
+        full_occupancy: Bitboard, enemy_pieces: Bitboard) {
+        let pawns = board.get_pieces(side, Piece::Pawn);
+        let empty = !full_occupancy;
+
+        // Target sets are computed for all pawns at once; the origin square of
+        // each target is recovered by undoing the shift offset
+        let (promotion_rank, double_push_rank, push_offset,
+            west_offset, east_offset, west_captures, east_captures) = match side {
+            Side::White => (
+                rank_mask(7), rank_mask(3), 8, 7, 9,
+                north_west(pawns), north_east(pawns),
+            ),
+            Side::Black => (
+                rank_mask(0), rank_mask(4), -8, -9, -7,
+                south_west(pawns), south_east(pawns),
+            ),
+        };
+
+        let single_pushes = forward(pawns, side) & empty;
+        let double_pushes = forward(single_pushes, side) & empty & double_push_rank;
+
+        // Pawn pushes
+        for to in (single_pushes & !promotion_rank).squares() {
+            let from = Self::offset_square(to, push_offset);
+            pawn_moves.push(ChessMove::quiet(Piece::Pawn, from, to));
+        }
+        for to in (single_pushes & promotion_rank).squares() {
+            let from = Self::offset_square(to, push_offset);
+            Self::push_promotions(pawn_moves, from, to, false);
+        }
+        for to in double_pushes.squares() {
+            let from = Self::offset_square(to, 2 * push_offset);
+            pawn_moves.push(ChessMove::double_pawn_push(from, to));
+        }
+
+        // Pawn captures
+        let ep_bitboard = match board.get_ep_square() {
+            Some(ep_square) => SQUARE_BITBOARDS[ep_square as usize],
+            None => 0,
+        };
+
+        for (captures, offset) in [(west_captures, west_offset), (east_captures, east_offset)] {
+            for to in (captures & enemy_pieces & !promotion_rank).squares() {
+                let from = Self::offset_square(to, offset);
+                pawn_moves.push(ChessMove::capture(Piece::Pawn, from, to));
+            }
+            for to in (captures & enemy_pieces & promotion_rank).squares() {
+                let from = Self::offset_square(to, offset);
+                Self::push_promotions(pawn_moves, from, to, true);
+            }
+            for to in (captures & ep_bitboard).squares() {
+                let from = Self::offset_square(to, offset);
+                pawn_moves.push(ChessMove::en_passant(from, to));
+            }
+        }
+    }
+
+    fn offset_square(to: Square, offset: isize) -> Square {
+        Square::try_from((to as isize - offset) as usize).unwrap()
+    }
+
+    fn push_promotions(moves: &mut Vec<ChessMove>, from: Square, to: Square, is_capture: bool) {
+        for promotion_piece in [Piece::Queen, Piece::Rook, Piece::Bishop, Piece::Knight] {
+            moves.push(ChessMove::promotion(from, to, promotion_piece, is_capture));
+        }
+    }
+
+    fn generate_knight_moves(&self, knight_moves: &mut Vec<ChessMove>, from: usize,
+        own_pieces: Bitboard, enemy_pieces: Bitboard) {
+        let square = Square::try_from(from).unwrap();
+        let knight_attacks = KNIGHT_BASE_ATTACKS[from];
+        let empty = !(own_pieces | enemy_pieces);
+
+        Self::push_piece_moves(knight_moves, Piece::Knight, square,
+                                knight_attacks, empty, enemy_pieces);
+    }
+
+    fn generate_bishop_moves(&self, bishop_moves: &mut Vec<ChessMove>, from: usize,
+        full_occupancy: Bitboard, enemy_pieces: Bitboard, piece_type: Piece) {
+        let square = Square::try_from(from).unwrap();
+        let bishop_attacks = self.bishop_attacks(square, full_occupancy);
+
+        Self::push_piece_moves(bishop_moves, piece_type, square,
+                                bishop_attacks, !full_occupancy, enemy_pieces);
+    }
+
+    fn generate_rook_moves(&self, rook_moves: &mut Vec<ChessMove>, from: usize,
+        full_occupancy: Bitboard, enemy_pieces: Bitboard, piece_type: Piece) {
+        let square = Square::try_from(from).unwrap();
+        let rook_attacks = self.rook_attacks(square, full_occupancy);
+
+        Self::push_piece_moves(rook_moves, piece_type, square,
+                                rook_attacks, !full_occupancy, enemy_pieces);
+    }
+
+    // Only the set bits of the attack bitboard are visited
+    fn push_piece_moves(moves: &mut Vec<ChessMove>, piece: Piece, from: Square,
+        attacks: Bitboard, empty: Bitboard, enemy_pieces: Bitboard) {
+        for to in (attacks & empty).squares() {
+            moves.push(ChessMove::quiet(piece, from, to));
+        }
+        for to in (attacks & enemy_pieces).squares() {
+            moves.push(ChessMove::capture(piece, from, to));
+        }
+    }
+
+    fn generate_king_moves(&self, king_moves: &mut Vec<ChessMove>, board: &Board, from: usize,
+        side: Side, own_pieces: Bitboard, enemy_pieces: Bitboard) {
+        let square = Square::try_from(from).unwrap();
+        let king_attacks = KING_BASE_ATTACKS[from];
+        let empty = !(own_pieces | enemy_pieces);
+
+        // Normal King moves
+        Self::push_piece_moves(king_moves, Piece::King, square,
+                                king_attacks, empty, enemy_pieces);
+
+        // Castling moves
+        let castling_rights = board.game_state.castling;
+        let (kingisde_flag, kingside_squares, queenside_flag,
+            queenside_squares, opponent) = if side == Side::White {
+            (
+                Castling::WhiteKing as u8,
+                [Square::E1, Square::F1, Square::G1, Square::H1],
+                Castling::WhiteQueen as u8,
+                [Square::E1, Square::D1, Square::C1, Square::B1, Square::A1],
+                Side::Black
+            )
+        } else {
+            (
+                Castling::BlackKing as u8,
+                [Square::E8, Square::F8, Square::G8, Square::H8],
+                Castling::BlackQueen as u8,
+                [Square::E8, Square::D8, Square::C8, Square::B8, Square::A8],
+                Side::White
+            )
+        };
+
+        if castling_rights & kingisde_flag != 0 {
+            if board.piece_list[kingside_squares[0] as usize] == Piece::King &&
+                board.piece_list[kingside_squares[1] as usize] == Piece::None &&
+                board.piece_list[kingside_squares[2] as usize] == Piece::None &&
+                board.piece_list[kingside_squares[3] as usize] == Piece::Rook {
+                if !self.is_square_attacked(board, kingside_squares[0], opponent) &&
+                    !self.is_square_attacked(board, kingside_squares[1], opponent) &&
+                    !self.is_square_attacked(board, kingside_squares[2], opponent) {
+                    king_moves.push(ChessMove::castle(
+                                kingside_squares[0], kingside_squares[2], true));
+                }
+            }
+        }
+        if castling_rights & queenside_flag != 0 {
+            if board.piece_list[queenside_squares[0] as usize] == Piece::King &&
+                board.piece_list[queenside_squares[1] as usize] == Piece::None &&
+                board.piece_list[queenside_squares[2] as usize] == Piece::None &&
+                board.piece_list[queenside_squares[3] as usize] == Piece::None &&
+                board.piece_list[queenside_squares[4] as usize] == Piece::Rook {
+                if !self.is_square_attacked(board, queenside_squares[0], opponent) &&
+                    !self.is_square_attacked(board, queenside_squares[1], opponent) &&
+                    !self.is_square_attacked(board, queenside_squares[2], opponent) {
+                    king_moves.push(ChessMove::castle(
+                                queenside_squares[0], queenside_squares[2], false));
+                }
+            }
+        }
+    }
+}
@@ -0,0 +1,96 @@
+use std::cmp::Reverse;
+
+use crate::{board::board::Board, definitions::Piece};
+use super::chess_move::ChessMove;
+
+
+// No legal position has more than 218 moves
+const MAX_MOVES: usize = 256;
+
+// Indexed by Piece discriminant, with Piece::None last
+const PIECE_KINDS: usize = 7;
+
+const PIECE_SCORES: [i32; PIECE_KINDS] = [5000, 900, 500, 325, 300, 100, 0];
+
+// [attacker][victim]; en passant captures land on an empty square and
+// score 0, as do moves of Piece::None
+const MVV_LVA_SCORES: [[i32; PIECE_KINDS]; PIECE_KINDS] = mvv_lva_scores();
+
+const fn mvv_lva_scores() -> [[i32; PIECE_KINDS]; PIECE_KINDS] {
+    let none = Piece::None as usize;
+    let mut scores = [[0; PIECE_KINDS]; PIECE_KINDS];
+    let mut attacker = 0;
+    while attacker < none {
+        let mut victim = 0;
+        while victim < none {
+            scores[attacker][victim] = PIECE_SCORES[victim] * 10 - PIECE_SCORES[attacker];
+            victim += 1;
+        }
+        attacker += 1;
+    }
+    scores
+}
+
+
+pub struct MoveSorter {
+    piece_scores: [i32; PIECE_KINDS],
+    mvv_lva_scores: [[i32; PIECE_KINDS]; PIECE_KINDS],
+}
+
+impl MoveSorter {
+    pub const fn new() -> Self {
+        MoveSorter {
+            piece_scores: PIECE_SCORES,
+            mvv_lva_scores: MVV_LVA_SCORES,
+        }
+    }
+
+
+    // Highest score first, moves with equal scores keeping their order.
+    // An insertion sort with the scores on the stack, so sorting allocates
+    // nothing and the buffer the moves came in is reused
+    pub fn sort_moves(&self, board: &Board, moves: &mut [ChessMove]) {
+        if moves.len() > MAX_MOVES {
+            moves.sort_by_key(|mv| Reverse(self.score_move(board, mv)));
+            return;
+        }
+
+        let mut scores = [0; MAX_MOVES];
+        for index in 0..moves.len() {
+            let mv = moves[index];
+            let score = self.score_move(board, &mv);
+            let mut slot = index;
+            while slot > 0 && scores[slot - 1] < score {
+                scores[slot] = scores[slot - 1];
+                moves[slot] = moves[slot - 1];
+                slot -= 1;
+            }
+            scores[slot] = score;
+            moves[slot] = mv;
+        }
+    }
+
+    fn score_move(&self, board: &Board, mv: &ChessMove) -> i32 {
+        if mv.is_checkmate {
+            100_000
+        } else {
+            if mv.is_capture() {
+            let attacker = board.piece_list[mv.from as usize];
+            let victim = board.piece_list[mv.to as usize];
+            self.mvv_lva_scores[attacker as usize][victim as usize]
+            } else {
+                if mv.is_promotion() {
+                    let piece = mv.promotion.unwrap();
+                    self.piece_scores[piece as usize]
+                } else {
+                    if mv.is_check {
+                        500
+                    }
+                    else {
+                        0
+                    }
+                }
+            }
+        }
+    }
+}
@@ -0,0 +1,248 @@
+use core::fmt;
+use std::{fmt::Display, str::FromStr};
+
+use crate::{board::board::Board, definitions::{Piece, Square}};
+use super::{chess_move::ChessMove, move_generator::MoveGenerator};
+
+
+#[derive(Debug)]
+pub enum NotationError {
+    InvalidFormat(String),
+    IllegalMove(String),
+}
+
+impl Display for NotationError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::InvalidFormat(message) =>
+                    write!(f, "Invalid move notation: {message}"),
+            Self::IllegalMove(message) =>
+                    write!(f, "Illegal move in this position: {message}"),
+        }
+    }
+}
+
+
+// Standard algebraic notation for a legal move in the given position
+pub fn san(board: &mut Board, move_generator: &MoveGenerator, mv: ChessMove) -> String {
+    let mut san = String::new();
+
+    if mv.is_king_castling() {
+        san.push_str("O-O");
+    } else if mv.is_queen_castling() {
+        san.push_str("O-O-O");
+    } else {
+        let piece = board.piece_list[mv.from as usize];
+
+        if piece == Piece::Pawn {
+            if mv.is_capture() {
+                san.push_str(&mv.from.file().to_string());
+            }
+        } else {
+            san.push_str(&piece.to_string());
+            san.push_str(&disambiguation(board, move_generator, mv, piece));
+        }
+
+        if mv.is_capture() {
+            san.push('x');
+        }
+        san.push_str(&mv.to.to_string());
+
+        if let Some(promotion) = mv.promotion {
+            san.push('=');
+            san.push_str(&promotion.to_string());
+        }
+    }
+
+    san.push_str(check_suffix(board, move_generator, mv));
+    san
+}
+
+// Long algebraic notation names the origin square of every move, e.g. Ng1-f3
+pub fn long_algebraic(board: &mut Board, move_generator: &MoveGenerator, mv: ChessMove) -> String {
+    let mut lan = String::new();
+
+    if mv.is_king_castling() {
+        lan.push_str("O-O");
+    } else if mv.is_queen_castling() {
+        lan.push_str("O-O-O");
+    } else {
+        let piece = board.piece_list[mv.from as usize];
+        if piece != Piece::Pawn {
+            lan.push_str(&piece.to_string());
+        }
+
+        lan.push_str(&mv.from.to_string());
+        lan.push(if mv.is_capture() { 'x' } else { '-' });
+        lan.push_str(&mv.to.to_string());
+
+        if let Some(promotion) = mv.promotion {
+            lan.push('=');
+            lan.push_str(&promotion.to_string());
+        }
+    }
+
+    lan.push_str(check_suffix(board, move_generator, mv));
+    lan
+}
+
+fn check_suffix(board: &mut Board, move_generator: &MoveGenerator, mv: ChessMove) -> &'static str {
+    board.make_move(mv);
+    let suffix = if !board.in_check() {
+        ""
+    } else if move_generator.exist_legal_moves(board) {
+        "+"
+    } else {
+        "#"
+    };
+    board.undo_move();
+    suffix
+}
+
+// File, rank or full origin square needed to tell the move apart from other
+// legal moves of the same piece type to the same square
+fn disambiguation(board: &mut Board, move_generator: &MoveGenerator,
+    mv: ChessMove, piece: Piece) -> String {
+    let rivals: Vec<Square> = move_generator.generate_legal_moves(board)
+        .into_iter()
+        .filter(|other| other.to == mv.to && other.from != mv.from
+            && board.piece_list[other.from as usize] == piece)
+        .map(|other| other.from)
+        .collect();
+
+    if rivals.is_empty() {
+        return String::new();
+    }
+
+    let same_file = rivals.iter().any(|square| square.file() == mv.from.file());
+    let same_rank = rivals.iter().any(|square| square.rank() == mv.from.rank());
+
+    if !same_file {
+        mv.from.file().to_string()
+    } else if !same_rank {
+        mv.from.rank().to_string()
+    } else {
+        mv.from.to_string()
+    }
+}
+
+
+// Splits coordinate notation such as "e2e4" or "e7e8q" into its squares and
+// promotion piece
+pub fn parse_uci(uci: &str) -> Result<(Square, Square, Option<Piece>), NotationError> {
+    if !uci.is_ascii() || !(4..=5).contains(&uci.len()) {
+        return Err(NotationError::InvalidFormat(uci.to_string()));
+    }
+
+    let from = Square::from_str(&uci[0..2])
+        .map_err(|_| NotationError::InvalidFormat(uci.to_string()))?;
+    let to = Square::from_str(&uci[2..4])
+        .map_err(|_| NotationError::InvalidFormat(uci.to_string()))?;
+    let promotion = match uci[4..].chars().next() {
+        None => None,
+        Some('q') => Some(Piece::Queen),
+        Some('r') => Some(Piece::Rook),
+        Some('b') => Some(Piece::Bishop),
+        Some('n') => Some(Piece::Knight),
+        Some(_) => return Err(NotationError::InvalidFormat(uci.to_string())),
+    };
+
+    Ok((from, to, promotion))
+}
+
+// Resolves coordinate notation to a legal move
+pub fn move_from_uci(board: &mut Board, move_generator: &MoveGenerator,
+    uci: &str) -> Result<ChessMove, NotationError> {
+    let (from, to, promotion) = parse_uci(uci)?;
+
+    move_generator.generate_legal_moves(board)
+        .into_iter()
+        .find(|mv| mv.from == from && mv.to == to && mv.promotion == promotion)
+        .ok_or_else(|| NotationError::IllegalMove(uci.to_string()))
+}
+
+// Resolves standard algebraic notation (e.g. "Nxe5+", "exd6", "e8=Q", "O-O")
+// to a legal move. Check marks and annotation glyphs are ignored
+pub fn move_from_san(board: &mut Board, move_generator: &MoveGenerator,
+    san: &str) -> Result<ChessMove, NotationError> {
+    let invalid = || NotationError::InvalidFormat(san.to_string());
+
+    let text = san.trim()
+        .trim_end_matches("e.p.")
+        .trim_end()
+        .trim_end_matches(['+', '#', '!', '?']);
+
+    let legal_moves = move_generator.generate_legal_moves(board);
+
+    let candidates: Vec<ChessMove> = match text {
+        "O-O" | "0-0" => legal_moves.into_iter()
+            .filter(|mv| mv.is_king_castling()).collect(),
+        "O-O-O" | "0-0-0" => legal_moves.into_iter()
+            .filter(|mv| mv.is_queen_castling()).collect(),
+        _ => {
+            if !text.is_ascii() {
+                return Err(invalid());
+            }
+            let mut chars: Vec<char> = text.chars().collect();
+
+            let piece = match chars.first() {
+                Some('K') => Piece::King,
+                Some('Q') => Piece::Queen,
+                Some('R') => Piece::Rook,
+                Some('B') => Piece::Bishop,
+                Some('N') => Piece::Knight,
+                Some(_) => Piece::Pawn,
+                None => return Err(invalid()),
+            };
+            if piece != Piece::Pawn {
+                chars.remove(0);
+            }
+
+            let promotion = match chars.last() {
+                Some('Q') => Some(Piece::Queen),
+                Some('R') => Some(Piece::Rook),
+                Some('B') => Some(Piece::Bishop),
+                Some('N') => Some(Piece::Knight),
+                _ => None,
+            };
+            if promotion.is_some() {
+                chars.pop();
+                if chars.last() == Some(&'=') {
+                    chars.pop();
+                }
+            }
+
+            if chars.len() < 2 {
+                return Err(invalid());
+            }
+            let destination: String = chars.split_off(chars.len() - 2).into_iter().collect();
+            let to = Square::from_str(&destination).map_err(|_| invalid())?;
+
+            // What is left is the optional origin file/rank and capture mark
+            let mut from_file = None;
+            let mut from_rank = None;
+            for c in chars {
+                match c {
+                    'a'..='h' => from_file = Some(c),
+                    '1'..='8' => from_rank = Some(c),
+                    'x' | ':' | '-' => (),
+                    _ => return Err(invalid()),
+                }
+            }
+
+            legal_moves.into_iter()
+                .filter(|mv| mv.to == to && mv.promotion == promotion
+                    && !mv.is_king_castling() && !mv.is_queen_castling()
+                    && board.piece_list[mv.from as usize] == piece
+                    && from_file.is_none_or(|file| mv.from.file().to_string() == file.to_string())
+                    && from_rank.is_none_or(|rank| mv.from.rank().to_string() == rank.to_string()))
+                .collect()
+        }
+    };
+
+    match candidates.len() {
+        1 => Ok(candidates[0]),
+        0 => Err(NotationError::IllegalMove(san.to_string())),
+        _ => Err(NotationError::InvalidFormat(format!("{san} is ambiguous"))),
+    }
+}
@@ -0,0 +1,158 @@
+use rayon::prelude::*;
+
+use crate::{board::board::Board, definitions::ZobristKey};
+use super::{chess_move::ChessMove, move_generator::MoveGenerator};
+
+
+#[derive(Clone, Copy)]
+pub struct PerftEntry {
+    pub zobrist: ZobristKey,
+    pub depth: u8,
+    pub nodes: u64,
+}
+
+pub struct PerftTable {
+    entries: Vec<Option<PerftEntry>>,
+    mask: usize,
+}
+
+impl PerftTable {
+    pub fn new(size_bits: usize) -> Self {
+        let size = 1 << size_bits;
+        PerftTable {
+            entries: vec![None; size],
+            mask: size - 1,
+        }
+    }
+
+    fn index(&self, zobrist: ZobristKey, depth: u8) -> usize {
+        // Mix the depth in so the same position at different depths
+        // does not always compete for a single slot
+        ((zobrist ^ (depth as u64).wrapping_mul(0x9E37_79B9_7F4A_7C15)) as usize) & self.mask
+    }
+
+    pub fn store(&mut self, zobrist: ZobristKey, depth: u8, nodes: u64) {
+        let idx = self.index(zobrist, depth);
+        self.entries[idx] = Some(PerftEntry { zobrist, depth, nodes });
+    }
+
+    pub fn retrieve(&self, zobrist: ZobristKey, depth: u8) -> Option<u64> {
+        let idx = self.index(zobrist, depth);
+        self.entries[idx]
+            .filter(|e| e.zobrist == zobrist && e.depth == depth)
+            .map(|e| e.nodes)
+    }
+
+    pub fn clear(&mut self) {
+        self.entries.iter_mut().for_each(|entry| *entry = None);
+    }
+}
+
+
+pub fn perft(board: &mut Board, move_generator: &MoveGenerator, depth: u8) -> u64 {
+    if depth == 0 {
+        return 1;
+    }
+
+    let moves = move_generator.generate_legal_moves(board);
+    if depth == 1 {
+        return moves.len() as u64;
+    }
+
+    let mut nodes = 0;
+    for mv in moves {
+        board.make_move(mv);
+        nodes += perft(board, move_generator, depth - 1);
+        board.undo_move();
+    }
+
+    nodes
+}
+
+pub fn parallel_perft(board: &Board, move_generator: &MoveGenerator, depth: u8) -> u64 {
+    if depth <= 1 {
+        return perft(&mut board.clone(), move_generator, depth);
+    }
+
+    let mut root = board.clone();
+    let moves = move_generator.generate_legal_moves(&mut root);
+
+    // Split at the root: every worker gets its own copy of the board
+    moves
+        .par_iter()
+        .map(|mv| {
+            let mut child = board.clone();
+            child.make_move(*mv);
+            perft(&mut child, move_generator, depth - 1)
+        })
+        .sum()
+}
+
+pub fn hashed_perft(board: &mut Board, move_generator: &MoveGenerator,
+    depth: u8, table: &mut PerftTable) -> u64 {
+    if depth == 0 {
+        return 1;
+    }
+
+    let zobrist = board.game_state.zobrist_key;
+    if let Some(nodes) = table.retrieve(zobrist, depth) {
+        return nodes;
+    }
+
+    let moves = move_generator.generate_legal_moves(board);
+    let nodes = if depth == 1 {
+        moves.len() as u64
+    } else {
+        let mut nodes = 0;
+        for mv in moves {
+            board.make_move(mv);
+            nodes += hashed_perft(board, move_generator, depth - 1, table);
+            board.undo_move();
+        }
+        nodes
+    };
+
+    table.store(zobrist, depth, nodes);
+    nodes
+}
+
+pub fn divide(board: &mut Board, move_generator: &MoveGenerator,
+    depth: u8, mut table: Option<&mut PerftTable>) -> Vec<(ChessMove, u64)> {
+    let mut results = Vec::new();
+    if depth == 0 {
+        return results;
+    }
+
+    let moves = move_generator.generate_legal_moves(board);
+    for mv in moves {
+        board.make_move(mv);
+        let nodes = match table.as_deref_mut() {
+            Some(table) => hashed_perft(board, move_generator, depth - 1, table),
+            None => perft(board, move_generator, depth - 1),
+        };
+        board.undo_move();
+        results.push((mv, nodes));
+    }
+
+    results
+}
+
+pub fn print_divide(board: &mut Board, move_generator: &MoveGenerator,
+    depth: u8, table: Option<&mut PerftTable>) -> u64 {
+    let results = divide(board, move_generator, depth, table);
+    let mut total = 0;
+
+    for (mv, nodes) in &results {
+        let promotion = match mv.promotion {
+            Some(piece) => piece.to_string().to_ascii_lowercase(),
+            None => String::new(),
+        };
+        println!("{}{}{}: {}", mv.from, mv.to, promotion, nodes);
+        total += nodes;
+    }
+
+    println!("\nMoves: {}", results.len());
+    println!("Nodes: {}", total);
+
+    total
+}
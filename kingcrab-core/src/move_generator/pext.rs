@@ -0,0 +1,55 @@
+use crate::definitions::Bitboard;
+
+
+// PEXT is only worth using where it runs in hardware; AMD implemented it
+// in microcode before Zen 3 (family 0x19), which is slower than magics.
+#[cfg(target_arch = "x86_64")]
+pub fn fast_pext_available() -> bool {
+    use std::arch::x86_64::__cpuid;
+
+    if !is_x86_feature_detected!("bmi2") {
+        return false;
+    }
+
+    let vendor = __cpuid(0);
+    let is_amd = vendor.ebx == u32::from_le_bytes(*b"Auth")
+        && vendor.edx == u32::from_le_bytes(*b"enti")
+        && vendor.ecx == u32::from_le_bytes(*b"cAMD");
+
+    if is_amd {
+        let signature = __cpuid(1).eax;
+        let base_family = (signature >> 8) & 0xF;
+        let family = if base_family == 0xF {
+            base_family + ((signature >> 20) & 0xFF)
+        } else {
+            base_family
+        };
+        return family >= 0x19;
+    }
+
+    true
+}
+
+#[cfg(not(target_arch = "x86_64"))]
+pub fn fast_pext_available() -> bool {
+    false
+}
+
+#[cfg(target_arch = "x86_64")]
+#[target_feature(enable = "bmi2")]
+unsafe fn pext_bmi2(value: Bitboard, mask: Bitboard) -> Bitboard {
+    std::arch::x86_64::_pext_u64(value, mask)
+}
+
+#[cfg(target_arch = "x86_64")]
+#[inline]
+pub fn pext(value: Bitboard, mask: Bitboard) -> usize {
+    // Only called after fast_pext_available() confirmed BMI2 support
+    unsafe { pext_bmi2(value, mask) as usize }
+}
+
+#[cfg(not(target_arch = "x86_64"))]
+#[inline]
+pub fn pext(_value: Bitboard, _mask: Bitboard) -> usize {
+    unreachable!()
+}
@@ -0,0 +1,251 @@
+// This file is also compiled into build.rs to generate the attack tables,
+// so it must stay free of any dependency on the rest of the crate.
+#![allow(dead_code)]
+
+
+pub const ROOK_MAGICS: [u64; 64] = [
+    0x80004000908020,
+    0xc40004120001006,
+    0x2100090140112000,
+    0x2080280070008024,
+    0x200140910200200,
+    0x1100140042210008,
+    0x2080010040800600,
+    0xa00050400802042,
+    0x4001800081400020,
+    0xd010804008200080,
+    0x1002003110042,
+    0x1054800800100080,
+    0x2c2000601201008,
+    0x102000600259008,
+    0x802000d08040200,
+    0x19002082004b00,
+    0x8029808000214010,
+    0x2010004040006008,
+    0x4410020010113,
+    0x8810030011610088,
+    0xa0110005000800,
+    0x11040801204c1040,
+    0x10100400080a2990,
+    0x80002000944812c,
+    0xc0208280004000,
+    0x834001002b0180,
+    0x1000450100102002,
+    0x1400a00220030,
+    0x1041021100140800,
+    0x2005200081004,
+    0x142008200480104,
+    0x5008a00005401,
+    0x290400830800080,
+    0x1040110081002040,
+    0x1001108a802000,
+    0x10426202000810,
+    0x3140241101000800,
+    0x40080800200,
+    0x2010885014000201,
+    0x400310442000184,
+    0x2200234000808000,
+    0x4113c200810e0020,
+    0xa102082020040,
+    0x20201001010028,
+    0x404112801010004,
+    0x202001409020030,
+    0x5a0901208040001,
+    0x4008004484060001,
+    0x1248000c00480,
+    0x2090042004400040,
+    0x30001120068080,
+    0x210a80081100380,
+    0x81001005480100,
+    0x101000a04000900,
+    0x8018300201080400,
+    0x20110400408200,
+    0x8025044410208001,
+    0x48120088410022,
+    0x2080090012200041,
+    0x1105601001000409,
+    0x5006002010484482,
+    0x215006c00028801,
+    0x2200aa10130804,
+    0x80810403418022
+];
+
+pub const BISHOP_MAGICS: [u64; 64] = [
+    0x10222004042180a0,
+    0x40810008200c041,
+    0x10451441000008,
+    0x202208200104b90,
+    0x1002021000000000,
+    0x10920d0400006,
+    0xa403280210040400,
+    0x20080cd00a02010,
+    0x222100c1800a421,
+    0x80c0200840858180,
+    0x2009200a2020018,
+    0x10282080201205,
+    0x8004040460000800,
+    0x1000809010680000,
+    0x80340308229001,
+    0x18800410407a020,
+    0x1008006508082808,
+    0x102000430240900,
+    0x8041000802108,
+    0x4008010482004000,
+    0x41c008080a061c0,
+    0x8001000280600600,
+    0x104002101080b21,
+    0x8012224500821000,
+    0x402048000c080800,
+    0x814601b90011100,
+    0x4020404208120042,
+    0x3208080000e02020,
+    0x46820044010410,
+    0x8010048081080100,
+    0x140e020000c80648,
+    0x104014240820880,
+    0x8001044000101008,
+    0x2001011000200404,
+    0x44048099000a0,
+    0x40000e0080180080,
+    0x124030200240094,
+    0x90a0180880818,
+    0x4048082040440,
+    0xc03004688220208,
+    0x1800c610400c1000,
+    0x1044020210400302,
+    0x4023402410000109,
+    0x4080002018000104,
+    0xc6052012028101,
+    0x204010041000200,
+    0x10d01080810900,
+    0x30030101040021,
+    0x821002210001,
+    0x40018404020a0c00,
+    0x2000048048280001,
+    0x1042060184,
+    0x34009d90020a0001,
+    0x1001200410008030,
+    0x230060891040200,
+    0x24a00c0502002020,
+    0x200802082104050,
+    0x4020420200840c20,
+    0x20400b861080801,
+    0xa000020c11089,
+    0x902000004208200,
+    0x80000810010a02,
+    0x80201286680104,
+    0x102500208004082
+];
+
+
+pub fn generate_blocker_permutations(mask: u64) -> Vec<u64> {
+    let mut bits = vec![];
+    for i in 0..64 {
+        if (mask >> i) & 1 != 0 {
+            bits.push(i);
+        }
+    }
+    let mut permutations = Vec::new();
+    for combo in 0..(1 << bits.len()) {
+        let mut b = 0;
+        for (j, &bit) in bits.iter().enumerate() {
+            if (combo >> j) & 1 != 0 {
+                b |= 1 << bit;
+            }
+        }
+        permutations.push(b);
+    }
+    permutations
+}
+
+pub fn compute_rook_attacks(square: usize, blockers: u64) -> u64 {
+    let mut attacks = 0;
+    let rank = square / 8;
+    let file = square % 8;
+
+    for r in (rank + 1)..8 {
+        let sq = r * 8 + file;
+        attacks |= 1 << sq;
+        if blockers & (1 << sq) != 0 { break; }
+    }
+    for r in (0..rank).rev() {
+        let sq = r * 8 + file;
+        attacks |= 1 << sq;
+        if blockers & (1 << sq) != 0 { break; }
+    }
+    for f in (file + 1)..8 {
+        let sq = rank * 8 + f;
+        attacks |= 1 << sq;
+        if blockers & (1 << sq) != 0 { break; }
+    }
+    for f in (0..file).rev() {
+        let sq = rank * 8 + f;
+        attacks |= 1 << sq;
+        if blockers & (1 << sq) != 0 { break; }
+    }
+    attacks
+}
+
+pub fn compute_bishop_attacks(square: usize, blockers: u64) -> u64 {
+    let mut attacks = 0;
+    let rank = square / 8;
+    let file = square % 8;
+
+    // NE
+    let mut r = rank + 1;
+    let mut f = file + 1;
+    while r < 8 && f < 8 {
+        let sq = r * 8 + f;
+        attacks |= 1 << sq;
+        if blockers & (1 << sq) != 0 { break; }
+        r += 1;
+        f += 1;
+    }
+
+    // NW
+    r = rank + 1;
+    f = file.wrapping_sub(1);
+    while r < 8 && f < 8 {
+        let sq = r * 8 + f;
+        attacks |= 1 << sq;
+        if blockers & (1 << sq) != 0 { break; }
+        r += 1;
+        if f == 0 { break; } else { f -= 1; }
+    }
+
+    // SE
+    r = rank.wrapping_sub(1);
+    f = file + 1;
+    while r < 8 && f < 8 {
+        let sq = r * 8 + f;
+        attacks |= 1 << sq;
+        if blockers & (1 << sq) != 0 { break; }
+        if r == 0 { break; } else { r -= 1; }
+        f += 1;
+    }
+
+    // SW
+    r = rank.wrapping_sub(1);
+    f = file.wrapping_sub(1);
+    while r < 8 && f < 8 {
+        let sq = r * 8 + f;
+        attacks |= 1 << sq;
+        if blockers & (1 << sq) != 0 { break; }
+        if r == 0 || f == 0 { break; } else { r -= 1; f -= 1; }
+    }
+
+    attacks
+}
+
+pub fn rook_blocker_mask(square: usize) -> u64 {
+    let rank = square / 8;
+    let file = square % 8;
+    let rank_mask: u64 = 0x7E << (rank * 8);
+    let file_mask: u64 = 0x0001_0101_0101_0100 << file;
+    (rank_mask | file_mask) & !(1 << square)
+}
+
+pub fn bishop_blocker_mask(square: usize) -> u64 {
+    const EDGES: u64 = 0xFF81_8181_8181_81FF;
+    compute_bishop_attacks(square, 0) & !EDGES
+}
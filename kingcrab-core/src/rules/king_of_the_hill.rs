@@ -0,0 +1,25 @@
+use crate::{board::board::Board,
+    definitions::{Bitboard, Piece, Side, Square, SQUARE_BITBOARDS}};
+use super::rules::Rules;
+
+
+const HILL: Bitboard = SQUARE_BITBOARDS[Square::D4 as usize] | SQUARE_BITBOARDS[Square::E4 as usize]
+    | SQUARE_BITBOARDS[Square::D5 as usize] | SQUARE_BITBOARDS[Square::E5 as usize];
+
+
+// Standard chess, except that a king reaching one of the four centre
+// squares wins at once. The four squares touch each other, so only one
+// king can be on them
+#[derive(Clone, Copy, Default, Debug)]
+pub struct KingOfTheHill;
+
+impl Rules for KingOfTheHill {
+    fn get_name(&self) -> &'static str {
+        "kingofthehill"
+    }
+
+    fn variant_winner(&self, board: &Board) -> Option<Side> {
+        [Side::White, Side::Black].into_iter()
+            .find(|&side| board.get_pieces(side, Piece::King) & HILL != 0)
+    }
+}
@@ -0,0 +1,5 @@
+pub mod rules;
+
+pub mod king_of_the_hill;
+
+pub mod variant;
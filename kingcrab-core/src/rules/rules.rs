@@ -0,0 +1,67 @@
+use crate::{board::{board::Board, game_result::{DrawRules, GameResult}},
+    definitions::Side,
+    move_generator::{chess_move::ChessMove, move_generator::MoveGenerator}};
+
+
+// What a variant may change about the game: which of the moves legal in
+// standard chess may be played, and wins on top of checkmate. Move
+// generation, the board and the draw rules stay those of standard chess
+pub trait Rules: Send + Sync {
+    // The variant's name in the UCI_Variant option, e.g. "kingofthehill"
+    fn get_name(&self) -> &'static str;
+
+    // Whether these are the rules of standard chess, whose results
+    // tablebases hold
+    fn is_standard(&self) -> bool {
+        false
+    }
+
+    // Only asked about moves legal in standard chess
+    fn is_legal_move(&self, _board: &Board, _mv: ChessMove) -> bool {
+        true
+    }
+
+    // The winner of a position the variant declares won, e.g. by a king
+    // reaching the centre. Checked before mate and the draw rules, and no
+    // moves are legal once it holds
+    fn variant_winner(&self, _board: &Board) -> Option<Side> {
+        None
+    }
+
+    fn generate_legal_moves(&self, move_generator: &MoveGenerator, board: &mut Board) -> Vec<ChessMove> {
+        if self.variant_winner(board).is_some() {
+            return Vec::new();
+        }
+        let mut moves = move_generator.generate_legal_moves(board);
+        moves.retain(|mv| self.is_legal_move(board, *mv));
+        moves
+    }
+
+    fn game_result(&self, board: &mut Board, move_generator: &MoveGenerator,
+        draw_rules: DrawRules) -> GameResult {
+        if let Some(winner) = self.variant_winner(board) {
+            return GameResult::VariantWin(winner);
+        }
+        if self.generate_legal_moves(move_generator, board).is_empty() {
+            return match board.in_check() {
+                false => GameResult::Stalemate,
+                true => GameResult::Checkmate(board.get_opponent()),
+            };
+        }
+        board.game_result_with_rules(move_generator, draw_rules)
+    }
+}
+
+
+#[derive(Clone, Copy, Default, Debug)]
+pub struct StandardRules;
+
+impl Rules for StandardRules {
+    fn get_name(&self) -> &'static str {
+        "chess"
+    }
+
+    fn is_standard(&self) -> bool {
+        true
+    }
+}
@@ -0,0 +1,62 @@
+use std::{fmt::{self, Display}, str::FromStr};
+
+use super::{king_of_the_hill::KingOfTheHill, rules::{Rules, StandardRules}};
+
+
+// The UCI option announcing the variants, for the engine's reply to "uci"
+pub const UCI_VARIANT_OPTION: &str =
+    "option name UCI_Variant type combo default chess var chess var kingofthehill";
+
+
+// The variants there are rules for. King of the Hill positions are written
+// in standard FEN, so the variant is chosen alongside the position: by the
+// UCI_Variant option or a PGN Variant tag
+#[derive(Clone, Copy, PartialEq, Eq, Default, Debug)]
+pub enum Variant {
+    #[default]
+    Standard,
+    KingOfTheHill,
+}
+
+impl Variant {
+    pub fn get_rules(&self) -> &'static dyn Rules {
+        match self {
+            Variant::Standard => &StandardRules,
+            Variant::KingOfTheHill => &KingOfTheHill,
+        }
+    }
+
+    // The value of a "setoption name UCI_Variant value <variant>" command;
+    // other commands are rejected
+    pub fn from_uci_option(command: &str) -> Result<Self, String> {
+        let tokens: Vec<&str> = command.split_whitespace().collect();
+        match tokens.as_slice() {
+            ["setoption", "name", name, "value", value] if name.eq_ignore_ascii_case("UCI_Variant") =>
+                value.parse(),
+            _ => Err(format!("Not a UCI_Variant option: {command}")),
+        }
+    }
+}
+
+impl Display for Variant {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.get_rules().get_name())
+    }
+}
+
+// Takes the UCI names as well as the names PGN Variant tags use
+impl FromStr for Variant {
+    type Err = String;
+
+    fn from_str(name: &str) -> Result<Self, Self::Err> {
+        let normalized: String = name.chars()
+            .filter(|c| !c.is_whitespace() && *c != '-')
+            .collect::<String>()
+            .to_lowercase();
+        match normalized.as_str() {
+            "chess" | "standard" => Ok(Variant::Standard),
+            "kingofthehill" | "koth" => Ok(Variant::KingOfTheHill),
+            _ => Err(format!("Unknown variant: {name}")),
+        }
+    }
+}
@@ -0,0 +1,81 @@
+// Accuracy as lichess computes it: evals become win probabilities, each
+// move is scored by how much win probability it gave away, and a player's
+// moves are averaged with more weight on the volatile parts of the game,
+// where finding the right move mattered most
+
+// Fitted by lichess to the results of its games
+const WIN_PERCENT_SLOPE: f64 = 0.00368208;
+const ACCURACY_SCALE: f64 = 103.1668100711649;
+const ACCURACY_DECAY: f64 = 0.04354415386753951;
+const ACCURACY_OFFSET: f64 = 3.166924740191411;
+
+// Evals beyond this many centipawns are all the same win
+const WIN_PERCENT_EVAL_CAP: i32 = 1000;
+
+
+// The chance of winning, 0 to 100, of a side whose eval is centipawns.
+// Draws count as half a win
+pub fn win_percent(centipawns: i32) -> f64 {
+    let centipawns = centipawns.clamp(-WIN_PERCENT_EVAL_CAP, WIN_PERCENT_EVAL_CAP) as f64;
+    50.0 + 50.0 * (2.0 / (1.0 + (-WIN_PERCENT_SLOPE * centipawns).exp()) - 1.0)
+}
+
+// 100 for a move that keeps the mover's win percentage, falling towards 0
+// as more of it is lost
+pub fn move_accuracy(win_before: f64, win_after: f64) -> f64 {
+    let lost = (win_before - win_after).max(0.0);
+    (ACCURACY_SCALE * (-ACCURACY_DECAY * lost).exp() - ACCURACY_OFFSET).clamp(0.0, 100.0)
+}
+
+// The mean of a player's move accuracies weighted by volatility, averaged
+// with their harmonic mean, which a single blunder pulls down hard.
+// white_wins holds White's win percentage of every position of the game,
+// one more than there are moves; accuracies and is_players_move one entry
+// per move. None when the player made no moves
+pub fn game_accuracy(white_wins: &[f64], accuracies: &[f64], is_players_move: &[bool]) -> Option<f64> {
+    let weights = volatility_weights(white_wins, accuracies.len());
+
+    let (mut weighted_sum, mut weight_total) = (0.0, 0.0);
+    let (mut inverse_sum, mut count) = (0.0, 0);
+    for ((&accuracy, &weight), _) in accuracies.iter().zip(&weights).zip(is_players_move)
+        .filter(|(_, players_move)| **players_move) {
+        weighted_sum += accuracy * weight;
+        weight_total += weight;
+        // Floored so that a move scored 0 does not make the mean 0
+        inverse_sum += 1.0 / accuracy.max(1.0);
+        count += 1;
+    }
+
+    if count == 0 {
+        return None;
+    }
+    let weighted_mean = weighted_sum / weight_total;
+    let harmonic_mean = count as f64 / inverse_sum;
+    Some((weighted_mean + harmonic_mean) / 2.0)
+}
+
+// One weight per move: the standard deviation of the win percentages in a
+// window of positions around it, between 0.5 and 12. The first moves, which
+// no window starts at, share the first window
+fn volatility_weights(white_wins: &[f64], moves: usize) -> Vec<f64> {
+    if white_wins.len() < 2 {
+        return vec![1.0; moves];
+    }
+    let window_size = (moves / 10).clamp(2, 8).min(white_wins.len());
+    let first_window = &white_wins[..window_size];
+
+    std::iter::repeat_n(first_window, window_size.saturating_sub(2))
+        .chain(white_wins.windows(window_size))
+        .map(|window| standard_deviation(window).clamp(0.5, 12.0))
+        .chain(std::iter::repeat(1.0))
+        .take(moves)
+        .collect()
+}
+
+fn standard_deviation(values: &[f64]) -> f64 {
+    let mean = values.iter().sum::<f64>() / values.len() as f64;
+    let variance = values.iter()
+        .map(|value| (value - mean).powi(2))
+        .sum::<f64>() / values.len() as f64;
+    variance.sqrt()
+}
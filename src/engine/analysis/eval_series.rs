@@ -0,0 +1,66 @@
+use crate::engine::analysis::{accuracy::win_percent, game_analysis::GameAnalysis};
+
+
+pub const EVAL_SERIES_CSV_HEADER: &str = "ply,move,cp,mate,win_percent";
+
+
+// The eval of one position of a game, for plotting an evaluation graph.
+// Everything is from White's point of view
+#[derive(Clone, Debug)]
+pub struct EvalPoint {
+    // Half-moves played, 0 for the starting position
+    pub ply: usize,
+    // The move that led to the position, in SAN
+    pub san: Option<String>,
+    pub centipawns: i32,
+    pub mate: Option<i32>,
+    // 0 to 100, see accuracy::win_percent
+    pub win_percent: f64,
+}
+
+impl EvalPoint {
+    // A row under EVAL_SERIES_CSV_HEADER; missing values are left empty
+    pub fn to_csv(&self) -> String {
+        format!("{},{},{},{},{:.2}",
+            self.ply,
+            self.san.as_deref().unwrap_or(""),
+            self.centipawns,
+            self.mate.map_or(String::new(), |plies| plies.to_string()),
+            self.win_percent)
+    }
+
+    // {"ply": .., "move": "e4" or null, "cp": .., "mate": .. or null, "win": ..}
+    pub fn to_json(&self) -> String {
+        format!("{{\"ply\": {}, \"move\": {}, \"cp\": {}, \"mate\": {}, \"win\": {:.2}}}",
+            self.ply,
+            self.san.as_ref().map_or("null".to_string(), |san| format!("\"{san}\"")),
+            self.centipawns,
+            self.mate.map_or("null".to_string(), |plies| plies.to_string()),
+            self.win_percent)
+    }
+}
+
+
+// One point per position of the analysed game, the starting one included
+pub fn eval_series(analysis: &GameAnalysis) -> Vec<EvalPoint> {
+    analysis.positions.iter()
+        .enumerate()
+        .map(|(ply, position)| EvalPoint {
+            ply,
+            san: ply.checked_sub(1)
+                .and_then(|index| analysis.moves.get(index))
+                .map(|analyzed| analyzed.san.clone()),
+            centipawns: position.centipawns,
+            mate: position.mate,
+            win_percent: win_percent(position.centipawns),
+        })
+        .collect()
+}
+
+pub fn eval_series_to_json(series: &[EvalPoint]) -> String {
+    let points = series.iter()
+        .map(EvalPoint::to_json)
+        .collect::<Vec<_>>()
+        .join(", ");
+    format!("[{points}]")
+}
@@ -0,0 +1,371 @@
+use std::fmt::{self, Display};
+
+use crate::engine::{board::board::Board, definitions::Side,
+    evaluator::evaluator::Evaluator,
+    analysis::accuracy::{game_accuracy, move_accuracy, win_percent},
+    move_generator::{chess_move::ChessMove, move_generator::MoveGenerator, notation},
+    pgn::{game::Game, nag::{NAG_BLUNDER, NAG_DUBIOUS_MOVE, NAG_MISTAKE}},
+    searcher::{score::Score, searcher::Searcher, transposition_table::TranspositionTable}};
+
+
+const DEFAULT_DEPTH: u8 = 4;
+
+// 2^20 entries, kept between the positions of a game, which share most of
+// their subtrees
+const HASH_BITS: usize = 20;
+
+// What a forced mate counts as, in centipawns
+pub const MATE_CENTIPAWNS: i32 = 10000;
+
+// Evals are capped to this before taking the loss, so that a won position
+// going from +15 to +8 does not count as a blunder
+const LOSS_EVAL_CAP: i32 = 1000;
+
+
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum MoveClass {
+    Best,
+    Good,
+    Inaccuracy,
+    Mistake,
+    Blunder,
+}
+
+impl MoveClass {
+    // The NAG the move is annotated with, none for best and good moves
+    pub fn get_nag(&self) -> Option<u8> {
+        match self {
+            MoveClass::Best | MoveClass::Good => None,
+            MoveClass::Inaccuracy => Some(NAG_DUBIOUS_MOVE),
+            MoveClass::Mistake => Some(NAG_MISTAKE),
+            MoveClass::Blunder => Some(NAG_BLUNDER),
+        }
+    }
+}
+
+impl Display for MoveClass {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            MoveClass::Best => write!(f, "best"),
+            MoveClass::Good => write!(f, "good"),
+            MoveClass::Inaccuracy => write!(f, "inaccuracy"),
+            MoveClass::Mistake => write!(f, "mistake"),
+            MoveClass::Blunder => write!(f, "blunder"),
+        }
+    }
+}
+
+
+// Centipawn losses from which a move is an inaccuracy, a mistake or a
+// blunder. Moves that lose nothing are best moves, the rest good ones
+#[derive(Clone, Copy, Debug)]
+pub struct ClassificationThresholds {
+    inaccuracy: i32,
+    mistake: i32,
+    blunder: i32,
+}
+
+impl ClassificationThresholds {
+    pub fn new() -> Self {
+        Self {
+            inaccuracy: 50,
+            mistake: 100,
+            blunder: 300,
+        }
+    }
+
+    pub fn inaccuracy(mut self, centipawns: i32) -> Self {
+        self.inaccuracy = centipawns;
+        self
+    }
+
+    pub fn mistake(mut self, centipawns: i32) -> Self {
+        self.mistake = centipawns;
+        self
+    }
+
+    pub fn blunder(mut self, centipawns: i32) -> Self {
+        self.blunder = centipawns;
+        self
+    }
+
+    pub fn classify(&self, centipawn_loss: i32) -> MoveClass {
+        match centipawn_loss {
+            loss if loss <= 0 => MoveClass::Best,
+            loss if loss >= self.blunder => MoveClass::Blunder,
+            loss if loss >= self.mistake => MoveClass::Mistake,
+            loss if loss >= self.inaccuracy => MoveClass::Inaccuracy,
+            _ => MoveClass::Good,
+        }
+    }
+}
+
+impl Default for ClassificationThresholds {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+
+#[derive(Clone, Copy, Debug)]
+pub struct PositionEval {
+    // From White's point of view, a mate counting as MATE_CENTIPAWNS
+    pub centipawns: i32,
+    // Plies to mate, positive when White mates. 0 once the game ended in
+    // checkmate, where centipawns tells who won
+    pub mate: Option<i32>,
+    // None once the game is over
+    pub best_move: Option<ChessMove>,
+}
+
+impl PositionEval {
+    fn from_score(score: Score, side_to_move: Side, best_move: Option<ChessMove>) -> Self {
+        let white = match side_to_move {
+            Side::White => score,
+            Side::Black => -score,
+        };
+        match white.get_mate_plies() {
+            Some(plies) => PositionEval {
+                centipawns: if white.get_value() > 0.0 { MATE_CENTIPAWNS } else { -MATE_CENTIPAWNS },
+                mate: Some(plies),
+                best_move,
+            },
+            None => PositionEval {
+                centipawns: white.get_value().round() as i32,
+                mate: None,
+                best_move,
+            },
+        }
+    }
+
+    // The eval seen by side, capped for working out losses
+    fn capped_for(&self, side: Side) -> i32 {
+        let centipawns = match side {
+            Side::White => self.centipawns,
+            Side::Black => -self.centipawns,
+        };
+        centipawns.clamp(-LOSS_EVAL_CAP, LOSS_EVAL_CAP)
+    }
+}
+
+
+#[derive(Clone, Debug)]
+pub struct AnalyzedMove {
+    // Half-moves played before this one; the position it was played in is
+    // positions[ply] of the analysis, the one it led to positions[ply + 1]
+    pub ply: usize,
+    pub side: Side,
+    pub played: ChessMove,
+    pub san: String,
+    pub best_san: Option<String>,
+    pub centipawn_loss: i32,
+    pub class: MoveClass,
+    // 0 to 100, from the mover's win percentage before and after the move
+    pub accuracy: f64,
+}
+
+
+#[derive(Clone, Debug)]
+pub struct GameAnalysis {
+    // One more than there are moves, the last being the final position
+    pub positions: Vec<PositionEval>,
+    pub moves: Vec<AnalyzedMove>,
+}
+
+impl GameAnalysis {
+    // Lichess-style accuracy of side's moves, 0 to 100. None when side
+    // made no moves
+    pub fn get_accuracy(&self, side: Side) -> Option<f64> {
+        let white_wins: Vec<f64> = self.positions.iter()
+            .map(|position| win_percent(position.centipawns))
+            .collect();
+        let accuracies: Vec<f64> = self.moves.iter().map(|analyzed| analyzed.accuracy).collect();
+        let is_players_move: Vec<bool> = self.moves.iter().map(|analyzed| analyzed.side == side).collect();
+        game_accuracy(&white_wins, &accuracies, &is_players_move)
+    }
+
+    // Puts the evals on the game's moves, marks inaccuracies, mistakes and
+    // blunders with their NAG and says in a comment which move was best.
+    // The game must be the one that was analysed. Both players' accuracy
+    // goes in the comment before the first move
+    pub fn annotate(&self, game: &mut Game) {
+        let accuracies: Vec<String> = [Side::White, Side::Black].into_iter()
+            .filter_map(|side| self.get_accuracy(side)
+                .map(|accuracy| format!("{} accuracy {accuracy:.1}%", side_name_capitalized(side))))
+            .collect();
+        if !accuracies.is_empty() {
+            let report = accuracies.join(", ");
+            match &mut game.comment {
+                Some(comment) => {
+                    comment.push(' ');
+                    comment.push_str(&report);
+                }
+                None => game.comment = Some(report),
+            }
+        }
+
+        for (game_move, analyzed) in game.moves.iter_mut().zip(&self.moves) {
+            let after = self.positions[analyzed.ply + 1];
+            if after.mate.is_none() {
+                game_move.eval = Some(after.centipawns as f32 / 100.0);
+            }
+
+            let Some(nag) = analyzed.class.get_nag() else {
+                continue;
+            };
+            game_move.add_nag(nag);
+            let label = match analyzed.class {
+                MoveClass::Inaccuracy => "Inaccuracy.",
+                MoveClass::Mistake => "Mistake.",
+                _ => "Blunder.",
+            };
+            match &analyzed.best_san {
+                Some(best_san) => game_move.add_comment(&format!("{label} {best_san} was best.")),
+                None => game_move.add_comment(label),
+            }
+        }
+    }
+
+    // {"positions": [{"cp": .., "mate": .. or null, "best": "e2e4" or null}, ..],
+    //  "moves": [{"ply": .., "side": "white", "move": "e4", "best": "d4" or null,
+    //             "loss": .., "class": "good", "accuracy": ..}, ..],
+    //  "accuracy": {"white": .. or null, "black": .. or null}}
+    pub fn to_json(&self) -> String {
+        let string_or_null = |value: Option<String>| value
+            .map_or("null".to_string(), |value| format!("\"{value}\""));
+
+        let positions = self.positions.iter()
+            .map(|position| format!("{{\"cp\": {}, \"mate\": {}, \"best\": {}}}",
+                position.centipawns,
+                position.mate.map_or("null".to_string(), |plies| plies.to_string()),
+                string_or_null(position.best_move.map(|mv| mv.to_uci()))))
+            .collect::<Vec<_>>()
+            .join(", ");
+        let moves = self.moves.iter()
+            .map(|analyzed| format!("{{\"ply\": {}, \"side\": \"{}\", \"move\": \"{}\", \"best\": {}, \
+                \"loss\": {}, \"class\": \"{}\", \"accuracy\": {:.1}}}",
+                analyzed.ply,
+                side_name(analyzed.side),
+                analyzed.san,
+                string_or_null(analyzed.best_san.clone()),
+                analyzed.centipawn_loss,
+                analyzed.class,
+                analyzed.accuracy))
+            .collect::<Vec<_>>()
+            .join(", ");
+
+        let accuracy = |side| self.get_accuracy(side)
+            .map_or("null".to_string(), |accuracy| format!("{accuracy:.1}"));
+
+        format!("{{\"positions\": [{positions}], \"moves\": [{moves}], \
+            \"accuracy\": {{\"white\": {}, \"black\": {}}}}}",
+            accuracy(Side::White), accuracy(Side::Black))
+    }
+}
+
+
+// Searches every position of a game's mainline to a fixed depth and
+// compares each move played with the best one found. A move other than the
+// best is valued by searching the position after it one ply shallower, so
+// that both lines are equally deep; comparing with the next position's own
+// eval would count the horizon effect of the extra ply as a loss. Losses
+// are in the evaluator's units, which are centipawns for the classical
+// evaluator
+pub struct GameAnalyzer<'a> {
+    evaluator: &'a mut dyn Evaluator,
+    movegen: &'a MoveGenerator,
+    transposition_table: TranspositionTable,
+    depth: u8,
+    thresholds: ClassificationThresholds,
+}
+
+impl<'a> GameAnalyzer<'a> {
+    pub fn new(evaluator: &'a mut dyn Evaluator, movegen: &'a MoveGenerator) -> Self {
+        Self {
+            evaluator,
+            movegen,
+            transposition_table: TranspositionTable::new(HASH_BITS),
+            depth: DEFAULT_DEPTH,
+            thresholds: ClassificationThresholds::new(),
+        }
+    }
+
+    pub fn depth(mut self, depth: u8) -> Self {
+        self.depth = depth.max(1);
+        self
+    }
+
+    pub fn thresholds(mut self, thresholds: ClassificationThresholds) -> Self {
+        self.thresholds = thresholds;
+        self
+    }
+
+    pub fn analyze(&mut self, game: &Game) -> GameAnalysis {
+        let mut board = game.starting_position.clone();
+        let mut positions = vec![self.evaluate_position(&mut board, self.depth)];
+        let mut moves = Vec::new();
+
+        for (ply, game_move) in game.moves.iter().enumerate() {
+            let side = board.get_active_side();
+            let before = positions[ply];
+            let best_san = before.best_move
+                .map(|best_move| notation::san(&mut board, self.movegen, best_move));
+
+            let san = if game_move.mv.is_null() {
+                board.make_null_move();
+                "--".to_string()
+            } else {
+                let san = notation::san(&mut board, self.movegen, game_move.mv);
+                board.make_move(game_move.mv);
+                san
+            };
+            let played = match before.best_move == Some(game_move.mv) {
+                true => before,
+                false => self.evaluate_position(&mut board, self.depth - 1),
+            };
+            let centipawn_loss = (before.capped_for(side) - played.capped_for(side)).max(0);
+            positions.push(self.evaluate_position(&mut board, self.depth));
+            moves.push(AnalyzedMove {
+                ply,
+                side,
+                played: game_move.mv,
+                san,
+                best_san,
+                centipawn_loss,
+                class: self.thresholds.classify(centipawn_loss),
+                accuracy: move_accuracy(win_percent(before.capped_for(side)),
+                    win_percent(played.capped_for(side))),
+            });
+        }
+
+        GameAnalysis { positions, moves }
+    }
+
+    fn evaluate_position(&mut self, board: &mut Board, depth: u8) -> PositionEval {
+        let side = board.get_active_side();
+        if self.movegen.generate_legal_moves(board).is_empty() {
+            let score = if board.in_check() { Score::MATED } else { Score::ZERO };
+            return PositionEval::from_score(score, side, None);
+        }
+
+        let mut searcher = Searcher::new(&mut *self.evaluator, self.movegen, &self.transposition_table);
+        searcher.new_search();
+        let result = searcher.search_move(board, depth, Score::MATED, Score::MATE);
+        PositionEval::from_score(result.score, side, result.best_move)
+    }
+}
+
+
+fn side_name(side: Side) -> &'static str {
+    match side {
+        Side::White => "white",
+        Side::Black => "black",
+    }
+}
+
+fn side_name_capitalized(side: Side) -> &'static str {
+    match side {
+        Side::White => "White",
+        Side::Black => "Black",
+    }
+}
@@ -0,0 +1,5 @@
+pub mod game_analysis;
+
+pub mod accuracy;
+
+pub mod eval_series;
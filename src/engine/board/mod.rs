@@ -1,9 +0,0 @@
-pub mod board;
-
-mod zobrist;
-
-mod game_state;
-
-mod game_history;
-
-pub mod fen;
\ No newline at end of file
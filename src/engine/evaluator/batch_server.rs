@@ -0,0 +1,181 @@
+use std::{sync::{atomic::{AtomicUsize, Ordering}, mpsc::{self, Receiver, RecvTimeoutError, Sender}, Arc},
+    thread::{self, JoinHandle},
+    time::{Duration, Instant}};
+
+use crate::engine::{board::board::Board, evaluator::evaluator::{EvalError, Evaluator}};
+
+
+struct EvalRequest {
+    boards: Vec<Board>,
+    reply: Sender<Result<Vec<f32>, EvalError>>,
+}
+
+enum Message {
+    Evaluate(EvalRequest),
+    Shutdown,
+}
+
+#[derive(Default)]
+struct Counters {
+    batches: AtomicUsize,
+    positions: AtomicUsize,
+}
+
+
+// Owns one model-backed evaluator on its own thread and serves several
+// search threads with it. Requests that arrive close together are merged
+// into a single evaluate_batch call, so threads sharing a GPU session run
+// one large inference instead of queueing up for many small ones
+pub struct BatchServer {
+    sender: Sender<Message>,
+    counters: Arc<Counters>,
+    handle: Option<JoinHandle<()>>,
+}
+
+impl BatchServer {
+    // A batch is run once it holds max_batch_size positions or max_wait
+    // has passed since its first request, whichever comes first
+    pub fn spawn(evaluator: Box<dyn Evaluator>, max_batch_size: usize, max_wait: Duration) -> Self {
+        let (sender, receiver) = mpsc::channel();
+        let counters = Arc::new(Counters::default());
+        let thread_counters = Arc::clone(&counters);
+        let handle = thread::Builder::new()
+            .name("batch-eval".to_string())
+            .spawn(move || serve(evaluator, receiver, max_batch_size.max(1), max_wait, &thread_counters))
+            .expect("Failed to spawn the batch evaluation thread");
+
+        BatchServer {
+            sender,
+            counters,
+            handle: Some(handle),
+        }
+    }
+
+    // An evaluator for one search thread. Clients can be cloned into more
+    // threads with clone_for_thread
+    pub fn client(&self) -> BatchClient {
+        BatchClient {
+            sender: self.sender.clone(),
+        }
+    }
+
+    pub fn get_batches_run(&self) -> usize {
+        self.counters.batches.load(Ordering::Relaxed)
+    }
+
+    pub fn get_positions_evaluated(&self) -> usize {
+        self.counters.positions.load(Ordering::Relaxed)
+    }
+}
+
+impl Drop for BatchServer {
+    fn drop(&mut self) {
+        let _ = self.sender.send(Message::Shutdown);
+        if let Some(handle) = self.handle.take() {
+            let _ = handle.join();
+        }
+    }
+}
+
+
+// Sends positions to a BatchServer and waits for their scores. Once the
+// server is gone every call fails, which the searcher answers with its
+// classical fallback
+pub struct BatchClient {
+    sender: Sender<Message>,
+}
+
+impl BatchClient {
+    fn request(&self, boards: Vec<Board>) -> Result<Vec<f32>, EvalError> {
+        let (reply, receiver) = mpsc::channel();
+        self.sender.send(Message::Evaluate(EvalRequest { boards, reply }))
+            .map_err(|_| EvalError::InferenceFailed("batch server stopped".to_string()))?;
+        receiver.recv()
+            .map_err(|_| EvalError::InferenceFailed("batch server stopped".to_string()))?
+    }
+}
+
+impl Evaluator for BatchClient {
+    fn evaluate_board(&mut self, board: &Board) -> Result<f32, EvalError> {
+        let scores = self.request(vec![board.clone()])?;
+        scores.first()
+            .copied()
+            .ok_or_else(|| EvalError::InvalidOutput("no score for the position".to_string()))
+    }
+
+    fn evaluate_batch(&mut self, boards: &[Board]) -> Result<Vec<f32>, EvalError> {
+        if boards.is_empty() {
+            return Ok(Vec::new());
+        }
+        self.request(boards.to_vec())
+    }
+
+    fn clone_for_thread(&self) -> Option<Box<dyn Evaluator>> {
+        Some(Box::new(BatchClient {
+            sender: self.sender.clone(),
+        }))
+    }
+}
+
+
+fn serve(mut evaluator: Box<dyn Evaluator>, receiver: Receiver<Message>, max_batch_size: usize,
+    max_wait: Duration, counters: &Counters) {
+    // Blocks for the first request of each batch, then waits at most
+    // max_wait for more
+    while let Ok(Message::Evaluate(first)) = receiver.recv() {
+        let deadline = Instant::now() + max_wait;
+        let mut size = first.boards.len();
+        let mut requests = vec![first];
+        let mut shutdown = false;
+
+        while size < max_batch_size {
+            let remaining = deadline.saturating_duration_since(Instant::now());
+            match receiver.recv_timeout(remaining) {
+                Ok(Message::Evaluate(request)) => {
+                    size += request.boards.len();
+                    requests.push(request);
+                }
+                Ok(Message::Shutdown) | Err(RecvTimeoutError::Disconnected) => {
+                    shutdown = true;
+                    break;
+                }
+                Err(RecvTimeoutError::Timeout) => break,
+            }
+        }
+
+        let boards: Vec<Board> = requests.iter()
+            .flat_map(|request| request.boards.iter().cloned())
+            .collect();
+        let result = evaluator.evaluate_batch(&boards);
+        counters.batches.fetch_add(1, Ordering::Relaxed);
+        counters.positions.fetch_add(boards.len(), Ordering::Relaxed);
+
+        // Threads that gave up waiting have dropped their receiver, so
+        // failed sends are ignored
+        match result {
+            Ok(scores) if scores.len() == boards.len() => {
+                let mut scores = scores.into_iter();
+                for request in requests {
+                    let reply: Vec<f32> = scores.by_ref().take(request.boards.len()).collect();
+                    let _ = request.reply.send(Ok(reply));
+                }
+            }
+            Ok(scores) => {
+                let error = EvalError::InvalidOutput(
+                    format!("{} scores for a batch of {}", scores.len(), boards.len()));
+                for request in requests {
+                    let _ = request.reply.send(Err(error.clone()));
+                }
+            }
+            Err(error) => {
+                for request in requests {
+                    let _ = request.reply.send(Err(error.clone()));
+                }
+            }
+        }
+
+        if shutdown {
+            break;
+        }
+    }
+}
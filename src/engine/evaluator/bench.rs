@@ -0,0 +1,88 @@
+use std::{fmt::{self, Display}, time::{Duration, Instant}};
+
+use crate::engine::{board::board::Board, evaluator::evaluator::Evaluator};
+
+
+// Middlegame and endgame positions for benchmarks run without positions of
+// their own
+pub const BENCH_FENS: [&str; 8] = [
+    "rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBNR w KQkq - 0 1",
+    "r3k2r/p1ppqpb1/bn2pnp1/3PN3/1p2P3/2N2Q1p/PPPBBPPP/R3K2R w KQkq - 0 1",
+    "r1bq1rk1/pp2bppp/2n1pn2/3p4/2PP4/2N1PN2/PP3PPP/R2QKB1R w KQ - 0 8",
+    "r4rk1/1pp1qppp/p1np1n2/2b1p1B1/2B1P1b1/P1NP1N2/1PP1QPPP/R4RK1 w - - 0 10",
+    "2r3k1/pp3ppp/4p3/3pP3/3P4/P4N2/1P3PPP/2R3K1 b - - 2 24",
+    "8/2p5/3p4/KP5r/1R3p1k/8/4P1P1/8 w - - 0 1",
+    "8/5pk1/6p1/8/3R4/6P1/5PK1/3r4 w - - 0 40",
+    "8/8/4k3/8/2K5/3Q4/8/8 w - - 0 1",
+];
+
+
+#[derive(Clone, Copy, Default, Debug)]
+pub struct EvalBenchReport {
+    pub evaluations: usize,
+    // Evaluations that returned an error; their time is still counted
+    pub errors: usize,
+    pub elapsed: Duration,
+    pub evals_per_second: f64,
+    // Latency of a single evaluate_board call
+    pub p50: Duration,
+    pub p90: Duration,
+    pub p99: Duration,
+    pub max: Duration,
+}
+
+impl Display for EvalBenchReport {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(f, "{} evaluations in {:.2} s ({} errors)",
+            self.evaluations, self.elapsed.as_secs_f64(), self.errors)?;
+        writeln!(f, "{:.0} evals/s", self.evals_per_second)?;
+        write!(f, "latency p50 {:?}, p90 {:?}, p99 {:?}, max {:?}", self.p50, self.p90, self.p99, self.max)
+    }
+}
+
+
+// Evaluates the positions one at a time, cycling through them until the
+// time is up. The first evaluation is a warm-up and is not counted, as
+// model-backed evaluators do their one-off setup on it
+pub fn bench<E: Evaluator + ?Sized>(evaluator: &mut E, positions: &[Board], seconds: f64) -> EvalBenchReport {
+    if positions.is_empty() {
+        return EvalBenchReport::default();
+    }
+    let _ = evaluator.evaluate_board(&positions[0]);
+
+    let budget = Duration::from_secs_f64(seconds.max(0.0));
+    let mut latencies = Vec::new();
+    let mut errors = 0;
+    let start = Instant::now();
+
+    for board in positions.iter().cycle() {
+        let call = Instant::now();
+        if evaluator.evaluate_board(board).is_err() {
+            errors += 1;
+        }
+        latencies.push(call.elapsed());
+
+        if start.elapsed() >= budget {
+            break;
+        }
+    }
+
+    let elapsed = start.elapsed();
+    latencies.sort_unstable();
+    EvalBenchReport {
+        evaluations: latencies.len(),
+        errors,
+        elapsed,
+        evals_per_second: latencies.len() as f64 / elapsed.as_secs_f64(),
+        p50: percentile(&latencies, 50.0),
+        p90: percentile(&latencies, 90.0),
+        p99: percentile(&latencies, 99.0),
+        max: latencies[latencies.len() - 1],
+    }
+}
+
+// Nearest-rank percentile of sorted latencies
+fn percentile(sorted: &[Duration], percent: f64) -> Duration {
+    let rank = (percent / 100.0 * sorted.len() as f64).ceil() as usize;
+    sorted[rank.clamp(1, sorted.len()) - 1]
+}
@@ -0,0 +1,176 @@
+use crate::engine::{board::board::Board,
+    evaluator::{evaluator::{EvalError, Evaluator}, piece_square_tables::MAX_PHASE},
+    move_generator::chess_move::ChessMove};
+
+
+// Material phases with their own scale, from bare kings to the full set
+pub const PHASE_BUCKETS: usize = 4;
+
+
+// A raw evaluation labelled with the game's outcome, both from the side to
+// move's point of view: 1.0 win, 0.5 draw, 0.0 loss
+#[derive(Clone, Copy, Debug)]
+pub struct CalibrationSample {
+    pub raw_eval: f32,
+    pub phase: i32,
+    pub result: f32,
+}
+
+
+// Maps an evaluator's raw output onto centipawns, scaled per material
+// phase, such that win probability = 1 / (1 + 10^(-cp / 400)). Two
+// evaluators calibrated on the same games then share one scale
+#[derive(Clone, PartialEq, Debug)]
+pub struct Calibration {
+    scales: [f32; PHASE_BUCKETS],
+}
+
+impl Calibration {
+    pub fn new() -> Self {
+        Self::with_scale(1.0)
+    }
+
+    pub fn with_scale(scale: f32) -> Self {
+        Calibration {
+            scales: [scale; PHASE_BUCKETS],
+        }
+    }
+
+    pub fn get_scales(&self) -> &[f32; PHASE_BUCKETS] {
+        &self.scales
+    }
+
+    pub fn set_scales(&mut self, scales: [f32; PHASE_BUCKETS]) {
+        self.scales = scales;
+    }
+
+    pub fn to_centipawns(&self, raw_eval: f32, phase: i32) -> f32 {
+        raw_eval * self.scales[phase_bucket(phase)]
+    }
+
+    // Expected score for the side to move
+    pub fn win_probability(&self, raw_eval: f32, phase: i32) -> f32 {
+        centipawns_to_win_probability(self.to_centipawns(raw_eval, phase))
+    }
+
+    pub fn board_centipawns(&self, board: &Board, raw_eval: f32) -> f32 {
+        self.to_centipawns(raw_eval, board.game_phase())
+    }
+
+    pub fn board_win_probability(&self, board: &Board, raw_eval: f32) -> f32 {
+        self.win_probability(raw_eval, board.game_phase())
+    }
+
+    // Fits each phase's scale to the outcomes by minimising the squared
+    // error of the predicted win probability. Buckets without samples keep
+    // their current scale
+    pub fn fit(&mut self, samples: &[CalibrationSample]) {
+        for bucket in 0..PHASE_BUCKETS {
+            let bucket_samples: Vec<&CalibrationSample> = samples.iter()
+                .filter(|sample| phase_bucket(sample.phase) == bucket)
+                .collect();
+            if bucket_samples.is_empty() {
+                continue;
+            }
+
+            let error = |scale: f32| -> f64 {
+                bucket_samples.iter()
+                    .map(|sample| {
+                        let predicted = centipawns_to_win_probability(sample.raw_eval * scale);
+                        let difference = (sample.result - predicted) as f64;
+                        difference * difference
+                    })
+                    .sum()
+            };
+
+            // Searched on a log scale, since raw outputs range from pawns
+            // to millipawns
+            let (mut low, mut high) = (-6.0f32, 6.0f32);
+            for _ in 0..60 {
+                let third = (high - low) / 3.0;
+                let (left, right) = (low + third, high - third);
+                if error(10f32.powf(left)) < error(10f32.powf(right)) {
+                    high = right;
+                } else {
+                    low = left;
+                }
+            }
+            self.scales[bucket] = 10f32.powf((low + high) / 2.0);
+        }
+    }
+}
+
+impl Default for Calibration {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+
+// Scores another evaluator in calibrated centipawns
+pub struct CalibratedEvaluator {
+    inner: Box<dyn Evaluator>,
+    calibration: Calibration,
+}
+
+impl CalibratedEvaluator {
+    pub fn new(inner: Box<dyn Evaluator>, calibration: Calibration) -> Self {
+        CalibratedEvaluator { inner, calibration }
+    }
+
+    pub fn get_calibration(&self) -> &Calibration {
+        &self.calibration
+    }
+
+    pub fn set_calibration(&mut self, calibration: Calibration) {
+        self.calibration = calibration;
+    }
+
+    pub fn win_probability(&mut self, board: &Board) -> Result<f32, EvalError> {
+        let raw_eval = self.inner.evaluate_board(board)?;
+        Ok(self.calibration.board_win_probability(board, raw_eval))
+    }
+}
+
+impl Evaluator for CalibratedEvaluator {
+    fn evaluate_board(&mut self, board: &Board) -> Result<f32, EvalError> {
+        let raw_eval = self.inner.evaluate_board(board)?;
+        Ok(self.calibration.board_centipawns(board, raw_eval))
+    }
+
+    fn evaluate_batch(&mut self, boards: &[Board]) -> Result<Vec<f32>, EvalError> {
+        Ok(boards.iter()
+            .zip(self.inner.evaluate_batch(boards)?)
+            .map(|(board, raw_eval)| self.calibration.board_centipawns(board, raw_eval))
+            .collect())
+    }
+
+    fn on_make_move(&mut self, board: &Board, mv: &ChessMove) {
+        self.inner.on_make_move(board, mv);
+    }
+
+    fn on_undo_move(&mut self) {
+        self.inner.on_undo_move();
+    }
+
+    fn clone_for_thread(&self) -> Option<Box<dyn Evaluator>> {
+        Some(Box::new(CalibratedEvaluator {
+            inner: self.inner.clone_for_thread()?,
+            calibration: self.calibration.clone(),
+        }))
+    }
+
+    fn reload_model(&mut self, path: &str) -> Result<(), String> {
+        self.inner.reload_model(path)
+    }
+}
+
+
+pub fn centipawns_to_win_probability(centipawns: f32) -> f32 {
+    1.0 / (1.0 + 10f32.powf(-centipawns / 400.0))
+}
+
+fn phase_bucket(phase: i32) -> usize {
+    let phase = phase.clamp(0, MAX_PHASE) as usize;
+    (phase * PHASE_BUCKETS / (MAX_PHASE as usize + 1)).min(PHASE_BUCKETS - 1)
+}
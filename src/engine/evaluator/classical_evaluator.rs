@@ -0,0 +1,234 @@
+use crate::engine::{bitboard::{adjacent_files_mask, east, file_mask, forward,
+    forward_fill, pawn_attack_set, west, BitboardExt},
+    board::board::Board,
+    definitions::{Piece, Side, SQUARE_BITBOARDS},
+    evaluator::{eval_params::{EvalParams, EvalWeight}, evaluator::{EvalError, Evaluator},
+        pawn_hash_table::PawnHashTable, piece_square_tables::taper},
+    move_generator::attacks};
+
+
+// 64k entries
+const PAWN_HASH_BITS: usize = 16;
+
+
+// Tapered evaluation in centipawns: the material and piece-square sums the
+// board keeps incrementally, plus pawn structure, king safety, mobility and
+// piece terms weighted by EvalParams. No model files are needed
+pub struct ClassicalEvaluator {
+    params: EvalParams,
+    pawn_table: PawnHashTable,
+}
+
+impl ClassicalEvaluator {
+    pub fn new() -> Self {
+        Self::with_params(EvalParams::new())
+    }
+
+    pub fn with_params(params: EvalParams) -> Self {
+        ClassicalEvaluator {
+            params,
+            pawn_table: PawnHashTable::new(PAWN_HASH_BITS),
+        }
+    }
+
+    pub fn get_params(&self) -> &EvalParams {
+        &self.params
+    }
+
+    // Cached pawn scores were computed with the old weights
+    pub fn set_params(&mut self, params: EvalParams) {
+        self.params = params;
+        self.pawn_table.clear();
+    }
+
+    // Centipawns from the side to move's point of view
+    pub fn evaluate(&mut self, board: &Board) -> i32 {
+        let pawn_key = board.get_pawn_key();
+        let pawn_score = match self.pawn_table.retrieve(pawn_key) {
+            Some(score) => score,
+            None => {
+                let score = self.pawn_score(board);
+                self.pawn_table.store(pawn_key, score);
+                score
+            }
+        };
+        self.evaluate_with(board, pawn_score)
+    }
+
+    // Same as evaluate, without touching the pawn cache, for callers that
+    // share the evaluator across threads
+    pub fn evaluate_uncached(&self, board: &Board) -> i32 {
+        self.evaluate_with(board, self.pawn_score(board))
+    }
+
+    fn evaluate_with(&self, board: &Board, pawn_score: EvalWeight) -> i32 {
+        if board.draw_by_insufficient_material() {
+            return 0;
+        }
+
+        let (mut mg, mut eg) = board.pst_scores();
+        mg += pawn_score.mg;
+        eg += pawn_score.eg;
+        for (side, sign) in [(Side::White, 1), (Side::Black, -1)] {
+            for term in [self.king_attack(board, side), self.pieces(board, side)] {
+                mg += sign * term.mg;
+                eg += sign * term.eg;
+            }
+        }
+
+        let score = taper(mg, eg, board.game_phase());
+        match board.get_active_side() {
+            Side::White => score,
+            Side::Black => -score,
+        }
+    }
+
+    // White-relative sum of the terms that only depend on pawns and kings
+    fn pawn_score(&self, board: &Board) -> EvalWeight {
+        let mut score = EvalWeight::default();
+        for (side, sign) in [(Side::White, 1), (Side::Black, -1)] {
+            add(&mut score, self.pawn_structure(board, side), sign);
+            add(&mut score, self.king_shelter(board, side), sign);
+        }
+        score
+    }
+
+    // Passed, isolated, doubled and backward pawns of one side
+    pub fn pawn_structure(&self, board: &Board, side: Side) -> EvalWeight {
+        let params = &self.params;
+        let opponent = side.opponent();
+        let own_pawns = board.get_pieces(side, Piece::Pawn);
+        let enemy_pawns = board.get_pieces(opponent, Piece::Pawn);
+        let enemy_pawn_attacks = pawn_attack_set(enemy_pawns, opponent);
+
+        let mut score = EvalWeight::default();
+        for square in own_pawns.squares() {
+            let pawn = SQUARE_BITBOARDS[square as usize];
+            let file = square.file() as usize;
+            let relative_rank = match side {
+                Side::White => square.rank() as usize,
+                Side::Black => 7 - square.rank() as usize,
+            };
+
+            let front_span = forward_fill(forward(pawn, side), side);
+            let front_spans = front_span | east(front_span) | west(front_span);
+
+            if front_spans & enemy_pawns == 0 {
+                add(&mut score, params.passed_pawn[relative_rank], 1);
+            }
+
+            let isolated = own_pawns & adjacent_files_mask(file) == 0;
+            if isolated {
+                add(&mut score, params.isolated_pawn, 1);
+            }
+
+            // Only the rear pawn of a doubled pair is counted
+            if front_span & own_pawns != 0 {
+                add(&mut score, params.doubled_pawn, 1);
+            }
+
+            // No friendly pawn beside or behind it can defend its advance,
+            // and an enemy pawn controls the square in front of it
+            let rear_span = forward_fill(pawn, opponent);
+            let supporters = (east(rear_span) | west(rear_span)) & own_pawns;
+            if !isolated && supporters == 0 && forward(pawn, side) & enemy_pawn_attacks != 0 {
+                add(&mut score, params.backward_pawn, 1);
+            }
+        }
+
+        score
+    }
+
+    // Own pawns on the king's file and the files beside it, up to two
+    // ranks in front of the king
+    pub fn king_shelter(&self, board: &Board, side: Side) -> EvalWeight {
+        let king = board.get_pieces(side, Piece::King);
+        let king_files = king | east(king) | west(king);
+        let shelter_zone = forward(king_files, side) | forward(forward(king_files, side), side);
+        let shelter = (shelter_zone & board.get_pieces(side, Piece::Pawn)).popcount() as i32;
+
+        let mut score = EvalWeight::default();
+        add(&mut score, self.params.king_shelter, shelter);
+        score
+    }
+
+    // Enemy pieces hitting the squares around the king
+    pub fn king_attack(&self, board: &Board, side: Side) -> EvalWeight {
+        let params = &self.params;
+        let opponent = side.opponent();
+        let king_square = board.get_king_square(side);
+
+        let king_zone = attacks::king_attacks(king_square) | SQUARE_BITBOARDS[king_square as usize];
+        let occupancy = board.get_full_occupancy();
+        let mut attack_units = 0;
+        for piece in [Piece::Queen, Piece::Rook, Piece::Bishop, Piece::Knight] {
+            for square in board.get_pieces(opponent, piece).squares() {
+                let hits = attacks::piece_attacks(piece, opponent, square, occupancy) & king_zone;
+                attack_units += params.king_attack_units[piece as usize] * hits.popcount() as i32;
+            }
+        }
+
+        let mut score = EvalWeight::default();
+        add(&mut score, params.king_attack, -(attack_units * attack_units) / 16);
+        score
+    }
+
+    // Mobility, rooks on open files and the bishop pair
+    pub fn pieces(&self, board: &Board, side: Side) -> EvalWeight {
+        let params = &self.params;
+        let opponent = side.opponent();
+        let occupancy = board.get_full_occupancy();
+        let own_pawns = board.get_pieces(side, Piece::Pawn);
+        let enemy_pawns = board.get_pieces(opponent, Piece::Pawn);
+
+        // Squares covered by enemy pawns are not counted as safe
+        let safe = !board.get_side_occupancy(side) & !pawn_attack_set(enemy_pawns, opponent);
+
+        let mut score = EvalWeight::default();
+        for piece in [Piece::Queen, Piece::Rook, Piece::Bishop, Piece::Knight] {
+            for square in board.get_pieces(side, piece).squares() {
+                let moves = attacks::piece_attacks(piece, side, square, occupancy) & safe;
+                add(&mut score, params.mobility[piece as usize], moves.popcount() as i32);
+            }
+        }
+
+        for square in board.get_pieces(side, Piece::Rook).squares() {
+            let file = file_mask(square.file() as usize);
+            if file & own_pawns == 0 {
+                match file & enemy_pawns {
+                    0 => add(&mut score, params.rook_open_file, 1),
+                    _ => add(&mut score, params.rook_semi_open_file, 1),
+                }
+            }
+        }
+
+        if board.get_pieces(side, Piece::Bishop).more_than_one() {
+            add(&mut score, params.bishop_pair, 1);
+        }
+
+        score
+    }
+}
+
+impl Default for ClassicalEvaluator {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Evaluator for ClassicalEvaluator {
+    fn evaluate_board(&mut self, board: &Board) -> Result<f32, EvalError> {
+        Ok(self.evaluate(board) as f32)
+    }
+
+    // Each thread gets its own pawn cache
+    fn clone_for_thread(&self) -> Option<Box<dyn Evaluator>> {
+        Some(Box::new(ClassicalEvaluator::with_params(self.params.clone())))
+    }
+}
+
+
+fn add(score: &mut EvalWeight, weight: EvalWeight, count: i32) {
+    score.mg += weight.mg * count;
+    score.eg += weight.eg * count;
+}
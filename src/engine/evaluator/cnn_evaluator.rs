@@ -1,111 +1,132 @@
-use std::collections::HashMap;
+use std::{path::Path, sync::Arc};
 
-use ort::{tensor::OrtOwnedTensor, Environment, SessionBuilder, Value};
-use ndarray::{Array3, Axis, CowArray, IxDyn};
+use ndarray::{s, Array4, Axis};
 use crate::engine::{board::board::Board,
-    definitions::{NrOf, Piece, Side, SQUARE_BITBOARDS},
-    evaluator::evaluator::Evaluator};
+    evaluator::{evaluator::{EvalError, Evaluator}, features::{write_cnn_planes, CNN_CHANNELS},
+        inference_config::InferenceConfig,
+        model_validation::{check_architecture, check_inputs, check_outputs, Dim, ModelError},
+        onnx_session::run_session_with},
+    searcher::score::Score};
 
 
 pub struct CNNEvaluator {
-    session: ort::Session,
-    piece_channels: HashMap<char, usize>,
+    // Shared with the copies made for search threads
+    session: Arc<ort::Session>,
+    config: InferenceConfig,
+    // Input planes for the largest batch seen so far, written in place
+    // and handed to the session without a copy
+    input: Array4<f32>,
 }
 
 impl CNNEvaluator {
     pub fn new(onnx_model_path: &str) -> Result<Self, String> {
-        let environment = std::sync::Arc::new(
-            Environment::builder()
-                .with_name("depthwise-cnn-eval")
-                .build()
-                .map_err(|e| e.to_string())?
-        );
-
-        let session = SessionBuilder::new(&environment)
-            .map_err(|e| e.to_string())?
-            .with_model_from_file(onnx_model_path)
-            .map_err(|e| e.to_string())?;
-
-        let piece_channels = HashMap::from([
-            ('P', 0),
-            ('N', 1),
-            ('B', 2),
-            ('R', 3),
-            ('Q', 4),
-            ('K', 5),
-            ('p', 6),
-            ('n', 7),
-            ('b', 8),
-            ('r', 9),
-            ('q', 10),
-            ('k', 11),
-        ]);
+        Self::with_config(onnx_model_path, &InferenceConfig::new())
+    }
+
+    pub fn with_config(onnx_model_path: &str, config: &InferenceConfig) -> Result<Self, String> {
+        let environment = config.build_environment("depthwise-cnn-eval")?;
+        let session = config.build_session(&environment, onnx_model_path)?;
+        Self::validate(&session, onnx_model_path).map_err(|e| e.to_string())?;
 
         Ok(CNNEvaluator {
-            session,
-            piece_channels,
+            session: Arc::new(session),
+            config: config.clone(),
+            input: Self::input_buffer(1),
         })
     }
 
 
-    fn encode_board(&mut self, board: &Board) -> Array3<f32> {
-        let mut planes = Array3::<f32>::zeros((12, 8, 8));
-
-        for i in 0..NrOf::SQUARES {
-            let piece = board.piece_list[i];
-
-            if piece != Piece::None {
-                let mut piece_char = piece.to_string()
-                    .chars()
-                    .next()
-                    .unwrap_or(' ');
+    // Architecture hashes go in <model>.arch next to the model
+    fn validate(session: &ort::Session, onnx_model_path: &str) -> Result<(), ModelError> {
+        let path = Path::new(onnx_model_path);
+        let name = path.file_name().and_then(|name| name.to_str()).unwrap_or(onnx_model_path);
+        check_architecture(&path.with_extension("arch"), name, &Self::architecture())?;
+        check_inputs(session, name, &[&[Dim::Any, Dim::Exactly(CNN_CHANNELS), Dim::Exactly(8), Dim::Exactly(8)]])?;
+        check_outputs(session, name, &[&[]])
+    }
 
-                let square_bitboard = SQUARE_BITBOARDS[i as usize];
-                let black_occupancy = board.get_side_occupancy(Side::Black);
+    pub fn architecture() -> String {
+        format!("cnn planes={CNN_CHANNELS}x8x8 value=white")
+    }
 
-                if black_occupancy & square_bitboard != 0 {
-                    piece_char = piece_char.to_ascii_lowercase();
-                }
+    fn input_buffer(positions: usize) -> Array4<f32> {
+        Array4::zeros((positions, CNN_CHANNELS, 8, 8))
+    }
 
-                let channel = self.piece_channels.get(&piece_char).unwrap();
+    // One inference over all positions. Fails when the session rejects the
+    // batch, e.g. when the model was exported with a fixed batch size of 1.
+    // The planes carry no side to move, so the model scores for White
+    fn run_batch(&mut self, boards: &[Board]) -> Result<Vec<f32>, EvalError> {
+        if self.input.len_of(Axis(0)) < boards.len() {
+            self.input = Self::input_buffer(boards.len());
+        }
+        for (index, board) in boards.iter().enumerate() {
+            write_cnn_planes(board, self.input.index_axis_mut(Axis(0), index));
+        }
 
-                let rank = i / NrOf::FILES;
-                let file = i % NrOf::FILES;
-                planes[[*channel, rank, file]] = 1.0;
+        let batch = self.input.slice(s![..boards.len(), .., .., ..]).into_dyn();
+        let mut values = Vec::with_capacity(boards.len());
+        let mut outputs = 0;
+        run_session_with(&self.session, vec![batch], |output| {
+            if outputs == 0 {
+                values.extend(output.iter().copied());
             }
-        }
+            outputs += 1;
+        }).map_err(EvalError::InferenceFailed)?;
 
-        planes
+        if outputs == 0 {
+            return Err(EvalError::InvalidOutput("no output tensor".to_string()));
+        }
+        if values.len() != boards.len() {
+            return Err(EvalError::InvalidOutput(
+                format!("{} values for a batch of {}", values.len(), boards.len())));
+        }
+        for (value, board) in values.iter_mut().zip(boards) {
+            *value = Score::from_white(*value, board.get_active_side()).get_value();
+        }
+        Ok(values)
     }
 
 }
 
 impl Evaluator for CNNEvaluator {
-    fn evaluate_board(&mut self, board: &Board) -> f32 {
-        let input_tensor: Array3<f32> = self.encode_board(board);
-
-        let batched = input_tensor.insert_axis(Axis(0));
-        let cow_input: CowArray<f32, IxDyn> = CowArray::from(batched.into_dyn());
-
-        let input = Value::from_array(self.session.allocator(),
-                                            &cow_input)
-                                                    .unwrap();
+    fn evaluate_board(&mut self, board: &Board) -> Result<f32, EvalError> {
+        write_cnn_planes(board, self.input.index_axis_mut(Axis(0), 0));
+        let position = self.input.slice(s![..1, .., .., ..]).into_dyn();
+
+        let mut value = None;
+        run_session_with(&self.session, vec![position], |output| {
+            if value.is_none() {
+                value = Some(output.iter().next().copied());
+            }
+        }).map_err(EvalError::InferenceFailed)?;
 
-        let outputs = self
-            .session
-            .run(vec![input]).unwrap();
+        let value = value.flatten()
+            .ok_or_else(|| EvalError::InvalidOutput("empty output tensor".to_string()))?;
+        Ok(Score::from_white(value, board.get_active_side()).get_value())
+    }
 
+    fn evaluate_batch(&mut self, boards: &[Board]) -> Result<Vec<f32>, EvalError> {
+        if boards.is_empty() {
+            return Ok(Vec::new());
+        }
 
-        let output_tensor: OrtOwnedTensor<f32, IxDyn> = outputs[0]
-        .try_extract()
-        .map_err(|e| format!("Failed to extract output tensor: {e}"))
-                            .unwrap();
+        match self.run_batch(boards) {
+            Ok(values) => Ok(values),
+            Err(_) => boards.iter().map(|board| self.evaluate_board(board)).collect(),
+        }
+    }
 
-        let view = output_tensor.view();
-        let value = *view
-            .iter()
-            .next().unwrap();
+    fn clone_for_thread(&self) -> Option<Box<dyn Evaluator>> {
+        Some(Box::new(CNNEvaluator {
+            session: Arc::clone(&self.session),
+            config: self.config.clone(),
+            input: Self::input_buffer(1),
+        }))
+    }
 
-        value
+    fn reload_model(&mut self, path: &str) -> Result<(), String> {
+        *self = Self::with_config(path, &self.config)?;
+        Ok(())
     }
-}
\ No newline at end of file
+}
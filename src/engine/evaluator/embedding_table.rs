@@ -0,0 +1,113 @@
+use std::path::Path;
+
+use ndarray::{Array1, Array2};
+use ndarray_npy::read_npy;
+
+use crate::engine::evaluator::simd;
+
+
+// Most features active in one perspective at a time: every piece but the
+// perspective's own king
+const MAX_ACTIVE_FEATURES: f32 = 31.0;
+
+
+// Rows of feature embeddings in i16 fixed point, with one scale for the
+// whole table: a stored value v stands for v * scale. Accumulator sums use
+// the same fixed point, so updates are integer adds. The scale is picked so
+// that no sum of active rows leaves the i16 range; the adds wrap, which
+// keeps the final sum exact even when an update passes through an
+// out-of-range intermediate.
+//
+// Float tables are quantized on load. Tables already quantized to i16 or
+// i8, named <name>.npy and paired with <name>_scale.npy holding their
+// scale, are kept as they are when their scale leaves enough headroom
+pub struct EmbeddingTable {
+    weights: Array2<i16>,
+    scale: f32,
+}
+
+impl EmbeddingTable {
+    pub fn load(path: &Path) -> Result<Self, String> {
+        let read_error = |e: &dyn std::fmt::Display| format!("Failed to read {:?}: {}", path, e);
+
+        if let Ok(weights) = read_npy::<_, Array2<f32>>(path) {
+            return Ok(Self::quantize(&weights));
+        }
+        if let Ok(weights) = read_npy::<_, Array2<i16>>(path) {
+            let scale = Self::read_scale(path)?;
+            return Ok(Self::from_quantized(weights, scale));
+        }
+        match read_npy::<_, Array2<i8>>(path) {
+            Ok(weights) => {
+                let scale = Self::read_scale(path)?;
+                Ok(Self::from_quantized(weights.mapv(i16::from), scale))
+            }
+            Err(e) => Err(read_error(&e)),
+        }
+    }
+
+    fn read_scale(path: &Path) -> Result<f32, String> {
+        let stem = path.file_stem().and_then(|stem| stem.to_str()).unwrap_or_default();
+        let scale_path = path.with_file_name(format!("{stem}_scale.npy"));
+        let scale: Array1<f32> = read_npy(&scale_path)
+            .map_err(|e| format!("Failed to read {:?}: {}", scale_path, e))?;
+        scale.first().copied()
+            .ok_or_else(|| format!("{:?} holds no scale", scale_path))
+    }
+
+    pub fn quantize(weights: &Array2<f32>) -> Self {
+        let max_abs = weights.iter().fold(0.0f32, |max, weight| max.max(weight.abs()));
+        let scale = match max_abs {
+            0.0 => 1.0,
+            _ => max_abs * MAX_ACTIVE_FEATURES / i16::MAX as f32,
+        };
+        let weights = weights.mapv(|weight| (weight / scale).round() as i16);
+        EmbeddingTable {
+            weights: weights.as_standard_layout().into_owned(),
+            scale,
+        }
+    }
+
+    // Requantized through f32 when the stored values are too large for
+    // their sums to fit
+    fn from_quantized(weights: Array2<i16>, scale: f32) -> Self {
+        let max_abs = weights.iter().map(|weight| weight.unsigned_abs()).max().unwrap_or(0) as f32;
+        if max_abs * MAX_ACTIVE_FEATURES > i16::MAX as f32 {
+            return Self::quantize(&weights.mapv(|weight| weight as f32 * scale));
+        }
+        // Rows are handed to the kernels as contiguous slices
+        EmbeddingTable {
+            weights: weights.as_standard_layout().into_owned(),
+            scale,
+        }
+    }
+
+    pub fn nrows(&self) -> usize {
+        self.weights.nrows()
+    }
+
+    pub fn ncols(&self) -> usize {
+        self.weights.ncols()
+    }
+
+    pub fn get_scale(&self) -> f32 {
+        self.scale
+    }
+
+    pub fn add_row(&self, accumulator: &mut [i16], index: usize) {
+        simd::add_assign_i16(accumulator, self.row(index));
+    }
+
+    pub fn sub_row(&self, accumulator: &mut [i16], index: usize) {
+        simd::sub_assign_i16(accumulator, self.row(index));
+    }
+
+    // An accumulator sum back in the float units the network was trained in
+    pub fn dequantize(&self, accumulator: &[i16]) -> Vec<f32> {
+        accumulator.iter().map(|value| *value as f32 * self.scale).collect()
+    }
+
+    fn row(&self, index: usize) -> &[i16] {
+        self.weights.row(index).to_slice().unwrap()
+    }
+}
@@ -0,0 +1,465 @@
+use std::{collections::HashMap, fmt::{self, Display}, sync::{Arc, OnceLock}};
+
+use crate::engine::{bitboard::BitboardExt,
+    board::board::Board,
+    definitions::{NrOf, Piece, Side, Square},
+    evaluator::{evaluator::{EvalError, Evaluator}, piece_square_tables::EG_PIECE_VALUES},
+    move_generator::{attacks::{king_attacks, pawn_attacks}, chess_move::ChessMove}};
+
+
+// Well above any ordinary evaluation and well below a mate score, so the
+// search still prefers an actual mate
+pub const KNOWN_WIN: f32 = 10000.0;
+
+const PIECES: [Piece; NrOf::PIECE_TYPES] = [Piece::King, Piece::Queen, Piece::Rook,
+    Piece::Bishop, Piece::Knight, Piece::Pawn];
+
+
+// How many of each piece both sides have
+#[derive(Clone, Copy, PartialEq, Eq, Hash, Debug)]
+pub struct MaterialSignature {
+    counts: [[u8; NrOf::PIECE_TYPES]; NrOf::SIDES],
+}
+
+impl MaterialSignature {
+    pub fn from_board(board: &Board) -> Self {
+        let mut counts = [[0; NrOf::PIECE_TYPES]; NrOf::SIDES];
+        for side in [Side::White, Side::Black] {
+            for piece in PIECES {
+                counts[side as usize][piece as usize] = board.get_pieces(side, piece).popcount() as u8;
+            }
+        }
+        MaterialSignature { counts }
+    }
+
+    // Parses a code such as "KBNK": the pieces up to the second king belong
+    // to the strong side, the rest to the weak side
+    pub fn from_code(code: &str, strong_side: Side) -> Option<Self> {
+        let split = code.char_indices().skip(1).find(|(_, c)| *c == 'K')?.0;
+        let (strong, weak) = code.split_at(split);
+
+        let mut counts = [[0; NrOf::PIECE_TYPES]; NrOf::SIDES];
+        for (side, pieces) in [(strong_side, strong), (strong_side.opponent(), weak)] {
+            for c in pieces.chars() {
+                let piece = match c {
+                    'K' => Piece::King,
+                    'Q' => Piece::Queen,
+                    'R' => Piece::Rook,
+                    'B' => Piece::Bishop,
+                    'N' => Piece::Knight,
+                    'P' => Piece::Pawn,
+                    _ => return None,
+                };
+                counts[side as usize][piece as usize] += 1;
+            }
+        }
+        Some(MaterialSignature { counts })
+    }
+
+    pub fn get_count(&self, side: Side, piece: Piece) -> u8 {
+        self.counts[side as usize][piece as usize]
+    }
+}
+
+impl Display for MaterialSignature {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for side in [Side::White, Side::Black] {
+            for piece in PIECES {
+                for _ in 0..self.get_count(side, piece) {
+                    write!(f, "{piece}")?;
+                }
+            }
+        }
+        Ok(())
+    }
+}
+
+
+// What a recognizer makes of a position
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub enum EndgameVerdict {
+    // Replaces the evaluation; centipawns for the side to move
+    Exact(f32),
+    // Multiplies the evaluation; 0.0 marks a dead draw
+    Scale(f32),
+}
+
+// Called with the side that has the extra material. None leaves the
+// position to the regular evaluation
+pub type Recognizer = fn(&Board, Side) -> Option<EndgameVerdict>;
+
+
+// Recognizers keyed by material signature
+pub struct Endgames {
+    recognizers: HashMap<MaterialSignature, (Side, Recognizer)>,
+}
+
+impl Endgames {
+    // The built-in recognizers
+    pub fn new() -> Self {
+        let mut endgames = Self::empty();
+        endgames.add("KPK", kpk);
+        endgames.add("KRK", kxk);
+        endgames.add("KQK", kxk);
+        endgames.add("KBNK", kbnk);
+        for code in ["KBPK", "KBPPK", "KBPPPK"] {
+            endgames.add(code, wrong_bishop);
+        }
+        endgames
+    }
+
+    pub fn empty() -> Self {
+        Endgames {
+            recognizers: HashMap::new(),
+        }
+    }
+
+    // Registers the recognizer for the code with either side as the strong
+    // one. Invalid codes are ignored
+    pub fn add(&mut self, code: &str, recognizer: Recognizer) {
+        for strong_side in [Side::White, Side::Black] {
+            if let Some(signature) = MaterialSignature::from_code(code, strong_side) {
+                self.recognizers.insert(signature, (strong_side, recognizer));
+            }
+        }
+    }
+
+    pub fn probe(&self, board: &Board) -> Option<EndgameVerdict> {
+        let (strong_side, recognizer) = self.recognizers.get(&MaterialSignature::from_board(board))?;
+        recognizer(board, *strong_side)
+    }
+}
+
+impl Default for Endgames {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+
+// Lets the recognizers override or scale another evaluator. Exact verdicts
+// are in centipawns, so a model with other units should be wrapped in a
+// CalibratedEvaluator first
+pub struct EndgameEvaluator {
+    inner: Box<dyn Evaluator>,
+    // Shared with the copies made for search threads
+    endgames: Arc<Endgames>,
+}
+
+impl EndgameEvaluator {
+    pub fn new(inner: Box<dyn Evaluator>) -> Self {
+        Self::with_endgames(inner, Endgames::new())
+    }
+
+    pub fn with_endgames(inner: Box<dyn Evaluator>, endgames: Endgames) -> Self {
+        EndgameEvaluator {
+            inner,
+            endgames: Arc::new(endgames),
+        }
+    }
+}
+
+impl Evaluator for EndgameEvaluator {
+    fn evaluate_board(&mut self, board: &Board) -> Result<f32, EvalError> {
+        Ok(match self.endgames.probe(board) {
+            Some(EndgameVerdict::Exact(score)) => score,
+            Some(EndgameVerdict::Scale(factor)) => factor * self.inner.evaluate_board(board)?,
+            None => self.inner.evaluate_board(board)?,
+        })
+    }
+
+    // Only the positions without an exact verdict reach the inner evaluator
+    fn evaluate_batch(&mut self, boards: &[Board]) -> Result<Vec<f32>, EvalError> {
+        let verdicts: Vec<Option<EndgameVerdict>> = boards.iter()
+            .map(|board| self.endgames.probe(board))
+            .collect();
+        let pending: Vec<Board> = boards.iter()
+            .zip(&verdicts)
+            .filter(|(_, verdict)| !matches!(verdict, Some(EndgameVerdict::Exact(_))))
+            .map(|(board, _)| board.clone())
+            .collect();
+
+        let mut inner_scores = self.inner.evaluate_batch(&pending)?.into_iter();
+        Ok(verdicts.into_iter()
+            .map(|verdict| match verdict {
+                Some(EndgameVerdict::Exact(score)) => score,
+                Some(EndgameVerdict::Scale(factor)) => factor * inner_scores.next().unwrap_or(0.0),
+                None => inner_scores.next().unwrap_or(0.0),
+            })
+            .collect())
+    }
+
+    fn on_make_move(&mut self, board: &Board, mv: &ChessMove) {
+        self.inner.on_make_move(board, mv);
+    }
+
+    fn on_undo_move(&mut self) {
+        self.inner.on_undo_move();
+    }
+
+    fn clone_for_thread(&self) -> Option<Box<dyn Evaluator>> {
+        Some(Box::new(EndgameEvaluator {
+            inner: self.inner.clone_for_thread()?,
+            endgames: Arc::clone(&self.endgames),
+        }))
+    }
+
+    fn reload_model(&mut self, path: &str) -> Result<(), String> {
+        self.inner.reload_model(path)
+    }
+}
+
+
+// King and pawn against king, looked up in a bitbase
+fn kpk(board: &Board, strong_side: Side) -> Option<EndgameVerdict> {
+    let pawn = board.get_pieces(strong_side, Piece::Pawn).lsb()?;
+    let orient = |square: Square| {
+        let square = match strong_side {
+            Side::White => square as usize,
+            Side::Black => square as usize ^ 56,
+        };
+        // The bitbase only holds pawns on files A to D
+        if pawn as usize % NrOf::FILES > 3 { square ^ 7 } else { square }
+    };
+    let pawn_square = orient(pawn);
+    let strong_to_move = board.get_active_side() == strong_side;
+
+    if !kpk_bitbase().is_win(strong_to_move, orient(board.get_king_square(strong_side)),
+        orient(board.get_king_square(strong_side.opponent())), pawn_square) {
+        return Some(EndgameVerdict::Exact(0.0));
+    }
+
+    let score = KNOWN_WIN + EG_PIECE_VALUES[Piece::Pawn as usize] as f32
+        + 10.0 * (pawn_square / NrOf::FILES) as f32;
+    Some(EndgameVerdict::Exact(for_side_to_move(board, strong_side, score)))
+}
+
+// King and a rook or queen against a bare king: drive the king to the edge
+fn kxk(board: &Board, strong_side: Side) -> Option<EndgameVerdict> {
+    if piece_hangs(board, strong_side) {
+        return None;
+    }
+    let strong_king = board.get_king_square(strong_side);
+    let weak_king = board.get_king_square(strong_side.opponent());
+
+    let score = KNOWN_WIN + material(board, strong_side)
+        + push_to_edge(weak_king) + push_close(strong_king, weak_king);
+    Some(EndgameVerdict::Exact(for_side_to_move(board, strong_side, score)))
+}
+
+// Bishop and knight: the mate only works in a corner of the bishop's colour
+fn kbnk(board: &Board, strong_side: Side) -> Option<EndgameVerdict> {
+    if piece_hangs(board, strong_side) {
+        return None;
+    }
+    let strong_king = board.get_king_square(strong_side);
+    let weak_king = board.get_king_square(strong_side.opponent());
+    let bishop = board.get_pieces(strong_side, Piece::Bishop).lsb()?;
+
+    let corners = match bishop.is_light() {
+        true => [Square::A8, Square::H1],
+        false => [Square::A1, Square::H8],
+    };
+    let corner_distance = corners.iter()
+        .map(|corner| Square::distance(weak_king, *corner))
+        .min()
+        .unwrap_or(0);
+
+    let score = KNOWN_WIN + material(board, strong_side) + push_close(strong_king, weak_king)
+        + 50.0 * (7 - corner_distance) as f32;
+    Some(EndgameVerdict::Exact(for_side_to_move(board, strong_side, score)))
+}
+
+// Bishop and rook pawns whose promotion square the bishop does not cover:
+// drawn if the defending king reaches the corner
+fn wrong_bishop(board: &Board, strong_side: Side) -> Option<EndgameVerdict> {
+    let pawns = board.get_pieces(strong_side, Piece::Pawn);
+    let pawn_file = pawns.lsb()?.file() as usize;
+    let same_file = pawns.squares().all(|pawn| pawn.file() as usize == pawn_file);
+    if !same_file || (pawn_file != 0 && pawn_file != NrOf::FILES - 1) {
+        return None;
+    }
+
+    let promotion_square = match strong_side {
+        Side::White => Square::try_from(56 + pawn_file).unwrap(),
+        Side::Black => Square::try_from(pawn_file).unwrap(),
+    };
+    let bishop = board.get_pieces(strong_side, Piece::Bishop).lsb()?;
+    let weak_king = board.get_king_square(strong_side.opponent());
+
+    if bishop.is_light() != promotion_square.is_light()
+        && Square::distance(weak_king, promotion_square) <= 1 {
+        return Some(EndgameVerdict::Scale(0.0));
+    }
+    None
+}
+
+
+fn for_side_to_move(board: &Board, strong_side: Side, score: f32) -> f32 {
+    if board.get_active_side() == strong_side { score } else { -score }
+}
+
+fn material(board: &Board, side: Side) -> f32 {
+    PIECES.iter()
+        .map(|piece| EG_PIECE_VALUES[*piece as usize] * board.get_pieces(side, *piece).popcount() as i32)
+        .sum::<i32>() as f32
+}
+
+// A piece next to the defending king, with the defender to move and the
+// attacking king too far away to protect it
+fn piece_hangs(board: &Board, strong_side: Side) -> bool {
+    if board.get_active_side() == strong_side {
+        return false;
+    }
+    let strong_king = board.get_king_square(strong_side);
+    let weak_king = board.get_king_square(strong_side.opponent());
+    let pieces = board.get_side_occupancy(strong_side) & !board.get_pieces(strong_side, Piece::King);
+    (pieces & king_attacks(weak_king)).squares()
+        .any(|square| Square::distance(strong_king, square) > 1)
+}
+
+// Grows towards the edges and fastest near the corners, from 27 in the
+// centre to 90 in a corner
+fn push_to_edge(square: Square) -> f32 {
+    let edge_distance = |coordinate: usize| coordinate.min(7 - coordinate) as f32;
+    let file = edge_distance(square.file() as usize);
+    let rank = edge_distance(square.rank() as usize);
+    90.0 - 3.5 * (file * file + rank * rank)
+}
+
+fn push_close(a: Square, b: Square) -> f32 {
+    20.0 * (7 - Square::distance(a, b)) as f32
+}
+
+
+// Every KPK position with the pawn on files A to D, White as the side with
+// the pawn. Built once, on the first probe
+struct KpkBitbase {
+    wins: Vec<bool>,
+}
+
+const KPK_SIZE: usize = 2 * NrOf::SQUARES * NrOf::SQUARES * 24;
+
+#[derive(Clone, Copy, PartialEq)]
+enum KpkResult {
+    Invalid,
+    Unknown,
+    Draw,
+    Win,
+}
+
+fn kpk_bitbase() -> &'static KpkBitbase {
+    static BITBASE: OnceLock<KpkBitbase> = OnceLock::new();
+    BITBASE.get_or_init(KpkBitbase::generate)
+}
+
+impl KpkBitbase {
+    fn is_win(&self, white_to_move: bool, white_king: usize, black_king: usize, pawn: usize) -> bool {
+        self.wins[kpk_index(white_to_move, white_king, black_king, pawn)]
+    }
+
+    // Retrograde analysis: positions are resolved from the ones a move away
+    // until nothing changes, and whatever is still unknown is a draw
+    fn generate() -> Self {
+        let mut results = vec![KpkResult::Invalid; KPK_SIZE];
+        for (index, result) in results.iter_mut().enumerate() {
+            let (white_to_move, white_king, black_king, pawn) = kpk_position(index);
+            *result = kpk_initial(white_to_move, white_king, black_king, pawn);
+        }
+
+        let mut changed = true;
+        while changed {
+            changed = false;
+            for index in 0..KPK_SIZE {
+                if results[index] == KpkResult::Unknown {
+                    let result = kpk_classify(&results, index);
+                    if result != KpkResult::Unknown {
+                        results[index] = result;
+                        changed = true;
+                    }
+                }
+            }
+        }
+
+        KpkBitbase {
+            wins: results.iter().map(|result| *result == KpkResult::Win).collect(),
+        }
+    }
+}
+
+fn kpk_index(white_to_move: bool, white_king: usize, black_king: usize, pawn: usize) -> usize {
+    // Pawns on files A to D and ranks 2 to 7
+    let pawn_index = (pawn % NrOf::FILES) + 4 * (pawn / NrOf::FILES - 1);
+    (((pawn_index * NrOf::SQUARES + black_king) * NrOf::SQUARES + white_king) << 1)
+        | white_to_move as usize
+}
+
+fn kpk_position(index: usize) -> (bool, usize, usize, usize) {
+    let white_to_move = index & 1 == 1;
+    let white_king = (index >> 1) % NrOf::SQUARES;
+    let black_king = (index >> 7) % NrOf::SQUARES;
+    let pawn_index = index >> 13;
+    let pawn = (pawn_index % 4) + NrOf::FILES * (pawn_index / 4 + 1);
+    (white_to_move, white_king, black_king, pawn)
+}
+
+fn kpk_initial(white_to_move: bool, white_king: usize, black_king: usize, pawn: usize) -> KpkResult {
+    let square = |index: usize| Square::try_from(index).unwrap();
+    let (white_king, black_king, pawn) = (square(white_king), square(black_king), square(pawn));
+    let pawn_attacks = pawn_attacks(pawn, Side::White);
+
+    if Square::distance(white_king, black_king) <= 1 || white_king == pawn || black_king == pawn
+        || (white_to_move && pawn_attacks.contains_square(black_king)) {
+        return KpkResult::Invalid;
+    }
+
+    if white_to_move {
+        // Promotes without the new queen being taken
+        let promotion = pawn.forward(Side::White).unwrap();
+        if pawn.rank() as usize == 6 && white_king != promotion
+            && (Square::distance(black_king, promotion) > 1 || Square::distance(white_king, promotion) == 1) {
+            return KpkResult::Win;
+        }
+    } else {
+        let guarded = king_attacks(white_king) | pawn_attacks;
+        // Stalemate, or the pawn falls
+        if king_attacks(black_king) & !guarded == 0
+            || (king_attacks(black_king) & !king_attacks(white_king)).contains_square(pawn) {
+            return KpkResult::Draw;
+        }
+    }
+    KpkResult::Unknown
+}
+
+fn kpk_classify(results: &[KpkResult], index: usize) -> KpkResult {
+    let (white_to_move, white_king, black_king, pawn) = kpk_position(index);
+    let king = if white_to_move { white_king } else { black_king };
+
+    let mut successors: Vec<KpkResult> = king_attacks(Square::try_from(king).unwrap()).squares()
+        .map(|to| match white_to_move {
+            true => results[kpk_index(false, to as usize, black_king, pawn)],
+            false => results[kpk_index(true, white_king, to as usize, pawn)],
+        })
+        .collect();
+
+    if white_to_move && pawn / NrOf::FILES < 6 {
+        let push = pawn + NrOf::FILES;
+        successors.push(results[kpk_index(false, white_king, black_king, push)]);
+        if pawn / NrOf::FILES == 1 && push != white_king && push != black_king {
+            successors.push(results[kpk_index(false, white_king, black_king, push + NrOf::FILES)]);
+        }
+    }
+
+    // Each side picks its best reply; moves into invalid positions are illegal
+    let (good, bad) = match white_to_move {
+        true => (KpkResult::Win, KpkResult::Draw),
+        false => (KpkResult::Draw, KpkResult::Win),
+    };
+    if successors.contains(&good) {
+        good
+    } else if successors.contains(&KpkResult::Unknown) {
+        KpkResult::Unknown
+    } else {
+        bad
+    }
+}
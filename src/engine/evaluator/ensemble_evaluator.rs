@@ -0,0 +1,100 @@
+use crate::engine::{board::board::Board,
+    evaluator::evaluator::{EvalError, Evaluator},
+    move_generator::chess_move::ChessMove};
+
+
+// Weighted sum of several evaluators' scores. The members keep their own
+// scales, so the weights also have to convert between them
+pub struct EnsembleEvaluator {
+    members: Vec<(Box<dyn Evaluator>, f32)>,
+}
+
+impl EnsembleEvaluator {
+    pub fn new() -> Self {
+        EnsembleEvaluator {
+            members: Vec::new(),
+        }
+    }
+
+    pub fn with(mut self, evaluator: Box<dyn Evaluator>, weight: f32) -> Self {
+        self.add(evaluator, weight);
+        self
+    }
+
+    pub fn add(&mut self, evaluator: Box<dyn Evaluator>, weight: f32) {
+        self.members.push((evaluator, weight));
+    }
+
+    pub fn get_weights(&self) -> Vec<f32> {
+        self.members.iter().map(|(_, weight)| *weight).collect()
+    }
+
+    pub fn set_weight(&mut self, index: usize, weight: f32) {
+        if let Some(member) = self.members.get_mut(index) {
+            member.1 = weight;
+        }
+    }
+
+    pub fn len(&self) -> usize {
+        self.members.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.members.is_empty()
+    }
+}
+
+impl Default for EnsembleEvaluator {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Evaluator for EnsembleEvaluator {
+    fn evaluate_board(&mut self, board: &Board) -> Result<f32, EvalError> {
+        self.members.iter_mut()
+            .filter(|(_, weight)| *weight != 0.0)
+            .map(|(evaluator, weight)| Ok(*weight * evaluator.evaluate_board(board)?))
+            .sum()
+    }
+
+    fn evaluate_batch(&mut self, boards: &[Board]) -> Result<Vec<f32>, EvalError> {
+        let mut scores = vec![0.0; boards.len()];
+        for (evaluator, weight) in self.members.iter_mut() {
+            if *weight == 0.0 {
+                continue;
+            }
+            for (score, member_score) in scores.iter_mut().zip(evaluator.evaluate_batch(boards)?) {
+                *score += *weight * member_score;
+            }
+        }
+        Ok(scores)
+    }
+
+    fn evaluate_after_move(&mut self, board: &Board, mv: ChessMove) -> Result<f32, EvalError> {
+        self.members.iter_mut()
+            .filter(|(_, weight)| *weight != 0.0)
+            .map(|(evaluator, weight)| Ok(*weight * evaluator.evaluate_after_move(board, mv)?))
+            .sum()
+    }
+
+    fn on_make_move(&mut self, board: &Board, mv: &ChessMove) {
+        for (evaluator, _) in self.members.iter_mut() {
+            evaluator.on_make_move(board, mv);
+        }
+    }
+
+    fn on_undo_move(&mut self) {
+        for (evaluator, _) in self.members.iter_mut() {
+            evaluator.on_undo_move();
+        }
+    }
+
+    // Only possible when every member can be copied
+    fn clone_for_thread(&self) -> Option<Box<dyn Evaluator>> {
+        let members = self.members.iter()
+            .map(|(evaluator, weight)| Some((evaluator.clone_for_thread()?, *weight)))
+            .collect::<Option<Vec<_>>>()?;
+        Some(Box::new(EnsembleEvaluator { members }))
+    }
+}
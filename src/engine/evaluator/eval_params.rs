@@ -0,0 +1,114 @@
+use crate::engine::definitions::NrOf;
+
+
+// A middlegame and an endgame weight, blended by the game phase
+#[derive(Clone, Copy, PartialEq, Eq, Default, Debug)]
+pub struct EvalWeight {
+    pub mg: i32,
+    pub eg: i32,
+}
+
+impl EvalWeight {
+    pub const fn new(mg: i32, eg: i32) -> Self {
+        EvalWeight { mg, eg }
+    }
+}
+
+
+// Weights of the hand-crafted terms, in centipawns. Arrays indexed by piece
+// follow the Piece order (king, queen, rook, bishop, knight, pawn)
+#[derive(Clone, PartialEq, Debug)]
+pub struct EvalParams {
+    // By the pawn's rank counted from its own side
+    pub passed_pawn: [EvalWeight; NrOf::RANKS],
+    pub isolated_pawn: EvalWeight,
+    pub doubled_pawn: EvalWeight,
+    pub backward_pawn: EvalWeight,
+
+    // Per own pawn on the three files in front of the king
+    pub king_shelter: EvalWeight,
+    // Attack units each enemy piece adds per king-zone square it hits
+    pub king_attack_units: [i32; NrOf::PIECE_TYPES],
+    // Scales the square of the attack units, divided by 16
+    pub king_attack: EvalWeight,
+
+    // Per safe square a piece can move to
+    pub mobility: [EvalWeight; NrOf::PIECE_TYPES],
+    pub rook_open_file: EvalWeight,
+    pub rook_semi_open_file: EvalWeight,
+    pub bishop_pair: EvalWeight,
+}
+
+impl EvalParams {
+    pub fn new() -> Self {
+        EvalParams {
+            passed_pawn: [
+                EvalWeight::new(0, 0), EvalWeight::new(5, 10),
+                EvalWeight::new(5, 15), EvalWeight::new(10, 25),
+                EvalWeight::new(25, 45), EvalWeight::new(45, 80),
+                EvalWeight::new(70, 120), EvalWeight::new(0, 0),
+            ],
+            isolated_pawn: EvalWeight::new(-10, -12),
+            doubled_pawn: EvalWeight::new(-8, -20),
+            backward_pawn: EvalWeight::new(-8, -8),
+
+            king_shelter: EvalWeight::new(12, 0),
+            king_attack_units: [0, 5, 3, 2, 2, 0],
+            king_attack: EvalWeight::new(4, 1),
+
+            mobility: [
+                EvalWeight::new(0, 0), EvalWeight::new(1, 2),
+                EvalWeight::new(2, 4), EvalWeight::new(4, 5),
+                EvalWeight::new(4, 4), EvalWeight::new(0, 0),
+            ],
+            rook_open_file: EvalWeight::new(25, 10),
+            rook_semi_open_file: EvalWeight::new(12, 6),
+            bishop_pair: EvalWeight::new(25, 50),
+        }
+    }
+
+    // All weights flattened in declaration order, for the tuner
+    pub fn to_vector(&self) -> Vec<i32> {
+        self.clone().weights_mut().into_iter().map(|weight| *weight).collect()
+    }
+
+    pub fn from_vector(values: &[i32]) -> Self {
+        let mut params = EvalParams::new();
+        for (weight, value) in params.weights_mut().into_iter().zip(values) {
+            *weight = *value;
+        }
+        params
+    }
+
+    fn weights_mut(&mut self) -> Vec<&mut i32> {
+        let mut weights = Vec::new();
+        for pair in self.passed_pawn.iter_mut() {
+            push_pair(pair, &mut weights);
+        }
+        push_pair(&mut self.isolated_pawn, &mut weights);
+        push_pair(&mut self.doubled_pawn, &mut weights);
+        push_pair(&mut self.backward_pawn, &mut weights);
+        push_pair(&mut self.king_shelter, &mut weights);
+        weights.extend(self.king_attack_units.iter_mut());
+        push_pair(&mut self.king_attack, &mut weights);
+        for pair in self.mobility.iter_mut() {
+            push_pair(pair, &mut weights);
+        }
+        push_pair(&mut self.rook_open_file, &mut weights);
+        push_pair(&mut self.rook_semi_open_file, &mut weights);
+        push_pair(&mut self.bishop_pair, &mut weights);
+        weights
+    }
+}
+
+impl Default for EvalParams {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+
+fn push_pair<'a>(pair: &'a mut EvalWeight, weights: &mut Vec<&'a mut i32>) {
+    weights.push(&mut pair.mg);
+    weights.push(&mut pair.eg);
+}
@@ -1,5 +1,82 @@
-use crate::engine::{board::board::Board};
+use std::fmt::{self, Display};
 
-pub trait Evaluator {
-    fn evaluate_board(&mut self, board: &Board) -> f32;
+use crate::engine::{board::board::Board, evaluator::bench::{self, EvalBenchReport},
+    move_generator::chess_move::ChessMove, searcher::score::Score};
+
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum EvalError {
+    InferenceFailed(String),
+    InvalidOutput(String),
+}
+
+impl Display for EvalError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::InferenceFailed(message) =>
+                    write!(f, "Inference failed: {message}"),
+            Self::InvalidOutput(message) =>
+                    write!(f, "Invalid model output: {message}"),
+        }
+    }
+}
+
+
+// Send so each search thread can own an evaluator
+pub trait Evaluator: Send {
+    // Scores favour the side to move, as with searcher::score::Score.
+    // Evaluators that favour White can be wrapped in WhiteRelativeEvaluator.
+    // Model-backed evaluators return an error when inference fails, and
+    // leave it to the caller to fall back to something else
+    fn evaluate_board(&mut self, board: &Board) -> Result<f32, EvalError>;
+
+    // Scores several positions at once, in order. Evaluators backed by a
+    // model can override this with a single batched inference call
+    fn evaluate_batch(&mut self, boards: &[Board]) -> Result<Vec<f32>, EvalError> {
+        boards.iter().map(|board| self.evaluate_board(board)).collect()
+    }
+
+    // The score of the position after mv, for the side to move there,
+    // without the caller playing the move first, e.g. for futility pruning
+    // decisions. Incremental evaluators can work it out from the move
+    // alone; the default scores a copy of the board with the move played
+    fn evaluate_after_move(&mut self, board: &Board, mv: ChessMove) -> Result<f32, EvalError> {
+        self.evaluate_board(&board.make_move_copy(mv))
+    }
+
+    // Called by the searcher right after a move is played on the board it
+    // searches, so incremental evaluators can follow the line being searched
+    fn on_make_move(&mut self, _board: &Board, _mv: &ChessMove) {}
+
+    // Called right after that move is taken back
+    fn on_undo_move(&mut self) {}
+
+    // A fresh instance for another search thread, sharing read-only model
+    // weights but with its own caches and accumulators. None when the
+    // evaluator cannot be copied
+    fn clone_for_thread(&self) -> Option<Box<dyn Evaluator>> {
+        None
+    }
+
+    // Replaces the model with the one at path, e.g. a network a training
+    // run just produced. On failure the current model stays in use. Copies
+    // made with clone_for_thread keep the model they were made with
+    fn reload_model(&mut self, _path: &str) -> Result<(), String> {
+        Err("This evaluator does not load a model".to_string())
+    }
+
+    // Evaluation throughput and latency over the positions for about the
+    // given number of seconds, to compare evaluators on the machine at hand
+    fn bench(&mut self, positions: &[Board], seconds: f64) -> EvalBenchReport {
+        bench::bench(self, positions, seconds)
+    }
+}
+
+
+// Evaluators whose model also predicts how good each move is, e.g. a network
+// with a value and a policy head. The searcher can order moves by the policy
+pub trait PolicyEvaluator: Evaluator {
+    // The position's score and every legal move with its prior probability,
+    // the priors summing to 1
+    fn evaluate_with_policy(&mut self, board: &Board) -> Result<(Score, Vec<(ChessMove, f32)>), EvalError>;
 }
@@ -0,0 +1,213 @@
+use std::{fmt::{self, Display}, fs, io, path::Path};
+
+use ndarray::{s, Array3, ArrayViewMut3};
+
+use crate::engine::{bitboard::BitboardExt,
+    board::{board::Board, fen::FenError},
+    definitions::{NrOf, Piece, Side, Square},
+    move_generator::chess_move::ChessMove};
+
+
+// Model input encodings, shared by the evaluators and the feature dump so
+// that what gets printed is exactly what inference sees
+
+// HalfKA: 11 piece kinds (both kings share one) x 64 piece squares x 32
+// king squares. Squares are flipped vertically when the perspective king
+// stands on ranks 5-8, which keeps the king on the first four ranks
+pub const HALFKA_PIECE_KINDS: usize = 11;
+pub const HALFKA_KING_SQUARES: usize = 32;
+pub const HALFKA_FEATURES: usize = HALFKA_PIECE_KINDS * NrOf::SQUARES * HALFKA_KING_SQUARES;
+
+// CNN: one 8x8 plane per side and piece, indexed [channel, rank, file]
+pub const CNN_CHANNELS: usize = 12;
+
+// Policy-value models: the CNN planes plus one plane of ones when White is
+// to move. The policy has one logit per from and to square
+pub const POLICY_VALUE_CHANNELS: usize = CNN_CHANNELS + 1;
+pub const POLICY_SIZE: usize = NrOf::SQUARES * NrOf::SQUARES;
+
+const PIECE_ORDER: [Piece; 6] = [Piece::Pawn, Piece::Knight, Piece::Bishop, Piece::Rook, Piece::Queen, Piece::King];
+
+
+// P N B R Q for White are 0-4, the same for Black 5-9 and either king 10
+pub fn halfka_piece_index(side: Side, piece: Piece) -> usize {
+    match (side, piece) {
+        (_, Piece::King) => 10,
+        (Side::White, piece) => PIECE_ORDER.iter().position(|p| *p == piece).unwrap_or(0),
+        (Side::Black, piece) => 5 + PIECE_ORDER.iter().position(|p| *p == piece).unwrap_or(0),
+    }
+}
+
+// P N B R Q K for White are 0-5, the same for Black 6-11
+pub fn cnn_channel(side: Side, piece: Piece) -> usize {
+    let index = PIECE_ORDER.iter().position(|p| *p == piece).unwrap_or(0);
+    side as usize * 6 + index
+}
+
+// Feature of one piece seen from the given side's king, or None for that
+// side's own king
+pub fn halfka_feature(board: &Board, perspective: Side, piece_side: Side, piece: Piece,
+    square: Square) -> Option<usize> {
+    if piece_side == perspective && piece == Piece::King {
+        return None;
+    }
+
+    let king_square = board.get_king_square(perspective);
+    let flip = king_square.rank() as usize >= 4;
+    let orient = |square: Square| if flip {
+        square.flip_vertical() as usize
+    } else {
+        square as usize
+    };
+
+    let piece_square = orient(square);
+    let king_square = orient(king_square);
+    if piece_square == king_square {
+        return None;
+    }
+    Some((halfka_piece_index(piece_side, piece) * NrOf::SQUARES + piece_square) * HALFKA_KING_SQUARES + king_square)
+}
+
+// Active features for one perspective, lowest square first
+pub fn halfka_indices(board: &Board, perspective: Side) -> Vec<usize> {
+    board.get_full_occupancy().squares()
+        .filter_map(|square| {
+            let (piece_side, piece) = board.piece_on(square).unwrap();
+            halfka_feature(board, perspective, piece_side, piece, square)
+        })
+        .collect()
+}
+
+// Splits a feature back into piece index, oriented piece square and
+// oriented king square
+pub fn decode_halfka_index(index: usize) -> (usize, usize, usize) {
+    let king_square = index % HALFKA_KING_SQUARES;
+    let piece_square = (index / HALFKA_KING_SQUARES) % NrOf::SQUARES;
+    let piece_index = index / (HALFKA_KING_SQUARES * NrOf::SQUARES);
+    (piece_index, piece_square, king_square)
+}
+
+pub fn cnn_planes(board: &Board) -> Array3<f32> {
+    let mut planes = Array3::<f32>::zeros((CNN_CHANNELS, 8, 8));
+    write_cnn_planes(board, planes.view_mut());
+    planes
+}
+
+// cnn_planes written over an existing (CNN_CHANNELS, 8, 8) array, e.g. one
+// position of a reused batch
+pub fn write_cnn_planes(board: &Board, mut planes: ArrayViewMut3<f32>) {
+    planes.fill(0.0);
+    for square in board.get_full_occupancy().squares() {
+        let (side, piece) = board.piece_on(square).unwrap();
+        planes[[cnn_channel(side, piece), square.rank() as usize, square.file() as usize]] = 1.0;
+    }
+}
+
+pub fn policy_value_planes(board: &Board) -> Array3<f32> {
+    let mut planes = Array3::<f32>::zeros((POLICY_VALUE_CHANNELS, 8, 8));
+    planes.slice_mut(s![..CNN_CHANNELS, .., ..]).assign(&cnn_planes(board));
+    if board.get_active_side() == Side::White {
+        planes.slice_mut(s![CNN_CHANNELS, .., ..]).fill(1.0);
+    }
+    planes
+}
+
+// Promotions to different pieces share the logit of their squares
+pub fn policy_index(mv: &ChessMove) -> usize {
+    mv.from as usize * NrOf::SQUARES + mv.to as usize
+}
+
+
+// Everything both models are fed for one position, for checking the
+// training pipeline's encoder against the engine's
+pub struct FeatureDump {
+    fen: String,
+    halfka: [Vec<usize>; NrOf::SIDES],
+    planes: Array3<f32>,
+}
+
+impl FeatureDump {
+    pub fn new(board: &Board) -> Self {
+        FeatureDump {
+            fen: board.to_fen(),
+            halfka: [halfka_indices(board, Side::White), halfka_indices(board, Side::Black)],
+            planes: cnn_planes(board),
+        }
+    }
+
+    pub fn from_fen(fen: &str) -> Result<Self, FenError> {
+        Ok(Self::new(&Board::try_from_fen(fen)?))
+    }
+
+    pub fn get_fen(&self) -> &str {
+        &self.fen
+    }
+
+    pub fn get_halfka_indices(&self, perspective: Side) -> &[usize] {
+        &self.halfka[perspective as usize]
+    }
+
+    pub fn get_cnn_planes(&self) -> &Array3<f32> {
+        &self.planes
+    }
+
+    // Set cells of the CNN input as (channel, rank, file)
+    pub fn get_cnn_active(&self) -> Vec<(usize, usize, usize)> {
+        self.planes.indexed_iter()
+            .filter(|(_, value)| **value != 0.0)
+            .map(|(index, _)| index)
+            .collect()
+    }
+
+    // One JSON object, so that the training code can load it and diff it
+    // against its own encoding:
+    // {"fen": .., "halfka": {"white": [..], "black": [..]},
+    //  "cnn": {"shape": [12, 8, 8], "active": [[channel, rank, file], ..]}}
+    pub fn to_json(&self) -> String {
+        let list = |values: &[usize]| values.iter()
+            .map(|value| value.to_string())
+            .collect::<Vec<_>>()
+            .join(", ");
+        let active = self.get_cnn_active().iter()
+            .map(|(channel, rank, file)| format!("[{channel}, {rank}, {file}]"))
+            .collect::<Vec<_>>()
+            .join(", ");
+
+        format!("{{\"fen\": \"{}\", \"halfka\": {{\"white\": [{}], \"black\": [{}]}}, \
+            \"cnn\": {{\"shape\": [{CNN_CHANNELS}, 8, 8], \"active\": [{}]}}}}",
+            self.fen, list(&self.halfka[0]), list(&self.halfka[1]), active)
+    }
+
+    pub fn write_json<P: AsRef<Path>>(&self, path: P) -> io::Result<()> {
+        fs::write(path, self.to_json() + "\n")
+    }
+}
+
+impl Display for FeatureDump {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let names = ["P", "N", "B", "R", "Q", "p", "n", "b", "r", "q", "K"];
+        let square_name = |square: usize| Square::try_from(square).unwrap().to_string();
+
+        writeln!(f, "{}", self.fen)?;
+        for side in [Side::White, Side::Black] {
+            let indices = self.get_halfka_indices(side);
+            writeln!(f, "HalfKA {side:?} ({} features):", indices.len())?;
+            for &index in indices {
+                let (piece, piece_square, king_square) = decode_halfka_index(index);
+                writeln!(f, "  {index:>6}  {} {} king {}", names[piece],
+                    square_name(piece_square), square_name(king_square))?;
+            }
+        }
+
+        let active = self.get_cnn_active();
+        writeln!(f, "CNN planes:")?;
+        for (channel, name) in "PNBRQKpnbrqk".chars().enumerate() {
+            let squares: Vec<String> = active.iter()
+                .filter(|(c, _, _)| *c == channel)
+                .map(|(_, rank, file)| square_name(rank * NrOf::FILES + file))
+                .collect();
+            writeln!(f, "  {:>2} {}: {}", channel, name, squares.join(" "))?;
+        }
+        Ok(())
+    }
+}
@@ -1,281 +1,390 @@
-use std::{collections::{HashMap, HashSet}, path::Path};
+use std::{path::Path, sync::Arc};
 
-use ndarray_npy::read_npy;
-use ort::{tensor::OrtOwnedTensor, Environment, SessionBuilder, Value};
-use ndarray::{Array2, CowArray, IxDyn};
+use ndarray::{Array2, ArrayView1};
 
-use crate::engine::{board::board::Board,
-    definitions::{NrOf, Piece, Side, SQUARE_BITBOARDS},
-    evaluator::evaluator::Evaluator};
+use crate::engine::{bitboard::BitboardExt, board::board::Board,
+    definitions::{NrOf, Piece, Side, Square, ZobristKey, MAX_GAME_MOVES},
+    move_generator::chess_move::ChessMove,
+    evaluator::{embedding_table::EmbeddingTable, evaluator::{EvalError, Evaluator},
+        features::{halfka_feature, halfka_indices, HALFKA_FEATURES},
+        halfka_network::{HalfkaNetwork, PSQT_BUCKETS}, inference_config::InferenceConfig,
+        model_validation::{check_architecture, check_inputs, check_outputs, check_shape, Dim, ModelError},
+        onnx_session::run_session}};
 
 
-struct HalfKACache {
-    own_indices: HashSet<usize>,
-    opp_indices: HashSet<usize>,
-    own_sum: Array2<f32>,
-    opp_sum: Array2<f32>,
-}
+const OWN_TABLE: usize = 0;
+const OPP_TABLE: usize = 1;
 
-impl HalfKACache {
-    fn new() -> Self {
-        HalfKACache {
-            own_indices: HashSet::new(),
-            opp_indices: HashSet::new(),
-            own_sum: Array2::<f32>::zeros((1, 520)),
-            opp_sum: Array2::<f32>::zeros((1, 520)),
-        }
-    }
 
-    fn is_empty(&self) -> bool {
-        self.own_indices.is_empty() || self.opp_indices.is_empty()
-    }
+// Embedding sums of one position, seen from each king: the side to move
+// reads its sum over the own table, the other side its sum over the opp table.
+// Sums are in each table's fixed point, see EmbeddingTable
+#[derive(Clone)]
+struct Accumulator {
+    zobrist_key: ZobristKey,
+    // Indexed by perspective side, then table
+    sums: [[Vec<i16>; 2]; NrOf::SIDES],
+}
+
+// A piece taken off or put on a square by a move
+type PieceChange = (Side, Piece, Square);
+
+// Folders exported with the layer weights as .npy files are evaluated in
+// Rust; older folders with only the .onnx models still go through ort
+enum HalfkaBackend {
+    Native(HalfkaNetwork),
+    Onnx {
+        input_session: ort::Session,
+        bucket_sessions: Vec<ort::Session>,
+    },
 }
 
 pub struct HalfkaEvaluator {
-    own_embeddings: Array2<f32>,
-    opp_embeddings: Array2<f32>,
+    // Weights are shared between the copies made for search threads; the
+    // accumulators are per instance
+    own_embeddings: Arc<EmbeddingTable>,
+    opp_embeddings: Arc<EmbeddingTable>,
 
-    input_session: ort::Session,
-    bucket_sessions: Vec<ort::Session>,
+    backend: Arc<HalfkaBackend>,
 
-    piece_indices: HashMap<char, usize>,
-    cache: HalfKACache
+    config: InferenceConfig,
+    // One accumulator per position on the path from the root, pushed by
+    // make_move and popped by undo_move
+    accumulators: Vec<Accumulator>,
 }
 
 impl HalfkaEvaluator {
     pub fn new(model_dir: &str) -> Result<Self, String> {
-        let environment = std::sync::Arc::new(
-            Environment::builder()
-                .with_name("halfka-eval")
-                .build()
-                .map_err(|e| e.to_string())?
-        );
-
-        let input_path = Path::new(model_dir)
-                                    .join("halfka_input_processor.onnx");
-        let input_session = SessionBuilder::new(&environment)
-            .map_err(|e| e.to_string())?
-            .with_model_from_file(input_path)
-            .map_err(|e| e.to_string())?;
-
-        let mut bucket_sessions = Vec::with_capacity(8);
-        for i in 0..8 {
-            let bucket_path = Path::new(model_dir)
-                                        .join(format!("halfka_bucket_evaluator_{i}.onnx"));
-            let session = SessionBuilder::new(&environment)
-                .map_err(|e| e.to_string())?
-                .with_model_from_file(bucket_path)
-                .map_err(|e| e.to_string())?;
-            bucket_sessions.push(session);
-        }
+        Self::with_config(model_dir, &InferenceConfig::new())
+    }
 
+    // The config only matters for folders without the native export
+    pub fn with_config(model_dir: &str, config: &InferenceConfig) -> Result<Self, String> {
         let own_embeddings_path = Path::new(model_dir)
                                             .join("halfka_embeddings_own.npy");
         let opp_embeddings_path = Path::new(model_dir)
                                             .join("halfka_embeddings_opp.npy");
 
-        let embedding_own: Array2<f32> = read_npy(&own_embeddings_path)
-            .map_err(|e| format!("Failed to read {:?}: {}", own_embeddings_path, e))?;
+        let embedding_own = EmbeddingTable::load(&own_embeddings_path)?;
+        let embedding_opp = EmbeddingTable::load(&opp_embeddings_path)?;
+        Self::validate_embeddings(model_dir, &embedding_own, &embedding_opp).map_err(|e| e.to_string())?;
 
-        let embedding_opp: Array2<f32> = read_npy(&opp_embeddings_path)
-            .map_err(|e| format!("Failed to read {:?}: {}", opp_embeddings_path, e))?;
-
-        let piece_indices = HashMap::from([
-            ('P', 0), ('N', 1), ('B', 2), ('R', 3), ('Q', 4),
-            ('p', 5), ('n', 6), ('b', 7), ('r', 8), ('q', 9), 
-            ('K', 10), ('k', 10),
-        ]);
+        let backend = if HalfkaNetwork::is_exported(model_dir) {
+            HalfkaBackend::Native(HalfkaNetwork::load(model_dir, embedding_own.ncols())?)
+        } else {
+            Self::load_onnx_backend(model_dir, embedding_own.ncols(), config)?
+        };
 
         Ok(HalfkaEvaluator {
-            own_embeddings: embedding_own,
-            opp_embeddings: embedding_opp,
-            input_session,
-            bucket_sessions,
-            piece_indices,
-            cache: HalfKACache::new(),
+            own_embeddings: Arc::new(embedding_own),
+            opp_embeddings: Arc::new(embedding_opp),
+            backend: Arc::new(backend),
+            config: config.clone(),
+            accumulators: Vec::with_capacity(MAX_GAME_MOVES),
         })
     }
 
-    fn vertical_flip(square: usize) -> usize {
-        let file = square % NrOf::FILES;
-        let rank = 7 - (square / NrOf::FILES);
-        rank * NrOf::FILES + file
-    }
-
-    fn feature_index(
-        piece_idx: usize,
-        piece_sq: usize,
-        king_sq: usize) -> Option<usize> {
-        if piece_sq == king_sq {
-            None
-        } else {
-            Some(piece_idx * 64 * 32 + piece_sq * 32 + king_sq)
+    // Both tables need a row per feature and the same width, one that
+    // leaves room for the PSQT buckets. An architecture hash, when the
+    // export wrote one, is in halfka.arch
+    fn validate_embeddings(model_dir: &str, own: &EmbeddingTable,
+        opp: &EmbeddingTable) -> Result<(), ModelError> {
+        check_architecture(&Path::new(model_dir).join("halfka.arch"), model_dir, &Self::architecture())?;
+        let own_name = "own embeddings in halfka_embeddings_own.npy";
+        check_shape(own_name, &[Dim::Exactly(HALFKA_FEATURES), Dim::Any], &[own.nrows(), own.ncols()])?;
+        if own.ncols() <= PSQT_BUCKETS {
+            return Err(ModelError::ShapeMismatch {
+                tensor: own_name.to_string(),
+                expected: format!("more than {PSQT_BUCKETS} columns in"),
+                got: format!("({},{})", own.nrows(), own.ncols()),
+            });
         }
+        check_shape("opp embeddings in halfka_embeddings_opp.npy",
+            &[Dim::Exactly(HALFKA_FEATURES), Dim::Exactly(own.ncols())], &[opp.nrows(), opp.ncols()])
     }
 
-    pub fn compute_halfka_indices(
-        &self,
-        board: &Board,
-        side: Side) -> Vec<usize> {
-        let mut indices = Vec::new();
+    pub fn architecture() -> String {
+        format!("halfka features={HALFKA_FEATURES} psqt={PSQT_BUCKETS}")
+    }
 
-        let mut king_sq = board.get_king_square(side) as usize;
-        let king_rank = king_sq / NrOf::FILES;
+    fn load_onnx_backend(model_dir: &str, width: usize, config: &InferenceConfig) -> Result<HalfkaBackend, String> {
+        let environment = config.build_environment("halfka-eval")?;
 
-        let flip = king_rank >= 4;
-        king_sq = if flip {
-            Self::vertical_flip(king_sq)
-        } else {
-            king_sq
+        let input_path = Path::new(model_dir)
+                                    .join("halfka_input_processor.onnx");
+        let input_session = config.build_session(&environment, input_path)?;
+
+        // The input processor takes both sums and returns the transformed
+        // features and the PSQT score, which the buckets take in turn
+        let features = Dim::Exactly(2 * (width - PSQT_BUCKETS));
+        let sum = [Dim::Any, Dim::Exactly(width)];
+        let transformed: [&[Dim]; 2] = [&[Dim::Any, features], &[Dim::Any, Dim::Exactly(1)]];
+        let validate = |session: &ort::Session, name: &str, inputs: &[&[Dim]], outputs: &[&[Dim]]| {
+            check_inputs(session, name, inputs)
+                .and_then(|_| check_outputs(session, name, outputs))
+                .map_err(|e| e.to_string())
         };
+        validate(&input_session, "halfka_input_processor.onnx", &[&sum, &sum], &transformed)?;
 
-        let side_occupancy = board.get_side_occupancy(side);
-
-        for i in 0..NrOf::SQUARES {
-            let piece = board.piece_list[i];
-            if piece == Piece::None {
-                continue;
-            }
-
-            let square_bitboard = SQUARE_BITBOARDS[i as usize];
-            if side_occupancy & square_bitboard != 0 && piece== Piece::King {
-                continue;
-            }
+        let mut bucket_sessions = Vec::with_capacity(8);
+        for i in 0..8 {
+            let name = format!("halfka_bucket_evaluator_{i}.onnx");
+            let session = config.build_session(&environment, Path::new(model_dir).join(&name))?;
+            validate(&session, &name, &transformed, &[&[]])?;
+            bucket_sessions.push(session);
+        }
 
-            let piece_char = piece.to_string().chars()
-                                    .next().unwrap_or(' ');
-            let piece_idx = *self.piece_indices
-                                    .get(&piece_char).unwrap_or(&0);
-            let piece_square = if flip {
-                Self::vertical_flip(i as usize)
-            } else {
-                i as usize
-            };
+        Ok(HalfkaBackend::Onnx { input_session, bucket_sessions })
+    }
 
-            let feature_idx = Self::feature_index(
-                                            piece_idx,
-                                            piece_square,
-                                            king_sq);
-            if let Some(idx) = feature_idx {
-                indices.push(idx);
-            }
-        }
+    pub fn is_native(&self) -> bool {
+        matches!(*self.backend, HalfkaBackend::Native(_))
+    }
 
-        indices
+    pub fn compute_halfka_indices(
+        &self,
+        board: &Board,
+        side: Side) -> Vec<usize> {
+        halfka_indices(board, side)
     }
 
     fn sum_embedding(
         &self,
-        embedding: &Array2<f32>,
-        indices: &[usize]) -> Array2<f32> {
-        let mut sum = Array2::<f32>::zeros((1, 520));
+        embedding: &EmbeddingTable,
+        indices: &[usize]) -> Vec<i16> {
+        let mut sum = vec![0; embedding.ncols()];
         for &i in indices {
-            let result = &sum.row(0) + &embedding.row(i);
-            sum.row_mut(0).assign(&result);
+            embedding.add_row(&mut sum, i);
         }
         sum
     }
 
-}
-
-
-impl Evaluator for HalfkaEvaluator {
-    fn evaluate_board(&mut self, board: &Board) -> f32 {
-
-        let active_side = board.get_active_side();
-        let opp_side = board.get_opponent();
+    fn refresh_side(&self, board: &Board, side: Side) -> [Vec<i16>; 2] {
+        let indices = self.compute_halfka_indices(board, side);
+        [
+            self.sum_embedding(&self.own_embeddings, &indices),
+            self.sum_embedding(&self.opp_embeddings, &indices),
+        ]
+    }
 
-        let own_indices = self.compute_halfka_indices(board, active_side);
-        let opp_indices = self.compute_halfka_indices(board, opp_side);
+    fn refresh(&self, board: &Board) -> Accumulator {
+        Accumulator {
+            zobrist_key: board.game_state.zobrist_key,
+            sums: [self.refresh_side(board, Side::White), self.refresh_side(board, Side::Black)],
+        }
+    }
 
-        let new_own_set: HashSet<usize> = HashSet::from_iter(
-                                            own_indices.iter().cloned());
-        let new_opp_set: HashSet<usize> = HashSet::from_iter(
-                                            opp_indices.iter().cloned());
+    // Pieces a move takes off and puts on the board, worked out from the
+    // position it is played in
+    fn move_changes(board: &Board, mv: ChessMove) -> (Vec<PieceChange>, Vec<PieceChange>) {
+        let mover = board.get_active_side();
+        let mut removed = vec![(mover, mv.piece, mv.from)];
+        let mut added = vec![(mover, mv.promotion.unwrap_or(mv.piece), mv.to)];
+
+        if mv.is_king_castling() || mv.is_queen_castling() {
+            let (rook_from, rook_to) = Self::castling_rook(mv.to);
+            removed.push((mover, Piece::Rook, rook_from));
+            added.push((mover, Piece::Rook, rook_to));
+        }
+        if mv.is_en_passant() {
+            let captured = match mover {
+                Side::White => mv.to as usize - NrOf::FILES,
+                Side::Black => mv.to as usize + NrOf::FILES,
+            };
+            removed.push((mover.opponent(), Piece::Pawn, Square::try_from(captured).unwrap()));
+        } else if let Some((side, piece)) = board.piece_on(mv.to) {
+            removed.push((side, piece, mv.to));
+        }
+        (removed, added)
+    }
 
-        if self.cache.is_empty() {
-            for idx in &new_own_set {
-                self.cache.own_indices.insert(*idx);
-            }
+    fn castling_rook(king_to: Square) -> (Square, Square) {
+        match king_to {
+            Square::G1 => (Square::H1, Square::F1),
+            Square::C1 => (Square::A1, Square::D1),
+            Square::G8 => (Square::H8, Square::F8),
+            Square::C8 => (Square::A8, Square::D8),
+            _ => unreachable!()
+        }
+    }
 
-            for idx in &new_opp_set {
-                self.cache.opp_indices.insert(*idx);
+    // Updates the accumulators for a move just played on the board, from
+    // the pieces it moved and captured. A king move changes every feature
+    // of its own side, so that side is rebuilt instead
+    pub fn make_move(&mut self, board: &Board, mv: ChessMove) {
+        // The top accumulator has to belong to the position the move was
+        // played from, otherwise the stack is restarted here
+        let parent_key = board.game_history.last()
+            .map(|recorded_move| recorded_move.prev_state.zobrist_key);
+        let parent = match self.accumulators.last() {
+            Some(parent) if Some(parent.zobrist_key) == parent_key => parent,
+            _ => {
+                let accumulator = self.refresh(board);
+                self.accumulators.clear();
+                self.accumulators.push(accumulator);
+                return;
             }
+        };
 
-            self.cache.own_sum = self.sum_embedding(&self.own_embeddings, &own_indices);
-            self.cache.opp_sum = self.sum_embedding(&self.opp_embeddings, &opp_indices);
-        } else {
-            let added: Vec<usize> = new_own_set.difference(&self.cache.own_indices)
-                                    .cloned().collect();
-            let removed: Vec<usize> = self.cache.own_indices.difference(&new_own_set)
-                                    .cloned().collect();
+        if mv.is_null() {
+            let accumulator = Accumulator {
+                zobrist_key: board.game_state.zobrist_key,
+                sums: parent.sums.clone(),
+            };
+            self.accumulators.push(accumulator);
+            return;
+        }
 
-            for &i in &added {
-                self.cache.own_sum += &self.own_embeddings.row(i);
-            }
-            for &i in &removed {
-                self.cache.own_sum -= &self.opp_embeddings.row(i);
-            }
-            self.cache.own_indices = new_own_set;
+        let mover = board.get_opponent();
+        let moved_piece = match board.piece_on(mv.to) {
+            Some((_, piece)) => piece,
+            None => Piece::None,
+        };
+        let mut removed = vec![(mover, if mv.is_promotion() { Piece::Pawn } else { moved_piece }, mv.from)];
+        let mut added = vec![(mover, moved_piece, mv.to)];
 
+        if mv.is_king_castling() || mv.is_queen_castling() {
+            let (rook_from, rook_to) = Self::castling_rook(mv.to);
+            removed.push((mover, Piece::Rook, rook_from));
+            added.push((mover, Piece::Rook, rook_to));
+        }
+        if let Some((piece, side, square)) = board.game_history.last()
+            .and_then(|recorded_move| recorded_move.captured_piece) {
+            removed.push((side, piece, square));
+        }
 
-            let added: Vec<usize> = new_opp_set.difference(&self.cache.opp_indices)
-                                        .cloned().collect();
-            let removed: Vec<usize> = self.cache.opp_indices.difference(&new_opp_set)
-                                        .cloned().collect();
+        let accumulator = Accumulator {
+            zobrist_key: board.game_state.zobrist_key,
+            sums: self.child_sums(&parent.sums, board, mover, moved_piece, &removed, &added),
+        };
+        self.accumulators.push(accumulator);
+    }
 
-            for &i in &added {
-                self.cache.opp_sum += &self.opp_embeddings.row(i);
+    // The sums after a move, from those before it and the pieces it took
+    // off and put on the board. The board gives each perspective's king
+    // square, so it must be the one after the move when the mover's king
+    // moved, which has its side rebuilt from it
+    fn child_sums(&self, parent: &[[Vec<i16>; 2]; NrOf::SIDES], board: &Board, mover: Side,
+        moved_piece: Piece, removed: &[PieceChange], added: &[PieceChange]) -> [[Vec<i16>; 2]; NrOf::SIDES] {
+        let mut sums = parent.clone();
+        for perspective in [Side::White, Side::Black] {
+            let sums = &mut sums[perspective as usize];
+            if perspective == mover && moved_piece == Piece::King {
+                *sums = self.refresh_side(board, perspective);
+                continue;
             }
-            for &i in &removed {
-                self.cache.opp_sum -= &self.opp_embeddings.row(i);
+
+            for (table, embeddings) in [(OWN_TABLE, &self.own_embeddings), (OPP_TABLE, &self.opp_embeddings)] {
+                for &(side, piece, square) in removed {
+                    if let Some(i) = halfka_feature(board, perspective, side, piece, square) {
+                        embeddings.sub_row(&mut sums[table], i);
+                    }
+                }
+                for &(side, piece, square) in added {
+                    if let Some(i) = halfka_feature(board, perspective, side, piece, square) {
+                        embeddings.add_row(&mut sums[table], i);
+                    }
+                }
             }
-            self.cache.opp_indices = new_opp_set;
         }
+        sums
+    }
 
-        let own_sum = &self.cache.own_sum;
-        let opp_sum = &self.cache.opp_sum;
-
-        let input_own = CowArray::from(own_sum.clone().into_dyn());
-        let input_opp = CowArray::from(opp_sum.clone().into_dyn());
+    pub fn undo_move(&mut self) {
+        self.accumulators.pop();
+    }
 
-        let value_own = Value::from_array(
-                self.input_session.allocator(),
-                        &input_own).unwrap();
-        let value_opp = Value::from_array(
-                self.input_session.allocator(),
-                        &input_opp).unwrap();
+    fn forward(&self, sums: &[[Vec<i16>; 2]; NrOf::SIDES], active_side: Side,
+        piece_count: usize) -> Result<f32, EvalError> {
+        let bucket_index = ((piece_count - 1) / 4).min(7);
+        let own_sum = self.own_embeddings.dequantize(&sums[active_side as usize][OWN_TABLE]);
+        let opp_sum = self.opp_embeddings.dequantize(&sums[active_side.opponent() as usize][OPP_TABLE]);
+
+        match self.backend.as_ref() {
+            HalfkaBackend::Native(network) =>
+                Ok(network.forward(ArrayView1::from(&own_sum), ArrayView1::from(&opp_sum), bucket_index)),
+            HalfkaBackend::Onnx { input_session, bucket_sessions } => {
+                let input_own = Array2::from_shape_vec((1, own_sum.len()), own_sum)
+                    .map_err(|e| EvalError::InferenceFailed(e.to_string()))?.into_dyn();
+                let input_opp = Array2::from_shape_vec((1, opp_sum.len()), opp_sum)
+                    .map_err(|e| EvalError::InferenceFailed(e.to_string()))?.into_dyn();
+
+                // x_1024 is (1, 1024) and avg_score is (1, 1)
+                let outputs = run_session(input_session, vec![input_own, input_opp])
+                    .map_err(EvalError::InferenceFailed)?;
+                let result = run_session(&bucket_sessions[bucket_index], outputs)
+                    .map_err(EvalError::InferenceFailed)?;
+
+                result.first()
+                    .and_then(|output| output.iter().next())
+                    .copied()
+                    .ok_or_else(|| EvalError::InvalidOutput("empty output tensor".to_string()))
+            }
+        }
+    }
 
-        let outputs = self.input_session
-                                            .run(vec![value_own, value_opp])
-                                            .unwrap();
+    // Rebuilds the accumulator from scratch when the moves leading to the
+    // board were not reported through make_move
+    fn sync_accumulators(&mut self, board: &Board) {
+        let key = board.game_state.zobrist_key;
+        if self.accumulators.last().is_none_or(|top| top.zobrist_key != key) {
+            let accumulator = self.refresh(board);
+            self.accumulators.clear();
+            self.accumulators.push(accumulator);
+        }
+    }
+}
 
-        let x_1024_dyn: OrtOwnedTensor<f32, IxDyn> = outputs[0].try_extract().unwrap();
-        let avg_score_dyn: OrtOwnedTensor<f32, IxDyn> = outputs[1].try_extract().unwrap();
 
-        let x_1024 = x_1024_dyn.view().to_owned()
-                                                    .into_dimensionality::<ndarray::Ix2>()
-            .expect("Expected (1, 1024) array");
+impl Evaluator for HalfkaEvaluator {
+    fn evaluate_board(&mut self, board: &Board) -> Result<f32, EvalError> {
+        let piece_count = board.get_full_occupancy().popcount() as usize;
+        self.sync_accumulators(board);
+        let accumulator = self.accumulators.last().unwrap();
+        self.forward(&accumulator.sums, board.get_active_side(), piece_count)
+    }
 
-        let avg_score = avg_score_dyn.view().to_owned()
-                                                    .into_dimensionality::<ndarray::Ix2>()
-            .expect("Expected (1, 1) array");
+    // Applies the move to the top accumulator without pushing the result.
+    // King moves rebuild a whole side, so they are scored from a copy
+    fn evaluate_after_move(&mut self, board: &Board, mv: ChessMove) -> Result<f32, EvalError> {
+        let mover = board.get_active_side();
+        let piece_count = board.get_full_occupancy().popcount() as usize - mv.is_capture() as usize;
+        if mv.piece == Piece::King && !mv.is_null() {
+            let child = board.make_move_copy(mv);
+            return self.forward(&self.refresh(&child).sums, mover.opponent(), piece_count);
+        }
 
+        self.sync_accumulators(board);
+        let parent = &self.accumulators.last().unwrap().sums;
+        if mv.is_null() {
+            return self.forward(parent, mover.opponent(), piece_count);
+        }
+        let (removed, added) = Self::move_changes(board, mv);
+        let sums = self.child_sums(parent, board, mover, mv.piece, &removed, &added);
+        self.forward(&sums, mover.opponent(), piece_count)
+    }
 
-        let piece_count = own_indices.len() + 1;
-        let bucket_index = ((piece_count - 1) / 4).min(7);
+    fn on_make_move(&mut self, board: &Board, mv: &ChessMove) {
+        self.make_move(board, *mv);
+    }
 
-        let input_x_val = CowArray::from(x_1024.into_dyn());
-        let input_avg_val = CowArray::from(avg_score.into_dyn());
-        let x_val = Value::from_array(self.bucket_sessions[bucket_index]
-                                    .allocator(),&input_x_val).unwrap();
-        let avg_val = Value::from_array(self.bucket_sessions[bucket_index]
-                                    .allocator(),&input_avg_val).unwrap();
+    fn on_undo_move(&mut self) {
+        self.undo_move();
+    }
 
-        let result = self.bucket_sessions[bucket_index]
-                                            .run(vec![x_val, avg_val]).unwrap();
-        let output_tensor: OrtOwnedTensor<f32, IxDyn> = result[0].try_extract().unwrap();
+    fn clone_for_thread(&self) -> Option<Box<dyn Evaluator>> {
+        Some(Box::new(HalfkaEvaluator {
+            own_embeddings: Arc::clone(&self.own_embeddings),
+            opp_embeddings: Arc::clone(&self.opp_embeddings),
+            backend: Arc::clone(&self.backend),
+            config: self.config.clone(),
+            accumulators: Vec::with_capacity(MAX_GAME_MOVES),
+        }))
+    }
 
-        *output_tensor.view().iter().next().unwrap()
+    // Takes the model folder, as new does
+    fn reload_model(&mut self, path: &str) -> Result<(), String> {
+        *self = Self::with_config(path, &self.config)?;
+        Ok(())
     }
-}
\ No newline at end of file
+}
@@ -0,0 +1,122 @@
+use std::path::Path;
+
+use ndarray::{concatenate, s, Array1, Array2, ArrayView1, Axis};
+use ndarray_npy::read_npy;
+
+use crate::engine::evaluator::simd;
+
+
+// Each accumulator row ends with one material-style score per bucket
+pub const PSQT_BUCKETS: usize = 8;
+
+
+// Fully connected layer exported from PyTorch: weight is (outputs, inputs)
+struct DenseLayer {
+    weight: Array2<f32>,
+    bias: Array1<f32>,
+}
+
+impl DenseLayer {
+    fn load(model_dir: &Path, name: &str) -> Result<Self, String> {
+        let weight_path = model_dir.join(format!("{name}_weight.npy"));
+        let bias_path = model_dir.join(format!("{name}_bias.npy"));
+
+        let weight: Array2<f32> = read_npy(&weight_path)
+            .map_err(|e| format!("Failed to read {:?}: {}", weight_path, e))?;
+        let bias: Array1<f32> = read_npy(&bias_path)
+            .map_err(|e| format!("Failed to read {:?}: {}", bias_path, e))?;
+
+        if weight.nrows() != bias.len() {
+            return Err(format!("{name}: weight has {} rows but bias has {} entries",
+                weight.nrows(), bias.len()));
+        }
+        // Rows are handed to the SIMD kernels as contiguous slices
+        let weight = weight.as_standard_layout().into_owned();
+        Ok(DenseLayer { weight, bias })
+    }
+
+    fn forward(&self, input: &Array1<f32>) -> Array1<f32> {
+        let input = input.as_slice().unwrap();
+        self.weight.outer_iter()
+            .zip(&self.bias)
+            .map(|(row, bias)| simd::dot(row.to_slice().unwrap(), input) + bias)
+            .collect()
+    }
+}
+
+
+// The HalfKA input processor and bucket MLPs evaluated directly with ndarray.
+//
+// Besides the two embedding tables, the model folder holds the bucket
+// layers as halfka_bucket_{bucket}_l{layer}_weight.npy / _bias.npy, layer
+// 0 taking the 1024 transformed features and the last layer producing the
+// single output
+pub struct HalfkaNetwork {
+    feature_width: usize,
+    buckets: Vec<Vec<DenseLayer>>,
+}
+
+impl HalfkaNetwork {
+    pub fn load(model_dir: &str, accumulator_width: usize) -> Result<Self, String> {
+        let model_dir = Path::new(model_dir);
+        let feature_width = accumulator_width.checked_sub(PSQT_BUCKETS)
+            .ok_or_else(|| format!("Accumulator width {accumulator_width} is too small"))?;
+
+        let mut buckets = Vec::with_capacity(PSQT_BUCKETS);
+        for bucket in 0..PSQT_BUCKETS {
+            let mut layers = Vec::new();
+            while model_dir.join(format!("halfka_bucket_{bucket}_l{}_weight.npy", layers.len())).exists() {
+                layers.push(DenseLayer::load(model_dir,
+                    &format!("halfka_bucket_{bucket}_l{}", layers.len()))?);
+            }
+
+            match (layers.first(), layers.last()) {
+                (Some(first), Some(last)) if first.weight.ncols() == 2 * feature_width
+                    && last.weight.nrows() == 1 => buckets.push(layers),
+                (None, _) => return Err(format!("No layers found for bucket {bucket}")),
+                _ => return Err(format!("Layer shapes of bucket {bucket} do not match the embeddings")),
+            }
+        }
+
+        Ok(HalfkaNetwork { feature_width, buckets })
+    }
+
+    // True when the folder holds weights for the native network rather than
+    // only the ONNX sessions
+    pub fn is_exported(model_dir: &str) -> bool {
+        Path::new(model_dir).join("halfka_bucket_0_l0_weight.npy").exists()
+    }
+
+    // Same outputs as halfka_input_processor.onnx: both perspectives' clipped
+    // features side by side, and the averaged PSQT score difference
+    pub fn transform(&self, own_sum: ArrayView1<f32>,
+        opp_sum: ArrayView1<f32>) -> (Array1<f32>, f32) {
+        let width = self.feature_width;
+        let own = own_sum.slice(s![..width]).mapv(|x| x.clamp(0.0, 1.0));
+        let opp = opp_sum.slice(s![..width]).mapv(|x| x.clamp(0.0, 1.0));
+        let features = concatenate(Axis(0), &[own.view(), opp.view()]).unwrap();
+
+        let psqt = &own_sum.slice(s![width..]) - &opp_sum.slice(s![width..]);
+        let avg_score = psqt.mean().unwrap_or(0.0) / 2.0;
+
+        (features, avg_score)
+    }
+
+    // Same output as halfka_bucket_evaluator_{bucket}.onnx
+    pub fn evaluate_bucket(&self, bucket: usize, features: &Array1<f32>, avg_score: f32) -> f32 {
+        let layers = &self.buckets[bucket];
+        let mut x = features.clone();
+        for (index, layer) in layers.iter().enumerate() {
+            x = layer.forward(&x);
+            if index + 1 < layers.len() {
+                x.mapv_inplace(|value| value.clamp(0.0, 1.0));
+            }
+        }
+        x[0] + avg_score
+    }
+
+    pub fn forward(&self, own_sum: ArrayView1<f32>, opp_sum: ArrayView1<f32>, bucket: usize) -> f32 {
+        let (features, avg_score) = self.transform(own_sum, opp_sum);
+        self.evaluate_bucket(bucket, &features, avg_score)
+    }
+}
@@ -0,0 +1,199 @@
+use std::{fmt::{self, Display}, path::Path, str::FromStr, sync::Arc};
+
+use ort::{execution_providers::{CoreMLExecutionProviderOptions, CPUExecutionProviderOptions,
+    CUDAExecutionProviderOptions, DirectMLExecutionProviderOptions,
+    TensorRTExecutionProviderOptions, ExecutionProvider},
+    Environment, GraphOptimizationLevel, Session, SessionBuilder};
+
+
+// Hardware the ONNX sessions run on. Providers missing from the installed
+// ONNX Runtime build fall back to the CPU
+#[derive(Clone, Copy, PartialEq, Eq, Default, Debug)]
+pub enum InferenceDevice {
+    #[default]
+    Cpu,
+    Cuda,
+    TensorRt,
+    DirectMl,
+    CoreMl,
+}
+
+impl Display for InferenceDevice {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let name = match self {
+            Self::Cpu => "cpu",
+            Self::Cuda => "cuda",
+            Self::TensorRt => "tensorrt",
+            Self::DirectMl => "directml",
+            Self::CoreMl => "coreml",
+        };
+        write!(f, "{name}")
+    }
+}
+
+impl FromStr for InferenceDevice {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_ascii_lowercase().as_str() {
+            "cpu" => Ok(Self::Cpu),
+            "cuda" => Ok(Self::Cuda),
+            "tensorrt" => Ok(Self::TensorRt),
+            "directml" | "dml" => Ok(Self::DirectMl),
+            "coreml" => Ok(Self::CoreMl),
+            _ => Err(format!("Unknown inference device: {s}")),
+        }
+    }
+}
+
+
+// Graph rewrites ONNX Runtime applies when loading a model
+#[derive(Clone, Copy, PartialEq, Eq, Default, Debug)]
+pub enum OptimizationLevel {
+    Disabled,
+    Basic,
+    Extended,
+    #[default]
+    All,
+}
+
+impl OptimizationLevel {
+    fn to_ort(self) -> GraphOptimizationLevel {
+        match self {
+            Self::Disabled => GraphOptimizationLevel::Disable,
+            Self::Basic => GraphOptimizationLevel::Level1,
+            Self::Extended => GraphOptimizationLevel::Level2,
+            Self::All => GraphOptimizationLevel::Level3,
+        }
+    }
+}
+
+
+// How the evaluators build their ONNX sessions
+#[derive(Clone, Debug)]
+pub struct InferenceConfig {
+    device: InferenceDevice,
+    // Which GPU to use when several are present
+    device_id: u32,
+
+    // None leaves the choice to ONNX Runtime, which sizes its pools to all
+    // cores and competes with the engine's own search threads
+    intra_threads: Option<i16>,
+    inter_threads: Option<i16>,
+    optimization_level: OptimizationLevel,
+    memory_arena: bool,
+}
+
+impl InferenceConfig {
+    pub fn new() -> Self {
+        InferenceConfig {
+            device: InferenceDevice::Cpu,
+            device_id: 0,
+            intra_threads: None,
+            inter_threads: None,
+            optimization_level: OptimizationLevel::All,
+            memory_arena: true,
+        }
+    }
+
+    pub fn device(mut self, device: InferenceDevice) -> Self {
+        self.device = device;
+        self
+    }
+
+    pub fn device_id(mut self, device_id: u32) -> Self {
+        self.device_id = device_id;
+        self
+    }
+
+    // Threads used inside a single operator
+    pub fn intra_threads(mut self, threads: i16) -> Self {
+        self.intra_threads = Some(threads);
+        self
+    }
+
+    // Threads used to run independent operators in parallel
+    pub fn inter_threads(mut self, threads: i16) -> Self {
+        self.inter_threads = Some(threads);
+        self
+    }
+
+    pub fn optimization_level(mut self, level: OptimizationLevel) -> Self {
+        self.optimization_level = level;
+        self
+    }
+
+    // The CPU arena keeps freed tensor memory for reuse; turning it off
+    // lowers memory use at some cost in speed
+    pub fn memory_arena(mut self, enabled: bool) -> Self {
+        self.memory_arena = enabled;
+        self
+    }
+
+    pub fn get_device(&self) -> InferenceDevice {
+        self.device
+    }
+
+    pub fn get_device_id(&self) -> u32 {
+        self.device_id
+    }
+
+    // The chosen provider first, with the CPU kept as the fallback
+    pub fn execution_providers(&self) -> Vec<ExecutionProvider> {
+        let device_id = self.device_id;
+        let mut providers = match self.device {
+            InferenceDevice::Cpu => Vec::new(),
+            InferenceDevice::Cuda => vec![ExecutionProvider::CUDA(
+                CUDAExecutionProviderOptions { device_id, ..Default::default() })],
+            InferenceDevice::TensorRt => vec![ExecutionProvider::TensorRT(
+                TensorRTExecutionProviderOptions { device_id, ..Default::default() })],
+            InferenceDevice::DirectMl => vec![ExecutionProvider::DirectML(
+                DirectMLExecutionProviderOptions { device_id })],
+            InferenceDevice::CoreMl => vec![ExecutionProvider::CoreML(
+                CoreMLExecutionProviderOptions::default())],
+        };
+        providers.push(ExecutionProvider::CPU(
+            CPUExecutionProviderOptions { use_arena: self.memory_arena }));
+        providers
+    }
+
+    pub fn build_environment(&self, name: &str) -> Result<Arc<Environment>, String> {
+        let environment = Environment::builder()
+            .with_name(name)
+            .with_execution_providers(self.execution_providers())
+            .build()
+            .map_err(|e| e.to_string())?;
+        Ok(Arc::new(environment))
+    }
+
+    pub fn build_session<P: AsRef<Path>>(&self, environment: &Arc<Environment>,
+        model_path: P) -> Result<Session, String> {
+        let mut builder = SessionBuilder::new(environment)
+            .map_err(|e| e.to_string())?
+            .with_execution_providers(self.execution_providers())
+            .map_err(|e| e.to_string())?
+            .with_optimization_level(self.optimization_level.to_ort())
+            .map_err(|e| e.to_string())?
+            .with_memory_pattern(self.memory_arena)
+            .map_err(|e| e.to_string())?;
+
+        if let Some(threads) = self.intra_threads {
+            builder = builder.with_intra_threads(threads).map_err(|e| e.to_string())?;
+        }
+        if let Some(threads) = self.inter_threads {
+            builder = builder.with_parallel_execution(threads > 1)
+                .map_err(|e| e.to_string())?
+                .with_inter_threads(threads)
+                .map_err(|e| e.to_string())?;
+        }
+
+        builder.with_model_from_file(model_path)
+            .map_err(|e| e.to_string())
+    }
+}
+
+impl Default for InferenceConfig {
+    fn default() -> Self {
+        Self::new()
+    }
+}
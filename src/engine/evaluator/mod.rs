@@ -1,5 +1,51 @@
 pub mod evaluator;
 
+pub mod batch_server;
+
+pub mod bench;
+
+pub mod calibration;
+
+#[cfg(feature = "nn")]
+pub mod embedding_table;
+
+pub mod endgame;
+
+pub mod ensemble_evaluator;
+
+pub mod features;
+
+#[cfg(feature = "nn")]
 pub mod cnn_evaluator;
 
-pub mod halfka_evaluator;
\ No newline at end of file
+#[cfg(feature = "nn")]
+pub mod halfka_evaluator;
+
+#[cfg(feature = "nn")]
+pub mod halfka_network;
+
+#[cfg(feature = "nn")]
+pub mod inference_config;
+
+#[cfg(feature = "nn")]
+pub mod model_validation;
+
+#[cfg(feature = "nn")]
+pub mod onnx_session;
+
+#[cfg(feature = "nn")]
+pub mod policy_value_evaluator;
+
+pub mod simd;
+
+pub mod classical_evaluator;
+
+pub mod eval_params;
+
+pub mod texel_tuner;
+
+pub mod pawn_hash_table;
+
+pub use kingcrab_core::piece_square_tables;
+
+pub mod nnue;
\ No newline at end of file
@@ -0,0 +1,138 @@
+use std::{fmt::{self, Display}, fs, path::Path};
+
+use ort::Session;
+
+
+// Checks run when a model is loaded, so that a file exported for another
+// architecture is rejected with a readable message rather than failing
+// inside the first evaluation
+
+// Dimension of an expected shape; Any matches a batch or otherwise free
+// dimension
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum Dim {
+    Any,
+    Exactly(usize),
+}
+
+
+#[derive(Debug)]
+pub enum ModelError {
+    ShapeMismatch { tensor: String, expected: String, got: String },
+    TensorCount { model: String, kind: &'static str, expected: usize, got: usize },
+    ArchitectureMismatch { model: String, expected: String, found: String },
+}
+
+impl Display for ModelError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::ShapeMismatch { tensor, expected, got } =>
+                    write!(f, "Expected {expected} {tensor}, got {got}"),
+            Self::TensorCount { model, kind, expected, got } =>
+                    write!(f, "Expected {expected} {kind}s in {model}, got {got}"),
+            Self::ArchitectureMismatch { model, expected, found } =>
+                    write!(f, "{model} was exported for architecture {found}, the engine expects {expected}"),
+        }
+    }
+}
+
+
+// FNV-1a of an architecture description such as "halfka features=22528".
+// Exports store it as hex in a sidecar file, see check_architecture
+pub fn architecture_hash(description: &str) -> u64 {
+    description.bytes().fold(0xcbf2_9ce4_8422_2325, |hash, byte| {
+        (hash ^ byte as u64).wrapping_mul(0x0100_0000_01b3)
+    })
+}
+
+// Compares the hash in the sidecar file, when there is one, with the
+// description's. Exports from before the hashes were written have none and
+// are only checked by shape
+pub fn check_architecture(sidecar: &Path, model: &str, description: &str) -> Result<(), ModelError> {
+    let Ok(contents) = fs::read_to_string(sidecar) else {
+        return Ok(());
+    };
+    let found = contents.trim().trim_start_matches("0x").to_lowercase();
+    let expected = format!("{:016x}", architecture_hash(description));
+    if found == expected {
+        return Ok(());
+    }
+    Err(ModelError::ArchitectureMismatch {
+        model: model.to_string(),
+        expected: format!("{expected} ({description})"),
+        found,
+    })
+}
+
+// Checks a loaded array's shape, e.g. for own-embeddings "(22528,N)"
+pub fn check_shape(tensor: &str, expected: &[Dim], got: &[usize]) -> Result<(), ModelError> {
+    let matches = expected.len() == got.len() && expected.iter().zip(got)
+        .all(|(dim, size)| matches!(dim, Dim::Any) || *dim == Dim::Exactly(*size));
+    if matches {
+        return Ok(());
+    }
+    Err(ModelError::ShapeMismatch {
+        tensor: tensor.to_string(),
+        expected: format_expected(expected),
+        got: format_shape(got.iter().map(|size| size.to_string())),
+    })
+}
+
+// Checks the number and shapes of a session's inputs. Dimensions the
+// export left dynamic match anything, and an empty expected shape skips
+// the shape check for that tensor
+pub fn check_inputs(session: &Session, model: &str, expected: &[&[Dim]]) -> Result<(), ModelError> {
+    let shapes: Vec<(&str, &[Option<u32>])> = session.inputs.iter()
+        .map(|input| (input.name.as_str(), input.dimensions.as_slice()))
+        .collect();
+    check_tensors(model, "input", &shapes, expected)
+}
+
+pub fn check_outputs(session: &Session, model: &str, expected: &[&[Dim]]) -> Result<(), ModelError> {
+    let shapes: Vec<(&str, &[Option<u32>])> = session.outputs.iter()
+        .map(|output| (output.name.as_str(), output.dimensions.as_slice()))
+        .collect();
+    check_tensors(model, "output", &shapes, expected)
+}
+
+fn check_tensors(model: &str, kind: &'static str, shapes: &[(&str, &[Option<u32>])],
+    expected: &[&[Dim]]) -> Result<(), ModelError> {
+    if shapes.len() != expected.len() {
+        return Err(ModelError::TensorCount {
+            model: model.to_string(),
+            kind,
+            expected: expected.len(),
+            got: shapes.len(),
+        });
+    }
+
+    for ((name, dimensions), expected) in shapes.iter().zip(expected) {
+        let same_rank = dimensions.len() == expected.len();
+        let matches = expected.is_empty() || same_rank && dimensions.iter().zip(expected.iter())
+            .all(|(size, dim)| match (size, dim) {
+                (Some(size), Dim::Exactly(expected)) => *size as usize == *expected,
+                _ => true,
+            });
+        if !matches {
+            let got = dimensions.iter()
+                .map(|size| size.map_or("N".to_string(), |size| size.to_string()));
+            return Err(ModelError::ShapeMismatch {
+                tensor: format!("{kind} '{name}' of {model}"),
+                expected: format_expected(expected),
+                got: format_shape(got),
+            });
+        }
+    }
+    Ok(())
+}
+
+fn format_expected(expected: &[Dim]) -> String {
+    format_shape(expected.iter().map(|dim| match dim {
+        Dim::Any => "N".to_string(),
+        Dim::Exactly(size) => size.to_string(),
+    }))
+}
+
+fn format_shape<I: Iterator<Item = String>>(dims: I) -> String {
+    format!("({})", dims.collect::<Vec<_>>().join(","))
+}
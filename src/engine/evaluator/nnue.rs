@@ -0,0 +1,266 @@
+use std::{fmt::{self, Display}, fs, io, path::Path, sync::Arc};
+
+use crate::engine::{bitboard::BitboardExt, board::board::Board,
+    definitions::{Piece, Side, Square},
+    evaluator::evaluator::{EvalError, Evaluator}};
+
+
+// Networks in the widely published "HalfKP 256x2-32-32" .nnue layout. It is
+// the only one read: HalfKA and the other layouts of later networks are
+// refused as UnsupportedArchitecture (HalfkaEvaluator runs this repository's
+// own HalfKA models instead)
+const NNUE_VERSION: u32 = 0x7AF3_2F16;
+
+const HALFKP_FEATURES: usize = 64 * PIECE_SQUARE_FEATURES;
+// Ten non-king piece kinds on 64 squares, plus one unused slot
+const PIECE_SQUARE_FEATURES: usize = 10 * 64 + 1;
+const TRANSFORMED_SIZE: usize = 256;
+const HIDDEN_SIZE: usize = 32;
+
+// Fixed-point scales used when the networks were quantized
+const WEIGHT_SCALE_BITS: u32 = 6;
+const OUTPUT_SCALE: i32 = 16;
+// A pawn in the networks' scaled output, Stockfish's endgame pawn value
+const PAWN_VALUE: i32 = 208;
+const ACTIVATION_MAX: i32 = 127;
+
+
+#[derive(Debug)]
+pub enum NnueError {
+    ReadError(io::Error),
+    UnsupportedVersion(u32),
+    UnsupportedArchitecture(String),
+    TruncatedFile,
+}
+
+impl Display for NnueError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::ReadError(error) =>
+                    write!(f, "Failed to read network file: {error}"),
+            Self::UnsupportedVersion(version) =>
+                    write!(f, "Unsupported NNUE version: {version:#x}"),
+            Self::UnsupportedArchitecture(description) =>
+                    write!(f, "Unsupported NNUE architecture: {description}"),
+            Self::TruncatedFile =>
+                    write!(f, "Network file ends before all weights were read"),
+        }
+    }
+}
+
+
+// Little-endian cursor over the raw file
+struct NnueReader<'a> {
+    bytes: &'a [u8],
+    position: usize,
+}
+
+impl<'a> NnueReader<'a> {
+    fn take(&mut self, count: usize) -> Result<&'a [u8], NnueError> {
+        let end = self.position.checked_add(count).ok_or(NnueError::TruncatedFile)?;
+        let slice = self.bytes.get(self.position..end).ok_or(NnueError::TruncatedFile)?;
+        self.position = end;
+        Ok(slice)
+    }
+
+    fn read_u32(&mut self) -> Result<u32, NnueError> {
+        Ok(u32::from_le_bytes(self.take(4)?.try_into().unwrap()))
+    }
+
+    fn read_i16s(&mut self, count: usize) -> Result<Vec<i16>, NnueError> {
+        Ok(self.take(count * 2)?
+            .chunks_exact(2)
+            .map(|bytes| i16::from_le_bytes([bytes[0], bytes[1]]))
+            .collect())
+    }
+
+    fn read_i32s(&mut self, count: usize) -> Result<Vec<i32>, NnueError> {
+        Ok(self.take(count * 4)?
+            .chunks_exact(4)
+            .map(|bytes| i32::from_le_bytes(bytes.try_into().unwrap()))
+            .collect())
+    }
+
+    fn read_i8s(&mut self, count: usize) -> Result<Vec<i8>, NnueError> {
+        Ok(self.take(count)?.iter().map(|byte| *byte as i8).collect())
+    }
+}
+
+
+// Quantized affine layer; weights are stored one output row at a time
+struct DenseLayer {
+    biases: Vec<i32>,
+    weights: Vec<i8>,
+    inputs: usize,
+}
+
+impl DenseLayer {
+    fn read(reader: &mut NnueReader, inputs: usize, outputs: usize) -> Result<Self, NnueError> {
+        Ok(DenseLayer {
+            biases: reader.read_i32s(outputs)?,
+            weights: reader.read_i8s(inputs * outputs)?,
+            inputs,
+        })
+    }
+
+    fn forward(&self, input: &[u8]) -> Vec<i32> {
+        self.biases.iter()
+            .zip(self.weights.chunks_exact(self.inputs))
+            .map(|(bias, row)| bias + row.iter().zip(input)
+                .map(|(weight, value)| *weight as i32 * *value as i32)
+                .sum::<i32>())
+            .collect()
+    }
+}
+
+
+pub struct NnueNetwork {
+    description: String,
+    transformer_biases: Vec<i16>,
+    transformer_weights: Vec<i16>,
+    hidden1: DenseLayer,
+    hidden2: DenseLayer,
+    output: DenseLayer,
+}
+
+impl NnueNetwork {
+    pub fn from_file<P: AsRef<Path>>(path: P) -> Result<Self, NnueError> {
+        let bytes = fs::read(path).map_err(NnueError::ReadError)?;
+        Self::from_bytes(&bytes)
+    }
+
+    pub fn from_bytes(bytes: &[u8]) -> Result<Self, NnueError> {
+        let mut reader = NnueReader { bytes, position: 0 };
+
+        let version = reader.read_u32()?;
+        if version != NNUE_VERSION {
+            return Err(NnueError::UnsupportedVersion(version));
+        }
+        let _architecture_hash = reader.read_u32()?;
+        let description_length = reader.read_u32()? as usize;
+        let description = String::from_utf8_lossy(reader.take(description_length)?).to_string();
+
+        // Other architectures share the header, so the remaining size is
+        // what tells them apart
+        let expected = 4 + TRANSFORMED_SIZE * 2 + HALFKP_FEATURES * TRANSFORMED_SIZE * 2
+            + 4 + (HIDDEN_SIZE * 4 + 2 * TRANSFORMED_SIZE * HIDDEN_SIZE)
+            + (HIDDEN_SIZE * 4 + HIDDEN_SIZE * HIDDEN_SIZE)
+            + (4 + HIDDEN_SIZE);
+        if bytes.len() - reader.position != expected {
+            return Err(NnueError::UnsupportedArchitecture(description));
+        }
+
+        let _transformer_hash = reader.read_u32()?;
+        let transformer_biases = reader.read_i16s(TRANSFORMED_SIZE)?;
+        let transformer_weights = reader.read_i16s(HALFKP_FEATURES * TRANSFORMED_SIZE)?;
+
+        let _network_hash = reader.read_u32()?;
+        let hidden1 = DenseLayer::read(&mut reader, 2 * TRANSFORMED_SIZE, HIDDEN_SIZE)?;
+        let hidden2 = DenseLayer::read(&mut reader, HIDDEN_SIZE, HIDDEN_SIZE)?;
+        let output = DenseLayer::read(&mut reader, HIDDEN_SIZE, 1)?;
+
+        Ok(NnueNetwork {
+            description,
+            transformer_biases,
+            transformer_weights,
+            hidden1,
+            hidden2,
+            output,
+        })
+    }
+
+    pub fn get_description(&self) -> &str {
+        &self.description
+    }
+
+    // Centipawns from the side to move's point of view
+    pub fn evaluate(&self, board: &Board) -> i32 {
+        let side = board.get_active_side();
+        let mut transformed = Vec::with_capacity(2 * TRANSFORMED_SIZE);
+        for perspective in [side, side.opponent()] {
+            let accumulator = self.accumulate(board, perspective);
+            transformed.extend(accumulator.iter()
+                .map(|value| (*value as i32).clamp(0, ACTIVATION_MAX) as u8));
+        }
+
+        let hidden1 = clipped_relu(&self.hidden1.forward(&transformed));
+        let hidden2 = clipped_relu(&self.hidden2.forward(&hidden1));
+        self.output.forward(&hidden2)[0] * 100 / (OUTPUT_SCALE * PAWN_VALUE)
+    }
+
+    fn accumulate(&self, board: &Board, perspective: Side) -> Vec<i16> {
+        let mut accumulator = self.transformer_biases.clone();
+        for feature in halfkp_features(board, perspective) {
+            let row = &self.transformer_weights[feature * TRANSFORMED_SIZE..][..TRANSFORMED_SIZE];
+            for (value, weight) in accumulator.iter_mut().zip(row) {
+                *value = value.wrapping_add(*weight);
+            }
+        }
+        accumulator
+    }
+}
+
+
+pub struct NnueEvaluator {
+    network: Arc<NnueNetwork>,
+}
+
+impl NnueEvaluator {
+    pub fn new(nnue_path: &str) -> Result<Self, NnueError> {
+        Ok(NnueEvaluator {
+            network: Arc::new(NnueNetwork::from_file(nnue_path)?),
+        })
+    }
+
+    pub fn get_network(&self) -> &NnueNetwork {
+        &self.network
+    }
+}
+
+impl Evaluator for NnueEvaluator {
+    fn evaluate_board(&mut self, board: &Board) -> Result<f32, EvalError> {
+        Ok(self.network.evaluate(board) as f32)
+    }
+
+    fn clone_for_thread(&self) -> Option<Box<dyn Evaluator>> {
+        Some(Box::new(NnueEvaluator { network: Arc::clone(&self.network) }))
+    }
+
+    fn reload_model(&mut self, path: &str) -> Result<(), String> {
+        *self = Self::new(path).map_err(|e| e.to_string())?;
+        Ok(())
+    }
+}
+
+
+// Active features for one perspective: every non-king piece paired with
+// that side's king. Black's view is the board rotated by 180 degrees
+fn halfkp_features(board: &Board, perspective: Side) -> Vec<usize> {
+    let orient = |square: Square| match perspective {
+        Side::White => square as usize,
+        Side::Black => square as usize ^ 63,
+    };
+    let king = orient(board.get_king_square(perspective));
+
+    let mut features = Vec::with_capacity(32);
+    for square in board.get_full_occupancy().squares() {
+        let (side, piece) = board.piece_on(square).unwrap();
+        let kind = match piece {
+            Piece::Pawn => 0,
+            Piece::Knight => 1,
+            Piece::Bishop => 2,
+            Piece::Rook => 3,
+            Piece::Queen => 4,
+            Piece::King | Piece::None => continue,
+        };
+        let colour = usize::from(side != perspective);
+        features.push(king * PIECE_SQUARE_FEATURES + 1 + (kind * 2 + colour) * 64 + orient(square));
+    }
+    features
+}
+
+fn clipped_relu(values: &[i32]) -> Vec<u8> {
+    values.iter()
+        .map(|value| (value >> WEIGHT_SCALE_BITS).clamp(0, ACTIVATION_MAX) as u8)
+        .collect()
+}
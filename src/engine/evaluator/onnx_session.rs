@@ -0,0 +1,61 @@
+use half::f16;
+use ndarray::{ArrayD, ArrayViewD, CowArray, IxDyn};
+use ort::{tensor::{OrtOwnedTensor, TensorElementDataType}, Session, Value};
+
+
+// FP16 exports take and return half-precision tensors; int8 models keep
+// f32 inputs and outputs and need no conversion
+pub fn is_half_precision(session: &Session) -> bool {
+    session.inputs.first()
+        .is_some_and(|input| matches!(input.input_type, TensorElementDataType::Float16))
+}
+
+// Runs a session on f32 tensors and returns its outputs as f32, converting
+// on the way in and out when the model is FP16
+pub fn run_session(session: &Session, inputs: Vec<ArrayD<f32>>) -> Result<Vec<ArrayD<f32>>, String> {
+    let mut outputs = Vec::new();
+    let views = inputs.iter().map(|input| input.view()).collect();
+    run_session_with(session, views, |output| outputs.push(output.to_owned()))?;
+    Ok(outputs)
+}
+
+// run_session for callers that keep their own buffers: f32 inputs are
+// handed to the runtime without a copy, and each output is lent to
+// read_output in turn instead of being copied out
+pub fn run_session_with<F>(session: &Session, inputs: Vec<ArrayViewD<f32>>, mut read_output: F) -> Result<(), String>
+where
+    F: FnMut(ArrayViewD<f32>),
+{
+    if is_half_precision(session) {
+        let inputs: Vec<CowArray<f16, IxDyn>> = inputs.into_iter()
+            .map(|input| CowArray::from(input.mapv(f16::from_f32)))
+            .collect();
+        let values = inputs.iter()
+            .map(|input| Value::from_array(session.allocator(), input))
+            .collect::<Result<Vec<_>, _>>()
+            .map_err(|e| e.to_string())?;
+
+        let outputs = session.run(values).map_err(|e| e.to_string())?;
+        for output in &outputs {
+            let tensor: OrtOwnedTensor<f16, IxDyn> = output.try_extract()
+                .map_err(|e| format!("Failed to extract output tensor: {e}"))?;
+            read_output(tensor.view().mapv(f16::to_f32).view());
+        }
+    } else {
+        let inputs: Vec<CowArray<f32, IxDyn>> = inputs.into_iter()
+            .map(CowArray::from)
+            .collect();
+        let values = inputs.iter()
+            .map(|input| Value::from_array(session.allocator(), input))
+            .collect::<Result<Vec<_>, _>>()
+            .map_err(|e| e.to_string())?;
+
+        let outputs = session.run(values).map_err(|e| e.to_string())?;
+        for output in &outputs {
+            let tensor: OrtOwnedTensor<f32, IxDyn> = output.try_extract()
+                .map_err(|e| format!("Failed to extract output tensor: {e}"))?;
+            read_output(tensor.view().into_dyn());
+        }
+    }
+    Ok(())
+}
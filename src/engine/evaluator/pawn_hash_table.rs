@@ -0,0 +1,46 @@
+use crate::engine::{definitions::ZobristKey, evaluator::eval_params::EvalWeight};
+
+
+#[derive(Clone, Copy)]
+pub struct PawnHashEntry {
+    pub pawn_key: ZobristKey,
+    // White-relative pawn structure and king shelter score
+    pub score: EvalWeight,
+}
+
+// Pawn-related terms only depend on the pawns and kings, which change far
+// less often than the rest of the position, so they are cached by pawn key
+pub struct PawnHashTable {
+    entries: Vec<Option<PawnHashEntry>>,
+    mask: usize,
+}
+
+impl PawnHashTable {
+    pub fn new(size_bits: usize) -> Self {
+        let size = 1 << size_bits;
+        PawnHashTable {
+            entries: vec![None; size],
+            mask: size - 1,
+        }
+    }
+
+    fn index(&self, pawn_key: ZobristKey) -> usize {
+        (pawn_key as usize) & self.mask
+    }
+
+    pub fn store(&mut self, pawn_key: ZobristKey, score: EvalWeight) {
+        let idx = self.index(pawn_key);
+        self.entries[idx] = Some(PawnHashEntry { pawn_key, score });
+    }
+
+    pub fn retrieve(&self, pawn_key: ZobristKey) -> Option<EvalWeight> {
+        let idx = self.index(pawn_key);
+        self.entries[idx]
+            .filter(|e| e.pawn_key == pawn_key)
+            .map(|e| e.score)
+    }
+
+    pub fn clear(&mut self) {
+        self.entries.fill(None);
+    }
+}
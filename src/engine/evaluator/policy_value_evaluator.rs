@@ -0,0 +1,140 @@
+use std::{path::Path, sync::Arc};
+
+use ndarray::{ArrayD, Array4, Axis};
+
+use crate::engine::{board::board::Board,
+    evaluator::{evaluator::{EvalError, Evaluator, PolicyEvaluator},
+        features::{policy_index, policy_value_planes, POLICY_SIZE, POLICY_VALUE_CHANNELS},
+        inference_config::InferenceConfig,
+        model_validation::{check_architecture, check_inputs, check_outputs, Dim, ModelError},
+        onnx_session::run_session},
+    move_generator::{chess_move::ChessMove, move_generator::MoveGenerator},
+    searcher::score::Score};
+
+
+// A single ONNX session with two outputs: the value, one number per
+// position for the side to move, and the policy, POLICY_SIZE logits per
+// position. The outputs are told apart by their size, so their order in
+// the export does not matter
+pub struct PolicyValueEvaluator {
+    // Shared with the copies made for search threads
+    session: Arc<ort::Session>,
+    movegen: MoveGenerator,
+    config: InferenceConfig,
+}
+
+impl PolicyValueEvaluator {
+    pub fn new(onnx_model_path: &str) -> Result<Self, String> {
+        Self::with_config(onnx_model_path, &InferenceConfig::new())
+    }
+
+    pub fn with_config(onnx_model_path: &str, config: &InferenceConfig) -> Result<Self, String> {
+        let environment = config.build_environment("policy-value-eval")?;
+        let session = config.build_session(&environment, onnx_model_path)?;
+
+        Self::validate(&session, onnx_model_path).map_err(|e| e.to_string())?;
+
+        Ok(PolicyValueEvaluator {
+            session: Arc::new(session),
+            movegen: MoveGenerator::new(),
+            config: config.clone(),
+        })
+    }
+
+    // Architecture hashes go in <model>.arch next to the model. The two
+    // outputs may come in either order, so only their count is checked here
+    fn validate(session: &ort::Session, onnx_model_path: &str) -> Result<(), ModelError> {
+        let path = Path::new(onnx_model_path);
+        let name = path.file_name().and_then(|name| name.to_str()).unwrap_or(onnx_model_path);
+        check_architecture(&path.with_extension("arch"), name, &Self::architecture())?;
+        let planes = [Dim::Any, Dim::Exactly(POLICY_VALUE_CHANNELS), Dim::Exactly(8), Dim::Exactly(8)];
+        check_inputs(session, name, &[&planes])?;
+        check_outputs(session, name, &[&[], &[]])
+    }
+
+    pub fn architecture() -> String {
+        format!("policy-value planes={POLICY_VALUE_CHANNELS}x8x8 policy={POLICY_SIZE}")
+    }
+
+    // Values and policy logits for each board, in order
+    fn run_batch(&self, boards: &[Board]) -> Result<(Vec<f32>, Vec<Vec<f32>>), EvalError> {
+        let mut batch = Array4::<f32>::zeros((boards.len(), POLICY_VALUE_CHANNELS, 8, 8));
+        for (index, board) in boards.iter().enumerate() {
+            batch.index_axis_mut(Axis(0), index).assign(&policy_value_planes(board));
+        }
+        let outputs = run_session(&self.session, vec![batch.into_dyn()])
+            .map_err(EvalError::InferenceFailed)?;
+
+        let (value, policy) = Self::split_outputs(&outputs, boards.len())?;
+        let policy: Vec<f32> = policy.iter().copied().collect();
+        Ok((value.iter().copied().collect(), policy.chunks(POLICY_SIZE).map(<[f32]>::to_vec).collect()))
+    }
+
+    fn split_outputs(outputs: &[ArrayD<f32>], positions: usize) -> Result<(&ArrayD<f32>, &ArrayD<f32>), EvalError> {
+        let value = outputs.iter().find(|output| output.len() == positions);
+        let policy = outputs.iter().find(|output| output.len() == positions * POLICY_SIZE);
+        match (value, policy) {
+            (Some(value), Some(policy)) => Ok((value, policy)),
+            _ => {
+                let sizes: Vec<usize> = outputs.iter().map(|output| output.len()).collect();
+                Err(EvalError::InvalidOutput(format!("expected {positions} values and {} policy logits, got sizes {sizes:?}",
+                    positions * POLICY_SIZE)))
+            }
+        }
+    }
+
+    // Softmax of the legal moves' logits
+    fn priors(&self, board: &Board, logits: &[f32]) -> Vec<(ChessMove, f32)> {
+        let moves = self.movegen.generate_legal_moves(&mut board.clone());
+        let max = moves.iter()
+            .map(|mv| logits[policy_index(mv)])
+            .fold(f32::NEG_INFINITY, f32::max);
+        let weights: Vec<f32> = moves.iter()
+            .map(|mv| (logits[policy_index(mv)] - max).exp())
+            .collect();
+        let total: f32 = weights.iter().sum();
+
+        moves.into_iter()
+            .zip(weights)
+            .map(|(mv, weight)| (mv, weight / total))
+            .collect()
+    }
+}
+
+impl Evaluator for PolicyValueEvaluator {
+    fn evaluate_board(&mut self, board: &Board) -> Result<f32, EvalError> {
+        let (values, _) = self.run_batch(std::slice::from_ref(board))?;
+        Ok(values[0])
+    }
+
+    fn evaluate_batch(&mut self, boards: &[Board]) -> Result<Vec<f32>, EvalError> {
+        if boards.is_empty() {
+            return Ok(Vec::new());
+        }
+
+        match self.run_batch(boards) {
+            Ok((values, _)) => Ok(values),
+            Err(_) => boards.iter().map(|board| self.evaluate_board(board)).collect(),
+        }
+    }
+
+    fn clone_for_thread(&self) -> Option<Box<dyn Evaluator>> {
+        Some(Box::new(PolicyValueEvaluator {
+            session: Arc::clone(&self.session),
+            movegen: MoveGenerator::new(),
+            config: self.config.clone(),
+        }))
+    }
+
+    fn reload_model(&mut self, path: &str) -> Result<(), String> {
+        *self = Self::with_config(path, &self.config)?;
+        Ok(())
+    }
+}
+
+impl PolicyEvaluator for PolicyValueEvaluator {
+    fn evaluate_with_policy(&mut self, board: &Board) -> Result<(Score, Vec<(ChessMove, f32)>), EvalError> {
+        let (values, logits) = self.run_batch(std::slice::from_ref(board))?;
+        Ok((Score::new(values[0]), self.priors(board, &logits[0])))
+    }
+}
@@ -0,0 +1,375 @@
+// Vector kernels for the accumulator updates and dense layers. On x86_64
+// AVX2 is picked at runtime; NEON is part of the aarch64 baseline. Every
+// kernel has a scalar fallback and works on the common prefix of its inputs
+
+
+#[inline]
+pub fn add_assign(accumulator: &mut [f32], row: &[f32]) {
+    #[cfg(target_arch = "x86_64")]
+    if is_x86_feature_detected!("avx2") {
+        // AVX2 support was just checked
+        unsafe { avx2::add_assign(accumulator, row) };
+        return;
+    }
+
+    #[cfg(target_arch = "aarch64")]
+    {
+        neon::add_assign(accumulator, row);
+        return;
+    }
+
+    #[allow(unreachable_code)]
+    for (value, weight) in accumulator.iter_mut().zip(row) {
+        *value += weight;
+    }
+}
+
+#[inline]
+pub fn sub_assign(accumulator: &mut [f32], row: &[f32]) {
+    #[cfg(target_arch = "x86_64")]
+    if is_x86_feature_detected!("avx2") {
+        unsafe { avx2::sub_assign(accumulator, row) };
+        return;
+    }
+
+    #[cfg(target_arch = "aarch64")]
+    {
+        neon::sub_assign(accumulator, row);
+        return;
+    }
+
+    #[allow(unreachable_code)]
+    for (value, weight) in accumulator.iter_mut().zip(row) {
+        *value -= weight;
+    }
+}
+
+// Wrapping i16 versions for fixed-point accumulators
+#[inline]
+pub fn add_assign_i16(accumulator: &mut [i16], row: &[i16]) {
+    #[cfg(target_arch = "x86_64")]
+    if is_x86_feature_detected!("avx2") {
+        unsafe { avx2::add_assign_i16(accumulator, row) };
+        return;
+    }
+
+    #[cfg(target_arch = "aarch64")]
+    {
+        neon::add_assign_i16(accumulator, row);
+        return;
+    }
+
+    #[allow(unreachable_code)]
+    for (value, weight) in accumulator.iter_mut().zip(row) {
+        *value = value.wrapping_add(*weight);
+    }
+}
+
+#[inline]
+pub fn sub_assign_i16(accumulator: &mut [i16], row: &[i16]) {
+    #[cfg(target_arch = "x86_64")]
+    if is_x86_feature_detected!("avx2") {
+        unsafe { avx2::sub_assign_i16(accumulator, row) };
+        return;
+    }
+
+    #[cfg(target_arch = "aarch64")]
+    {
+        neon::sub_assign_i16(accumulator, row);
+        return;
+    }
+
+    #[allow(unreachable_code)]
+    for (value, weight) in accumulator.iter_mut().zip(row) {
+        *value = value.wrapping_sub(*weight);
+    }
+}
+
+#[inline]
+pub fn dot(a: &[f32], b: &[f32]) -> f32 {
+    #[cfg(target_arch = "x86_64")]
+    if is_x86_feature_detected!("avx2") && is_x86_feature_detected!("fma") {
+        return unsafe { avx2::dot(a, b) };
+    }
+
+    #[cfg(target_arch = "aarch64")]
+    {
+        return neon::dot(a, b);
+    }
+
+    #[allow(unreachable_code)]
+    a.iter().zip(b).map(|(x, y)| x * y).sum()
+}
+
+
+#[cfg(target_arch = "x86_64")]
+mod avx2 {
+    use std::arch::x86_64::*;
+
+    const LANES: usize = 8;
+
+    #[target_feature(enable = "avx2")]
+    pub unsafe fn add_assign(accumulator: &mut [f32], row: &[f32]) {
+        let len = accumulator.len().min(row.len());
+        let vector_len = len - len % LANES;
+        let (acc, row) = (accumulator.as_mut_ptr(), row.as_ptr());
+
+        for i in (0..vector_len).step_by(LANES) {
+            // i + LANES <= len for both slices
+            unsafe {
+                let sum = _mm256_add_ps(_mm256_loadu_ps(acc.add(i)), _mm256_loadu_ps(row.add(i)));
+                _mm256_storeu_ps(acc.add(i), sum);
+            }
+        }
+        for i in vector_len..len {
+            unsafe { *acc.add(i) += *row.add(i) };
+        }
+    }
+
+    #[target_feature(enable = "avx2")]
+    pub unsafe fn sub_assign(accumulator: &mut [f32], row: &[f32]) {
+        let len = accumulator.len().min(row.len());
+        let vector_len = len - len % LANES;
+        let (acc, row) = (accumulator.as_mut_ptr(), row.as_ptr());
+
+        for i in (0..vector_len).step_by(LANES) {
+            unsafe {
+                let difference = _mm256_sub_ps(_mm256_loadu_ps(acc.add(i)), _mm256_loadu_ps(row.add(i)));
+                _mm256_storeu_ps(acc.add(i), difference);
+            }
+        }
+        for i in vector_len..len {
+            unsafe { *acc.add(i) -= *row.add(i) };
+        }
+    }
+
+    const LANES_I16: usize = 16;
+
+    #[target_feature(enable = "avx2")]
+    pub unsafe fn add_assign_i16(accumulator: &mut [i16], row: &[i16]) {
+        let len = accumulator.len().min(row.len());
+        let vector_len = len - len % LANES_I16;
+        let (acc, row) = (accumulator.as_mut_ptr(), row.as_ptr());
+
+        for i in (0..vector_len).step_by(LANES_I16) {
+            unsafe {
+                let sum = _mm256_add_epi16(_mm256_loadu_si256(acc.add(i) as *const __m256i),
+                    _mm256_loadu_si256(row.add(i) as *const __m256i));
+                _mm256_storeu_si256(acc.add(i) as *mut __m256i, sum);
+            }
+        }
+        for i in vector_len..len {
+            unsafe { *acc.add(i) = (*acc.add(i)).wrapping_add(*row.add(i)) };
+        }
+    }
+
+    #[target_feature(enable = "avx2")]
+    pub unsafe fn sub_assign_i16(accumulator: &mut [i16], row: &[i16]) {
+        let len = accumulator.len().min(row.len());
+        let vector_len = len - len % LANES_I16;
+        let (acc, row) = (accumulator.as_mut_ptr(), row.as_ptr());
+
+        for i in (0..vector_len).step_by(LANES_I16) {
+            unsafe {
+                let difference = _mm256_sub_epi16(_mm256_loadu_si256(acc.add(i) as *const __m256i),
+                    _mm256_loadu_si256(row.add(i) as *const __m256i));
+                _mm256_storeu_si256(acc.add(i) as *mut __m256i, difference);
+            }
+        }
+        for i in vector_len..len {
+            unsafe { *acc.add(i) = (*acc.add(i)).wrapping_sub(*row.add(i)) };
+        }
+    }
+
+    #[target_feature(enable = "avx2,fma")]
+    pub unsafe fn dot(a: &[f32], b: &[f32]) -> f32 {
+        let len = a.len().min(b.len());
+        let vector_len = len - len % LANES;
+        let (a, b) = (a.as_ptr(), b.as_ptr());
+
+        let mut sum = _mm256_setzero_ps();
+        for i in (0..vector_len).step_by(LANES) {
+            unsafe {
+                sum = _mm256_fmadd_ps(_mm256_loadu_ps(a.add(i)), _mm256_loadu_ps(b.add(i)), sum);
+            }
+        }
+
+        // Fold the eight lanes into one
+        let halves = _mm_add_ps(_mm256_castps256_ps128(sum), _mm256_extractf128_ps(sum, 1));
+        let pairs = _mm_add_ps(halves, _mm_movehl_ps(halves, halves));
+        let mut total = _mm_cvtss_f32(_mm_add_ss(pairs, _mm_shuffle_ps(pairs, pairs, 1)));
+
+        for i in vector_len..len {
+            total += unsafe { *a.add(i) * *b.add(i) };
+        }
+        total
+    }
+}
+
+
+#[cfg(target_arch = "aarch64")]
+mod neon {
+    use std::arch::aarch64::*;
+
+    const LANES: usize = 4;
+
+    pub fn add_assign(accumulator: &mut [f32], row: &[f32]) {
+        let len = accumulator.len().min(row.len());
+        let vector_len = len - len % LANES;
+        let (acc, row_ptr) = (accumulator.as_mut_ptr(), row.as_ptr());
+
+        for i in (0..vector_len).step_by(LANES) {
+            // i + LANES <= len for both slices
+            unsafe { vst1q_f32(acc.add(i), vaddq_f32(vld1q_f32(acc.add(i)), vld1q_f32(row_ptr.add(i)))) };
+        }
+        for i in vector_len..len {
+            accumulator[i] += row[i];
+        }
+    }
+
+    pub fn sub_assign(accumulator: &mut [f32], row: &[f32]) {
+        let len = accumulator.len().min(row.len());
+        let vector_len = len - len % LANES;
+        let (acc, row_ptr) = (accumulator.as_mut_ptr(), row.as_ptr());
+
+        for i in (0..vector_len).step_by(LANES) {
+            unsafe { vst1q_f32(acc.add(i), vsubq_f32(vld1q_f32(acc.add(i)), vld1q_f32(row_ptr.add(i)))) };
+        }
+        for i in vector_len..len {
+            accumulator[i] -= row[i];
+        }
+    }
+
+    const LANES_I16: usize = 8;
+
+    pub fn add_assign_i16(accumulator: &mut [i16], row: &[i16]) {
+        let len = accumulator.len().min(row.len());
+        let vector_len = len - len % LANES_I16;
+        let (acc, row_ptr) = (accumulator.as_mut_ptr(), row.as_ptr());
+
+        for i in (0..vector_len).step_by(LANES_I16) {
+            unsafe { vst1q_s16(acc.add(i), vaddq_s16(vld1q_s16(acc.add(i)), vld1q_s16(row_ptr.add(i)))) };
+        }
+        for i in vector_len..len {
+            accumulator[i] = accumulator[i].wrapping_add(row[i]);
+        }
+    }
+
+    pub fn sub_assign_i16(accumulator: &mut [i16], row: &[i16]) {
+        let len = accumulator.len().min(row.len());
+        let vector_len = len - len % LANES_I16;
+        let (acc, row_ptr) = (accumulator.as_mut_ptr(), row.as_ptr());
+
+        for i in (0..vector_len).step_by(LANES_I16) {
+            unsafe { vst1q_s16(acc.add(i), vsubq_s16(vld1q_s16(acc.add(i)), vld1q_s16(row_ptr.add(i)))) };
+        }
+        for i in vector_len..len {
+            accumulator[i] = accumulator[i].wrapping_sub(row[i]);
+        }
+    }
+
+    pub fn dot(a: &[f32], b: &[f32]) -> f32 {
+        let len = a.len().min(b.len());
+        let vector_len = len - len % LANES;
+
+        let mut total = unsafe {
+            let mut sum = vdupq_n_f32(0.0);
+            for i in (0..vector_len).step_by(LANES) {
+                sum = vfmaq_f32(sum, vld1q_f32(a.as_ptr().add(i)), vld1q_f32(b.as_ptr().add(i)));
+            }
+            vaddvq_f32(sum)
+        };
+        for i in vector_len..len {
+            total += a[i] * b[i];
+        }
+        total
+    }
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // Covers empty inputs, the tails left after full AVX2 and NEON lanes
+    // and lengths of whole lanes
+    const LENGTHS: [usize; 12] = [0, 1, 3, 7, 8, 9, 15, 16, 17, 31, 33, 257];
+
+    fn floats(len: usize, seed: u32) -> Vec<f32> {
+        (0..len).map(|i| ((i as u32 * 7919 + seed * 104729) % 2001) as f32 / 100.0 - 10.0).collect()
+    }
+
+    // Mostly values near the ends of the range, so sums and differences wrap
+    fn shorts(len: usize, seed: i32) -> Vec<i16> {
+        (0..len).map(|i| match (i as i32 + seed) % 4 {
+            0 => i16::MAX - (i % 5) as i16,
+            1 => i16::MIN + (i % 7) as i16,
+            2 => (i as i16).wrapping_mul(1237),
+            _ => -(i as i16),
+        }).collect()
+    }
+
+    fn length_pairs() -> impl Iterator<Item = (usize, usize)> {
+        LENGTHS.into_iter().flat_map(|a| [(a, a), (a, a + 1), (a + 5, a), (a, a / 2)])
+    }
+
+    #[test]
+    fn add_and_sub_assign_match_scalar() {
+        for (acc_len, row_len) in length_pairs() {
+            let row = floats(row_len, 2);
+
+            let mut added = floats(acc_len, 1);
+            let mut expected = added.clone();
+            add_assign(&mut added, &row);
+            for (value, weight) in expected.iter_mut().zip(&row) {
+                *value += weight;
+            }
+            assert_eq!(added, expected, "add_assign, lengths {acc_len} and {row_len}");
+
+            let mut subtracted = floats(acc_len, 1);
+            let mut expected = subtracted.clone();
+            sub_assign(&mut subtracted, &row);
+            for (value, weight) in expected.iter_mut().zip(&row) {
+                *value -= weight;
+            }
+            assert_eq!(subtracted, expected, "sub_assign, lengths {acc_len} and {row_len}");
+        }
+    }
+
+    #[test]
+    fn i16_add_and_sub_assign_wrap_like_scalar() {
+        for (acc_len, row_len) in length_pairs() {
+            let row = shorts(row_len, 1);
+
+            let mut added = shorts(acc_len, 0);
+            let mut expected = added.clone();
+            add_assign_i16(&mut added, &row);
+            for (value, weight) in expected.iter_mut().zip(&row) {
+                *value = value.wrapping_add(*weight);
+            }
+            assert_eq!(added, expected, "add_assign_i16, lengths {acc_len} and {row_len}");
+
+            let mut subtracted = shorts(acc_len, 0);
+            let mut expected = subtracted.clone();
+            sub_assign_i16(&mut subtracted, &row);
+            for (value, weight) in expected.iter_mut().zip(&row) {
+                *value = value.wrapping_sub(*weight);
+            }
+            assert_eq!(subtracted, expected, "sub_assign_i16, lengths {acc_len} and {row_len}");
+        }
+    }
+
+    #[test]
+    fn dot_matches_scalar() {
+        for (a_len, b_len) in length_pairs() {
+            let (a, b) = (floats(a_len, 3), floats(b_len, 4));
+            let expected: f32 = a.iter().zip(&b).map(|(x, y)| x * y).sum();
+            // The lanes add up in another order, and fused multiply-adds
+            // round once instead of twice
+            let magnitude: f32 = a.iter().zip(&b).map(|(x, y)| (x * y).abs()).sum();
+            let result = dot(&a, &b);
+            assert!((result - expected).abs() <= 1e-5 * magnitude.max(1.0),
+                "dot, lengths {a_len} and {b_len}: {result} against {expected}");
+        }
+    }
+}
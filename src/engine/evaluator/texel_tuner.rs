@@ -0,0 +1,163 @@
+use std::io::BufRead;
+
+use rayon::prelude::*;
+
+use crate::engine::{board::{board::Board, game_history::GameHistory},
+    definitions::Side,
+    evaluator::{classical_evaluator::ClassicalEvaluator, eval_params::EvalParams},
+    pgn::training_data::TrainingSample};
+
+
+// A position labelled with the game's outcome from White's point of view:
+// 1.0 win, 0.5 draw, 0.0 loss
+pub struct TuningPosition {
+    pub board: Board,
+    pub result: f32,
+}
+
+
+// Fits the classical evaluation weights to game outcomes by minimising the
+// mean squared error between the result and sigmoid(eval), changing one
+// weight at a time. The piece-square tables stay fixed
+pub struct TexelTuner {
+    positions: Vec<TuningPosition>,
+    scaling: f64,
+    step: i32,
+    max_iterations: usize,
+}
+
+impl TexelTuner {
+    pub fn new(positions: Vec<TuningPosition>) -> Self {
+        TexelTuner {
+            positions,
+            scaling: 1.0,
+            step: 1,
+            max_iterations: 100,
+        }
+    }
+
+    pub fn from_samples(samples: &[TrainingSample]) -> Self {
+        let positions = samples.iter()
+            .filter_map(|sample| {
+                let mut board = Board::try_from_fen(&sample.fen).ok()?;
+                board.game_history = GameHistory::with_capacity(0);
+                Some(TuningPosition { board, result: sample.result })
+            })
+            .collect();
+        Self::new(positions)
+    }
+
+    // Reads the "fen,result,eval" lines the training-data extractor writes;
+    // lines that do not parse are skipped
+    pub fn from_csv<R: BufRead>(input: R) -> Self {
+        let positions = input.lines()
+            .map_while(Result::ok)
+            .filter_map(|line| {
+                let mut fields = line.split(',');
+                let mut board = Board::try_from_fen(fields.next()?).ok()?;
+                let result = fields.next()?.trim().parse().ok()?;
+                // Millions of positions are held at once, so drop the
+                // history's preallocated move buffer
+                board.game_history = GameHistory::with_capacity(0);
+                Some(TuningPosition { board, result })
+            })
+            .collect();
+        Self::new(positions)
+    }
+
+    pub fn set_step(&mut self, step: i32) {
+        self.step = step.max(1);
+    }
+
+    pub fn set_max_iterations(&mut self, max_iterations: usize) {
+        self.max_iterations = max_iterations;
+    }
+
+    pub fn get_scaling(&self) -> f64 {
+        self.scaling
+    }
+
+    pub fn len(&self) -> usize {
+        self.positions.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.positions.is_empty()
+    }
+
+    pub fn error(&self, params: &EvalParams) -> f64 {
+        if self.positions.is_empty() {
+            return 0.0;
+        }
+
+        let evaluator = ClassicalEvaluator::with_params(params.clone());
+        let total: f64 = self.positions.par_iter()
+            .map(|position| {
+                let score = evaluator.evaluate_uncached(&position.board);
+                let white_score = match position.board.get_active_side() {
+                    Side::White => score,
+                    Side::Black => -score,
+                };
+                let difference = position.result as f64 - self.sigmoid(white_score);
+                difference * difference
+            })
+            .sum();
+        total / self.positions.len() as f64
+    }
+
+    // Picks the sigmoid scaling that best fits the starting weights, so the
+    // tuning only moves weights and not the overall eval scale
+    pub fn fit_scaling(&mut self, params: &EvalParams) -> f64 {
+        let (mut low, mut high) = (0.1, 3.0);
+        for _ in 0..40 {
+            let third = (high - low) / 3.0;
+            let (left, right) = (low + third, high - third);
+            self.scaling = left;
+            let left_error = self.error(params);
+            self.scaling = right;
+            let right_error = self.error(params);
+            if left_error < right_error {
+                high = right;
+            } else {
+                low = left;
+            }
+        }
+        self.scaling = (low + high) / 2.0;
+        self.scaling
+    }
+
+    pub fn tune(&mut self, initial: &EvalParams) -> EvalParams {
+        self.fit_scaling(initial);
+
+        let mut weights = initial.to_vector();
+        let mut best_error = self.error(initial);
+
+        for _ in 0..self.max_iterations {
+            let mut improved = false;
+
+            for index in 0..weights.len() {
+                for delta in [self.step, -self.step] {
+                    weights[index] += delta;
+                    let error = self.error(&EvalParams::from_vector(&weights));
+                    if error < best_error {
+                        best_error = error;
+                        improved = true;
+                        break;
+                    }
+                    weights[index] -= delta;
+                }
+            }
+
+            if !improved {
+                break;
+            }
+        }
+
+        EvalParams::from_vector(&weights)
+    }
+
+    // Expected score for White of a centipawn evaluation
+    fn sigmoid(&self, score: i32) -> f64 {
+        1.0 / (1.0 + 10f64.powf(-self.scaling * score as f64 / 400.0))
+    }
+}
@@ -1,6 +1,12 @@
-pub mod definitions;
+// The rules live in kingcrab-core, and are re-exported here so the engine
+// keeps its module paths
+pub use kingcrab_core::{bitboard, board, definitions, move_generator, rules};
 
-pub mod board;
-pub mod move_generator;
 pub mod evaluator;
-pub mod searcher;
\ No newline at end of file
+pub mod searcher;
+pub mod tablebase;
+pub mod pgn;
+pub mod selfplay;
+pub mod analysis;
+
+pub mod solver;
@@ -1,7 +0,0 @@
-pub mod chess_move;
-
-pub mod move_generator;
-
-mod magics;
-
-mod move_sorter;
\ No newline at end of file
@@ -0,0 +1,213 @@
+use std::{fmt::{self, Display}, str::FromStr, time::Duration};
+
+use crate::engine::{board::{board::Board, game_result::GameResult},
+    definitions::Side, move_generator::chess_move::ChessMove, rules::variant::Variant};
+use super::nag::is_move_assessment;
+
+
+// The Seven Tag Roster, in the order the PGN standard exports it
+pub const SEVEN_TAG_ROSTER: [&str; 7] = [
+    "Event", "Site", "Date", "Round", "White", "Black", "Result",
+];
+
+
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum PgnResult {
+    WhiteWins,
+    BlackWins,
+    Draw,
+    Unknown,
+}
+
+impl Display for PgnResult {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            PgnResult::WhiteWins => write!(f, "1-0"),
+            PgnResult::BlackWins => write!(f, "0-1"),
+            PgnResult::Draw => write!(f, "1/2-1/2"),
+            PgnResult::Unknown => write!(f, "*"),
+        }
+    }
+}
+
+impl FromStr for PgnResult {
+    type Err = String;
+
+    fn from_str(result: &str) -> Result<Self, Self::Err> {
+        match result {
+            "1-0" => Ok(PgnResult::WhiteWins),
+            "0-1" => Ok(PgnResult::BlackWins),
+            "1/2-1/2" => Ok(PgnResult::Draw),
+            "*" => Ok(PgnResult::Unknown),
+            _ => Err(format!("Invalid game result: {result}")),
+        }
+    }
+}
+
+impl From<GameResult> for PgnResult {
+    fn from(result: GameResult) -> Self {
+        match result {
+            GameResult::Checkmate(Side::White) | GameResult::VariantWin(Side::White) => PgnResult::WhiteWins,
+            GameResult::Checkmate(Side::Black) | GameResult::VariantWin(Side::Black) => PgnResult::BlackWins,
+            GameResult::Ongoing => PgnResult::Unknown,
+            _ => PgnResult::Draw,
+        }
+    }
+}
+
+
+#[derive(Clone, Debug)]
+pub struct GameMove {
+    pub mv: ChessMove,
+    pub comment: Option<String>,
+    // In pawns, from White's point of view
+    pub eval: Option<f32>,
+    // Time left on the mover's clock after the move
+    pub clock: Option<Duration>,
+    // Numeric annotation glyphs, $1 being "good move"
+    pub nags: Vec<u8>,
+    // Alternatives to this move, each played from the position before it
+    pub variations: Vec<Vec<GameMove>>,
+}
+
+impl GameMove {
+    pub fn new(mv: ChessMove) -> Self {
+        Self {
+            mv,
+            comment: None,
+            eval: None,
+            clock: None,
+            nags: Vec::new(),
+            variations: Vec::new(),
+        }
+    }
+
+    // A move carries at most one assessment ($1-$6); a new one replaces it
+    pub fn add_nag(&mut self, nag: u8) {
+        if is_move_assessment(nag) {
+            self.nags.retain(|existing| !is_move_assessment(*existing));
+        }
+        if !self.nags.contains(&nag) {
+            self.nags.push(nag);
+        }
+    }
+
+    pub fn add_comment(&mut self, comment: &str) {
+        match &mut self.comment {
+            Some(existing) => {
+                existing.push(' ');
+                existing.push_str(comment);
+            }
+            None => self.comment = Some(comment.to_string()),
+        }
+    }
+
+    pub fn is_annotated(&self) -> bool {
+        self.comment.is_some() || self.eval.is_some() || !self.nags.is_empty()
+    }
+}
+
+
+#[derive(Clone)]
+pub struct Game {
+    pub tags: Vec<(String, String)>,
+    pub starting_position: Board,
+    // Text before the first move
+    pub comment: Option<String>,
+    pub moves: Vec<GameMove>,
+    pub result: PgnResult,
+}
+
+impl Game {
+    pub fn new() -> Self {
+        let mut starting_position = Board::new();
+        starting_position.from_fen(None).unwrap();
+        Self::from_position(&starting_position)
+    }
+
+    // Starts a game from the given position, ignoring how it was reached
+    pub fn from_position(board: &Board) -> Self {
+        let mut starting_position = board.clone();
+        starting_position.game_history.clear();
+
+        let tags = SEVEN_TAG_ROSTER.iter()
+            .map(|name| {
+                let value = match *name {
+                    "Date" => "????.??.??",
+                    "Result" => "*",
+                    _ => "?",
+                };
+                (name.to_string(), value.to_string())
+            })
+            .collect();
+
+        Self {
+            tags,
+            starting_position,
+            comment: None,
+            moves: Vec::new(),
+            result: PgnResult::Unknown,
+        }
+    }
+
+    // Rewinds the board's history so the game holds every move played on it
+    pub fn from_board(board: &Board) -> Self {
+        let mut starting_position = board.clone();
+        while !starting_position.game_history.is_empty() {
+            starting_position.undo_move();
+        }
+
+        let mut game = Self::from_position(&starting_position);
+        game.moves = board.game_history.iter()
+            .map(|recorded| GameMove::new(recorded.mv))
+            .collect();
+        game
+    }
+
+    pub fn get_tag(&self, name: &str) -> Option<&str> {
+        self.tags.iter()
+            .find(|(tag, _)| tag == name)
+            .map(|(_, value)| value.as_str())
+    }
+
+    // From the Variant tag; standard chess without one
+    pub fn get_variant(&self) -> Result<Variant, String> {
+        self.get_tag("Variant").map_or(Ok(Variant::Standard), str::parse)
+    }
+
+    pub fn set_tag(&mut self, name: &str, value: &str) {
+        match self.tags.iter_mut().find(|(tag, _)| tag == name) {
+            Some((_, old_value)) => *old_value = value.to_string(),
+            None => self.tags.push((name.to_string(), value.to_string())),
+        }
+    }
+
+    pub fn set_result(&mut self, result: PgnResult) {
+        self.result = result;
+        self.set_tag("Result", &result.to_string());
+    }
+
+    pub fn push(&mut self, mv: ChessMove) -> &mut GameMove {
+        self.moves.push(GameMove::new(mv));
+        self.moves.last_mut().unwrap()
+    }
+
+    // The position after the last move
+    pub fn final_position(&self) -> Board {
+        let mut board = self.starting_position.clone();
+        for game_move in &self.moves {
+            if game_move.mv.is_null() {
+                board.make_null_move();
+            } else {
+                board.make_move(game_move.mv);
+            }
+        }
+        board
+    }
+}
+
+impl Default for Game {
+    fn default() -> Self {
+        Self::new()
+    }
+}
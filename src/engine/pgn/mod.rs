@@ -0,0 +1,9 @@
+pub mod game;
+
+pub mod nag;
+
+pub mod writer;
+
+pub mod reader;
+
+pub mod training_data;
\ No newline at end of file
@@ -0,0 +1,88 @@
+// Numeric annotation glyphs from the PGN standard. Only the common ones get
+// names; any value up to 255 can be stored on a move
+
+pub const NAG_NULL: u8 = 0;
+pub const NAG_GOOD_MOVE: u8 = 1;
+pub const NAG_MISTAKE: u8 = 2;
+pub const NAG_BRILLIANT_MOVE: u8 = 3;
+pub const NAG_BLUNDER: u8 = 4;
+pub const NAG_SPECULATIVE_MOVE: u8 = 5;
+pub const NAG_DUBIOUS_MOVE: u8 = 6;
+pub const NAG_FORCED_MOVE: u8 = 7;
+pub const NAG_DRAWISH_POSITION: u8 = 10;
+pub const NAG_UNCLEAR_POSITION: u8 = 13;
+pub const NAG_WHITE_SLIGHT_ADVANTAGE: u8 = 14;
+pub const NAG_BLACK_SLIGHT_ADVANTAGE: u8 = 15;
+pub const NAG_WHITE_MODERATE_ADVANTAGE: u8 = 16;
+pub const NAG_BLACK_MODERATE_ADVANTAGE: u8 = 17;
+pub const NAG_WHITE_DECISIVE_ADVANTAGE: u8 = 18;
+pub const NAG_BLACK_DECISIVE_ADVANTAGE: u8 = 19;
+pub const NAG_WHITE_ZUGZWANG: u8 = 22;
+pub const NAG_BLACK_ZUGZWANG: u8 = 23;
+pub const NAG_WHITE_TIME_TROUBLE: u8 = 138;
+pub const NAG_BLACK_TIME_TROUBLE: u8 = 139;
+
+
+// $1 to $6 judge the move itself and are written as suffixes like "!?"
+pub fn is_move_assessment(nag: u8) -> bool {
+    (NAG_GOOD_MOVE..=NAG_DUBIOUS_MOVE).contains(&nag)
+}
+
+pub fn nag_symbol(nag: u8) -> Option<&'static str> {
+    match nag {
+        NAG_GOOD_MOVE => Some("!"),
+        NAG_MISTAKE => Some("?"),
+        NAG_BRILLIANT_MOVE => Some("!!"),
+        NAG_BLUNDER => Some("??"),
+        NAG_SPECULATIVE_MOVE => Some("!?"),
+        NAG_DUBIOUS_MOVE => Some("?!"),
+        NAG_FORCED_MOVE => Some("□"),
+        NAG_DRAWISH_POSITION => Some("="),
+        NAG_UNCLEAR_POSITION => Some("∞"),
+        NAG_WHITE_SLIGHT_ADVANTAGE => Some("⩲"),
+        NAG_BLACK_SLIGHT_ADVANTAGE => Some("⩱"),
+        NAG_WHITE_MODERATE_ADVANTAGE => Some("±"),
+        NAG_BLACK_MODERATE_ADVANTAGE => Some("∓"),
+        NAG_WHITE_DECISIVE_ADVANTAGE => Some("+-"),
+        NAG_BLACK_DECISIVE_ADVANTAGE => Some("-+"),
+        _ => None,
+    }
+}
+
+pub fn nag_from_symbol(symbol: &str) -> Option<u8> {
+    match symbol {
+        "!" => Some(NAG_GOOD_MOVE),
+        "?" => Some(NAG_MISTAKE),
+        "!!" => Some(NAG_BRILLIANT_MOVE),
+        "??" => Some(NAG_BLUNDER),
+        "!?" => Some(NAG_SPECULATIVE_MOVE),
+        "?!" => Some(NAG_DUBIOUS_MOVE),
+        _ => None,
+    }
+}
+
+pub fn nag_description(nag: u8) -> Option<&'static str> {
+    match nag {
+        NAG_NULL => Some("null annotation"),
+        NAG_GOOD_MOVE => Some("good move"),
+        NAG_MISTAKE => Some("mistake"),
+        NAG_BRILLIANT_MOVE => Some("brilliant move"),
+        NAG_BLUNDER => Some("blunder"),
+        NAG_SPECULATIVE_MOVE => Some("speculative move"),
+        NAG_DUBIOUS_MOVE => Some("dubious move"),
+        NAG_FORCED_MOVE => Some("forced move"),
+        NAG_DRAWISH_POSITION => Some("drawish position"),
+        NAG_UNCLEAR_POSITION => Some("unclear position"),
+        NAG_WHITE_SLIGHT_ADVANTAGE => Some("White has a slight advantage"),
+        NAG_BLACK_SLIGHT_ADVANTAGE => Some("Black has a slight advantage"),
+        NAG_WHITE_MODERATE_ADVANTAGE => Some("White has a moderate advantage"),
+        NAG_BLACK_MODERATE_ADVANTAGE => Some("Black has a moderate advantage"),
+        NAG_WHITE_DECISIVE_ADVANTAGE => Some("White has a decisive advantage"),
+        NAG_BLACK_DECISIVE_ADVANTAGE => Some("Black has a decisive advantage"),
+        NAG_WHITE_ZUGZWANG => Some("White is in zugzwang"),
+        NAG_BLACK_ZUGZWANG => Some("Black is in zugzwang"),
+        NAG_WHITE_TIME_TROUBLE => Some("White is in severe time trouble"),
+        NAG_BLACK_TIME_TROUBLE => Some("Black is in severe time trouble"),
+        _ => None,
+    }
+}
@@ -0,0 +1,463 @@
+use std::{fmt::{self, Display}, io::{self, BufRead}, str::FromStr, time::Duration};
+
+use crate::engine::{board::{board::Board, fen::FenError},
+    move_generator::{chess_move::ChessMove, move_generator::MoveGenerator,
+    notation::{self, NotationError}}};
+use super::{game::{Game, GameMove, PgnResult}, nag::nag_from_symbol};
+
+
+#[derive(Debug)]
+pub enum PgnError {
+    MalformedPgn(String),
+    InvalidFen(FenError),
+    InvalidMove(NotationError),
+    ReadError(io::Error),
+}
+
+impl Display for PgnError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::MalformedPgn(message) =>
+                    write!(f, "Malformed PGN: {message}"),
+            Self::InvalidFen(error) =>
+                    write!(f, "Invalid FEN tag: {error}"),
+            Self::InvalidMove(error) =>
+                    write!(f, "Invalid move in PGN: {error}"),
+            Self::ReadError(error) =>
+                    write!(f, "Failed to read PGN: {error}"),
+        }
+    }
+}
+
+
+#[derive(PartialEq, Debug)]
+enum Token {
+    Tag(String, String),
+    Comment(String),
+    Nag(u8),
+    VariationStart,
+    VariationEnd,
+    Move(String),
+    Result(PgnResult),
+}
+
+
+// A line of play being replayed while parsing; variations push a new one
+struct Line {
+    moves: Vec<GameMove>,
+    board: Board,
+    // Comment seen before the first move of the line
+    comment: Option<String>,
+}
+
+
+// Reads the games of a PGN file one at a time, so large collections do not
+// have to be loaded into memory
+pub struct PgnReader<'a, R: BufRead> {
+    input: R,
+    move_generator: &'a MoveGenerator,
+    // First line of the next game, read while looking for the end of the last
+    pending_line: Option<String>,
+}
+
+impl<'a, R: BufRead> PgnReader<'a, R> {
+    pub fn new(input: R, move_generator: &'a MoveGenerator) -> Self {
+        Self {
+            input,
+            move_generator,
+            pending_line: None,
+        }
+    }
+
+    // A game ends where the tags of the next one start, outside any comment
+    fn next_game_text(&mut self) -> Result<Option<String>, PgnError> {
+        let mut text = String::new();
+        let mut in_movetext = false;
+        let mut in_comment = false;
+
+        loop {
+            let line = match self.pending_line.take() {
+                Some(line) => line,
+                None => {
+                    let mut line = String::new();
+                    let bytes = self.input.read_line(&mut line)
+                        .map_err(PgnError::ReadError)?;
+                    if bytes == 0 {
+                        break;
+                    }
+                    line
+                }
+            };
+
+            // Lines starting with % are escaped for other programs
+            if line.starts_with('%') {
+                continue;
+            }
+
+            let trimmed = line.trim_start();
+            if !in_comment && trimmed.starts_with('[') {
+                if in_movetext {
+                    self.pending_line = Some(line);
+                    break;
+                }
+            } else if !trimmed.is_empty() {
+                in_movetext = true;
+            }
+
+            for c in line.chars() {
+                match c {
+                    '{' if !in_comment => in_comment = true,
+                    '}' if in_comment => in_comment = false,
+                    ';' if !in_comment => break,
+                    _ => (),
+                }
+            }
+
+            text.push_str(&line);
+        }
+
+        if text.trim().is_empty() {
+            Ok(None)
+        } else {
+            Ok(Some(text))
+        }
+    }
+}
+
+impl<R: BufRead> Iterator for PgnReader<'_, R> {
+    type Item = Result<Game, PgnError>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        match self.next_game_text() {
+            Ok(Some(text)) => Some(parse_game(&text, self.move_generator)),
+            Ok(None) => None,
+            Err(error) => Some(Err(error)),
+        }
+    }
+}
+
+
+pub fn parse_pgn(pgn: &str, move_generator: &MoveGenerator) -> Result<Vec<Game>, PgnError> {
+    PgnReader::new(pgn.as_bytes(), move_generator).collect()
+}
+
+// Parses a single game: tag pairs followed by movetext with comments,
+// NAGs and nested variations
+pub fn parse_game(pgn: &str, move_generator: &MoveGenerator) -> Result<Game, PgnError> {
+    let tokens = tokenize(pgn)?;
+
+    let mut tags = Vec::new();
+    let mut movetext = tokens.into_iter().peekable();
+    while let Some(Token::Tag(..)) = movetext.peek() {
+        if let Some(Token::Tag(name, value)) = movetext.next() {
+            tags.push((name, value));
+        }
+    }
+
+    let fen = tags.iter().find(|(name, _)| name == "FEN").map(|(_, value)| value);
+    let starting_position = match fen {
+        Some(fen) => Board::try_from_fen(fen).map_err(PgnError::InvalidFen)?,
+        None => {
+            let mut board = Board::new();
+            board.from_fen(None).map_err(PgnError::InvalidFen)?;
+            board
+        }
+    };
+
+    let mut game = Game::from_position(&starting_position);
+    for (name, value) in &tags {
+        game.set_tag(name, value);
+    }
+    if let Some(result) = game.get_tag("Result").and_then(|result| result.parse().ok()) {
+        game.result = result;
+    }
+
+    let mut lines = vec![Line {
+        moves: Vec::new(),
+        board: starting_position,
+        comment: None,
+    }];
+
+    for token in movetext {
+        let in_variation = lines.len() > 1;
+        let line = lines.last_mut().unwrap();
+        match token {
+            Token::Tag(name, _) => return Err(PgnError::MalformedPgn(format!(
+                "Tag {name} inside movetext"
+            ))),
+            Token::Comment(text) => {
+                let (comment, eval, clock) = split_comment(&text);
+                match line.moves.last_mut() {
+                    Some(game_move) => {
+                        game_move.eval = eval.or(game_move.eval);
+                        game_move.clock = clock.or(game_move.clock);
+                        if let Some(comment) = comment {
+                            game_move.add_comment(&comment);
+                        }
+                    }
+                    None => append_comment(&mut line.comment, comment),
+                }
+            }
+            Token::Nag(nag) => match line.moves.last_mut() {
+                Some(game_move) => game_move.add_nag(nag),
+                None => return Err(PgnError::MalformedPgn(format!(
+                    "NAG ${nag} before any move"
+                ))),
+            },
+            Token::Move(san) => {
+                let mv = if san == "--" || san == "Z0" {
+                    line.board.make_null_move();
+                    ChessMove::null()
+                } else {
+                    let mv = notation::move_from_san(&mut line.board, move_generator, &san)
+                        .map_err(PgnError::InvalidMove)?;
+                    line.board.make_move(mv);
+                    mv
+                };
+
+                let mut game_move = GameMove::new(mv);
+                // Only the mainline keeps its leading comment apart, as the game comment
+                if line.moves.is_empty() && in_variation {
+                    game_move.comment = line.comment.take();
+                }
+                line.moves.push(game_move);
+            }
+            Token::VariationStart => {
+                if line.moves.is_empty() {
+                    return Err(PgnError::MalformedPgn(
+                        String::from("Variation before any move")
+                    ));
+                }
+                let mut board = line.board.clone();
+                board.undo_move();
+                lines.push(Line {
+                    moves: Vec::new(),
+                    board,
+                    comment: None,
+                });
+            }
+            Token::VariationEnd => {
+                if lines.len() == 1 {
+                    return Err(PgnError::MalformedPgn(
+                        String::from("Unmatched ')'")
+                    ));
+                }
+                let variation = lines.pop().unwrap();
+                let parent = lines.last_mut().unwrap();
+                if !variation.moves.is_empty() {
+                    parent.moves.last_mut().unwrap().variations.push(variation.moves);
+                }
+            }
+            Token::Result(result) => {
+                if in_variation {
+                    return Err(PgnError::MalformedPgn(
+                        String::from("Game result inside a variation")
+                    ));
+                }
+                game.result = result;
+            }
+        }
+    }
+
+    if lines.len() > 1 {
+        return Err(PgnError::MalformedPgn(String::from("Unclosed variation")));
+    }
+
+    let mainline = lines.pop().unwrap();
+    game.comment = mainline.comment;
+    game.moves = mainline.moves;
+    game.set_result(game.result);
+    Ok(game)
+}
+
+
+fn append_comment(target: &mut Option<String>, comment: Option<String>) {
+    if let Some(comment) = comment {
+        match target {
+            Some(existing) => {
+                existing.push(' ');
+                existing.push_str(&comment);
+            }
+            None => *target = Some(comment),
+        }
+    }
+}
+
+// Pulls the [%eval] and [%clk] commands out of a comment, leaving the text
+fn split_comment(text: &str) -> (Option<String>, Option<f32>, Option<Duration>) {
+    let mut eval = None;
+    let mut clock = None;
+    let mut remaining = String::new();
+    let mut rest = text;
+
+    while let Some(start) = rest.find("[%") {
+        let Some(length) = rest[start..].find(']') else {
+            break;
+        };
+        let command = &rest[start + 2..start + length];
+        let mut parts = command.split_whitespace();
+        let parsed = match (parts.next(), parts.next()) {
+            (Some("eval"), Some(value)) => value.parse().ok()
+                .map(|value| eval = Some(value)),
+            (Some("clk"), Some(value)) => parse_clock(value)
+                .map(|value| clock = Some(value)),
+            _ => None,
+        };
+
+        remaining.push_str(&rest[..start]);
+        if parsed.is_none() {
+            remaining.push_str(&rest[start..=start + length]);
+        }
+        rest = &rest[start + length + 1..];
+    }
+    remaining.push_str(rest);
+
+    let remaining = remaining.split_whitespace().collect::<Vec<_>>().join(" ");
+    let comment = if remaining.is_empty() { None } else { Some(remaining) };
+    (comment, eval, clock)
+}
+
+fn parse_clock(clock: &str) -> Option<Duration> {
+    let mut seconds = 0.0;
+    for part in clock.split(':') {
+        seconds = seconds * 60.0 + part.parse::<f64>().ok()?;
+    }
+    Some(Duration::from_secs_f64(seconds))
+}
+
+fn tokenize(pgn: &str) -> Result<Vec<Token>, PgnError> {
+    let mut tokens = Vec::new();
+    let mut chars = pgn.chars().peekable();
+
+    while let Some(&c) = chars.peek() {
+        match c {
+            _ if c.is_whitespace() => {
+                chars.next();
+            }
+            '[' => {
+                chars.next();
+                let name: String = chars.by_ref()
+                    .take_while(|c| !c.is_whitespace())
+                    .collect();
+                while chars.next_if(|c| c.is_whitespace()).is_some() {}
+                if chars.next() != Some('"') {
+                    return Err(PgnError::MalformedPgn(format!(
+                        "Tag {name} has no quoted value"
+                    )));
+                }
+
+                let mut value = String::new();
+                loop {
+                    match chars.next() {
+                        Some('\\') => value.extend(chars.next()),
+                        Some('"') => break,
+                        Some(c) => value.push(c),
+                        None => return Err(PgnError::MalformedPgn(format!(
+                            "Unterminated value for tag {name}"
+                        ))),
+                    }
+                }
+                while chars.next_if(|c| *c != ']').is_some() {}
+                if chars.next() != Some(']') {
+                    return Err(PgnError::MalformedPgn(format!(
+                        "Unterminated tag {name}"
+                    )));
+                }
+                tokens.push(Token::Tag(name, value));
+            }
+            '{' => {
+                chars.next();
+                let mut comment = String::new();
+                loop {
+                    match chars.next() {
+                        Some('}') => break,
+                        Some(c) => comment.push(c),
+                        None => return Err(PgnError::MalformedPgn(
+                            String::from("Unterminated comment")
+                        )),
+                    }
+                }
+                tokens.push(Token::Comment(comment));
+            }
+            ';' => {
+                chars.next();
+                let comment: String = chars.by_ref()
+                    .take_while(|c| *c != '\n')
+                    .collect();
+                tokens.push(Token::Comment(comment));
+            }
+            '(' => {
+                chars.next();
+                tokens.push(Token::VariationStart);
+            }
+            ')' => {
+                chars.next();
+                tokens.push(Token::VariationEnd);
+            }
+            '$' => {
+                chars.next();
+                let mut digits = String::new();
+                while let Some(digit) = chars.next_if(|c| c.is_ascii_digit()) {
+                    digits.push(digit);
+                }
+                let nag = digits.parse().map_err(|_| PgnError::MalformedPgn(format!(
+                    "Invalid NAG ${digits}"
+                )))?;
+                tokens.push(Token::Nag(nag));
+            }
+            '!' | '?' => {
+                let mut suffix = String::new();
+                while let Some(c) = chars.next_if(|c| *c == '!' || *c == '?') {
+                    suffix.push(c);
+                }
+                tokens.push(Token::Nag(suffix_nag(&suffix)?));
+            }
+            _ => {
+                let mut symbol = String::new();
+                while let Some(c) = chars.next_if(|c| c.is_alphanumeric()
+                    || "+#=:-/._*".contains(*c)) {
+                    symbol.push(c);
+                }
+                if symbol.is_empty() {
+                    return Err(PgnError::MalformedPgn(format!(
+                        "Unexpected character '{c}'"
+                    )));
+                }
+                tokens.extend(symbol_token(&symbol));
+            }
+        }
+    }
+
+    Ok(tokens)
+}
+
+// Move numbers ("12.", "12...") carry no information and are dropped, also
+// when glued to the move as in "1.e4"
+fn symbol_token(symbol: &str) -> Option<Token> {
+    if let Ok(result) = PgnResult::from_str(symbol) {
+        return Some(Token::Result(result));
+    }
+    if symbol == "e.p." {
+        return None;
+    }
+
+    let san = symbol.trim_start_matches(|c: char| c.is_ascii_digit());
+    let san = if san.len() < symbol.len() && san.starts_with('.') {
+        san.trim_start_matches('.')
+    } else if san.is_empty() {
+        san
+    } else {
+        symbol
+    };
+
+    if san.is_empty() {
+        None
+    } else {
+        Some(Token::Move(san.to_string()))
+    }
+}
+
+fn suffix_nag(suffix: &str) -> Result<u8, PgnError> {
+    nag_from_symbol(suffix)
+        .ok_or_else(|| PgnError::MalformedPgn(format!("Invalid move suffix {suffix}")))
+}
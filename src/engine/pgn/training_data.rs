@@ -0,0 +1,151 @@
+use std::io::{self, BufRead, Write};
+
+use crate::engine::{evaluator::evaluator::Evaluator,
+    move_generator::move_generator::MoveGenerator, searcher::score::Score};
+use super::{game::{Game, PgnResult}, reader::{PgnError, PgnReader}};
+
+
+// One labelled position. Result and eval are both from White's point of view
+#[derive(Clone, Debug)]
+pub struct TrainingSample {
+    pub fen: String,
+    // 1.0 for a White win, 0.5 for a draw, 0.0 for a Black win
+    pub result: f32,
+    pub eval: Option<f32>,
+    // Half-moves played from the game's starting position
+    pub ply: usize,
+}
+
+impl TrainingSample {
+    pub fn to_csv(&self) -> String {
+        match self.eval {
+            Some(eval) => format!("{},{},{}", self.fen, self.result, eval),
+            None => format!("{},{},", self.fen, self.result),
+        }
+    }
+}
+
+
+#[derive(Clone, Copy, Default, Debug)]
+pub struct ExtractionStats {
+    pub games_read: usize,
+    pub games_skipped: usize,
+    pub samples_written: usize,
+}
+
+
+// Walks the mainline of each game and keeps the positions that pass the
+// filters. Without an evaluator the [%eval] annotations are used as evals
+pub struct TrainingDataExtractor<'a> {
+    skip_opening_plies: usize,
+    skip_in_check: bool,
+    skip_unfinished_games: bool,
+    evaluator: Option<&'a mut dyn Evaluator>,
+}
+
+impl<'a> TrainingDataExtractor<'a> {
+    pub fn new() -> Self {
+        Self {
+            skip_opening_plies: 0,
+            skip_in_check: true,
+            skip_unfinished_games: true,
+            evaluator: None,
+        }
+    }
+
+    pub fn skip_opening_plies(mut self, plies: usize) -> Self {
+        self.skip_opening_plies = plies;
+        self
+    }
+
+    pub fn skip_in_check(mut self, enabled: bool) -> Self {
+        self.skip_in_check = enabled;
+        self
+    }
+
+    // Games without a result ("*") have no label to train on
+    pub fn skip_unfinished_games(mut self, enabled: bool) -> Self {
+        self.skip_unfinished_games = enabled;
+        self
+    }
+
+    pub fn evaluator(mut self, evaluator: &'a mut dyn Evaluator) -> Self {
+        self.evaluator = Some(evaluator);
+        self
+    }
+
+    pub fn extract_game(&mut self, game: &Game) -> Vec<TrainingSample> {
+        let result = match game.result {
+            PgnResult::WhiteWins => 1.0,
+            PgnResult::BlackWins => 0.0,
+            PgnResult::Draw => 0.5,
+            PgnResult::Unknown if self.skip_unfinished_games => return Vec::new(),
+            PgnResult::Unknown => 0.5,
+        };
+
+        let mut samples = Vec::new();
+        let mut board = game.starting_position.clone();
+
+        for (index, game_move) in game.moves.iter().enumerate() {
+            if game_move.mv.is_null() {
+                board.make_null_move();
+            } else {
+                board.make_move(game_move.mv);
+            }
+
+            let ply = index + 1;
+            if ply <= self.skip_opening_plies || (self.skip_in_check && board.in_check()) {
+                continue;
+            }
+
+            // Evaluators score from the side to move's point of view.
+            // Positions the evaluator fails on are left without an eval
+            let eval = match self.evaluator.as_mut() {
+                Some(evaluator) => evaluator.evaluate_board(&board).ok()
+                    .map(|value| Score::new(value).to_white(board.get_active_side())),
+                None => game_move.eval,
+            };
+
+            samples.push(TrainingSample {
+                fen: board.to_fen(),
+                result,
+                eval,
+                ply,
+            });
+        }
+
+        samples
+    }
+
+    // Writes one "fen,result,eval" line per sample. Games that fail to parse
+    // are counted and skipped so one bad game does not stop a large collection
+    pub fn extract_to_csv<R: BufRead, W: Write>(&mut self, input: R,
+        move_generator: &MoveGenerator, output: &mut W) -> io::Result<ExtractionStats> {
+        let mut stats = ExtractionStats::default();
+
+        for game in PgnReader::new(input, move_generator) {
+            stats.games_read += 1;
+            let game = match game {
+                Ok(game) => game,
+                Err(PgnError::ReadError(error)) => return Err(error),
+                Err(_) => {
+                    stats.games_skipped += 1;
+                    continue;
+                }
+            };
+
+            for sample in self.extract_game(&game) {
+                writeln!(output, "{}", sample.to_csv())?;
+                stats.samples_written += 1;
+            }
+        }
+
+        Ok(stats)
+    }
+}
+
+impl Default for TrainingDataExtractor<'_> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
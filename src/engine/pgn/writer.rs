@@ -0,0 +1,220 @@
+use std::{io, time::Duration};
+
+use crate::engine::{board::board::Board, definitions::{Side, FEN_STARTING_POSITION},
+    move_generator::{move_generator::MoveGenerator, notation}};
+use super::{game::{Game, GameMove}, nag::{is_move_assessment, nag_symbol}};
+
+
+// Export format keeps movetext lines under 80 characters
+const DEFAULT_LINE_LENGTH: usize = 79;
+
+
+#[derive(Clone, Copy)]
+pub struct PgnWriter {
+    line_length: usize,
+    comments: bool,
+    evals: bool,
+    clocks: bool,
+    nag_symbols: bool,
+}
+
+impl PgnWriter {
+    pub fn new() -> Self {
+        Self {
+            line_length: DEFAULT_LINE_LENGTH,
+            comments: true,
+            evals: true,
+            clocks: true,
+            nag_symbols: false,
+        }
+    }
+
+    pub fn line_length(mut self, line_length: usize) -> Self {
+        self.line_length = line_length;
+        self
+    }
+
+    pub fn comments(mut self, enabled: bool) -> Self {
+        self.comments = enabled;
+        self
+    }
+
+    pub fn evals(mut self, enabled: bool) -> Self {
+        self.evals = enabled;
+        self
+    }
+
+    pub fn clocks(mut self, enabled: bool) -> Self {
+        self.clocks = enabled;
+        self
+    }
+
+    // Writes move assessments as "!?" suffixes instead of $5; export
+    // format requires the numeric form, so this is off by default
+    pub fn nag_symbols(mut self, enabled: bool) -> Self {
+        self.nag_symbols = enabled;
+        self
+    }
+
+    pub fn write(&self, game: &Game, move_generator: &MoveGenerator) -> String {
+        let mut pgn = self.tag_section(game);
+        pgn.push('\n');
+        pgn.push_str(&self.movetext(game, move_generator));
+        pgn.push('\n');
+        pgn
+    }
+
+    // Games written one after another are separated by a blank line
+    pub fn write_to<W: io::Write>(&self, writer: &mut W, game: &Game,
+        move_generator: &MoveGenerator) -> io::Result<()> {
+        writeln!(writer, "{}", self.write(game, move_generator))
+    }
+
+    fn tag_section(&self, game: &Game) -> String {
+        let mut tags = game.tags.clone();
+        match tags.iter_mut().find(|(name, _)| name == "Result") {
+            Some((_, value)) => *value = game.result.to_string(),
+            None => tags.push(("Result".to_string(), game.result.to_string())),
+        }
+
+        let fen = game.starting_position.to_fen();
+        if fen != FEN_STARTING_POSITION {
+            for (name, value) in [("SetUp", "1".to_string()), ("FEN", fen)] {
+                if !tags.iter().any(|(tag, _)| tag == name) {
+                    tags.push((name.to_string(), value));
+                }
+            }
+        }
+
+        tags.iter()
+            .map(|(name, value)| format!("[{name} \"{}\"]\n", escape(value)))
+            .collect()
+    }
+
+    fn movetext(&self, game: &Game, move_generator: &MoveGenerator) -> String {
+        let mut tokens = Vec::new();
+        if let Some(comment) = game.comment.as_ref().filter(|_| self.comments) {
+            tokens.extend(comment_tokens(&format!("{{{}}}", comment.replace('}', ")"))));
+        }
+        let mut board = game.starting_position.clone();
+        self.line_tokens(&game.moves, &mut board, move_generator, &mut tokens);
+        tokens.push(game.result.to_string());
+
+        let mut movetext = String::new();
+        let mut line_length = 0;
+        let mut after_paren = false;
+        for token in tokens {
+            // Parentheses hug the moves of the variation they enclose
+            let glued = after_paren || token == ")";
+            after_paren = token == "(";
+            if !glued && line_length > 0 {
+                if line_length + 1 + token.len() > self.line_length {
+                    movetext.push('\n');
+                    line_length = 0;
+                } else {
+                    movetext.push(' ');
+                    line_length += 1;
+                }
+            }
+            line_length += token.len();
+            movetext.push_str(&token);
+        }
+        movetext
+    }
+
+    fn line_tokens(&self, moves: &[GameMove], board: &mut Board,
+        move_generator: &MoveGenerator, tokens: &mut Vec<String>) {
+        let mut needs_number = true;
+
+        for game_move in moves {
+            let state = board.game_state;
+            if state.active_side == Side::White {
+                tokens.push(format!("{}.", state.full_move_number));
+            } else if needs_number {
+                tokens.push(format!("{}...", state.full_move_number));
+            }
+
+            let position_before = if game_move.variations.is_empty() {
+                None
+            } else {
+                Some(board.clone())
+            };
+            let mut san = if game_move.mv.is_null() {
+                board.make_null_move();
+                "--".to_string()
+            } else {
+                let san = notation::san(board, move_generator, game_move.mv);
+                board.make_move(game_move.mv);
+                san
+            };
+
+            let assessment = game_move.nags.iter()
+                .find(|nag| self.nag_symbols && is_move_assessment(**nag))
+                .and_then(|nag| nag_symbol(*nag));
+            if let Some(symbol) = assessment {
+                san.push_str(symbol);
+            }
+            tokens.push(san);
+            tokens.extend(game_move.nags.iter()
+                .filter(|nag| !self.nag_symbols || !is_move_assessment(**nag))
+                .map(|nag| format!("${nag}")));
+
+            // Black's move needs its number repeated after a comment or variation
+            needs_number = false;
+            if let Some(comment) = self.comment(game_move) {
+                tokens.extend(comment_tokens(&comment));
+                needs_number = true;
+            }
+
+            for variation in &game_move.variations {
+                let mut variation_board = position_before.clone().unwrap();
+                tokens.push("(".to_string());
+                self.line_tokens(variation, &mut variation_board, move_generator, tokens);
+                tokens.push(")".to_string());
+                needs_number = true;
+            }
+        }
+    }
+
+    // Evals and clocks use the [%eval] and [%clk] commands most GUIs read
+    fn comment(&self, game_move: &GameMove) -> Option<String> {
+        let mut parts = Vec::new();
+        if let Some(eval) = game_move.eval.filter(|_| self.evals) {
+            parts.push(format!("[%eval {eval:.2}]"));
+        }
+        if let Some(clock) = game_move.clock.filter(|_| self.clocks) {
+            parts.push(format!("[%clk {}]", format_clock(clock)));
+        }
+        if let Some(comment) = game_move.comment.as_ref().filter(|_| self.comments) {
+            // A closing brace would end the comment early
+            parts.push(comment.replace('}', ")"));
+        }
+
+        if parts.is_empty() {
+            None
+        } else {
+            Some(format!("{{{}}}", parts.join(" ")))
+        }
+    }
+}
+
+impl Default for PgnWriter {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+
+// Comments are split on whitespace so long ones can be wrapped
+fn comment_tokens(comment: &str) -> Vec<String> {
+    comment.split_whitespace().map(String::from).collect()
+}
+
+fn escape(value: &str) -> String {
+    value.replace('\\', "\\\\").replace('"', "\\\"")
+}
+
+fn format_clock(clock: Duration) -> String {
+    let seconds = clock.as_secs();
+    format!("{}:{:02}:{:02}", seconds / 3600, seconds / 60 % 60, seconds % 60)
+}
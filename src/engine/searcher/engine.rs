@@ -0,0 +1,198 @@
+use std::{sync::{mpsc, Arc, Mutex}, thread::{self, JoinHandle}, time::{Duration, Instant}};
+
+use crate::engine::{board::board::Board,
+    evaluator::evaluator::Evaluator,
+    move_generator::chess_move::ChessMove,
+    rules::variant::Variant,
+    searcher::thread_pool::{SearchProgress, SearchThreadPool, ThreadResult}};
+
+
+// Deep enough that a search without a depth limit only ends when it is
+// stopped or its time is up
+const UNLIMITED_DEPTH: u8 = 64;
+
+
+#[derive(Clone, Copy, Debug)]
+pub struct SearchLimits {
+    max_depth: u8,
+    move_time: Option<Duration>,
+}
+
+impl SearchLimits {
+    // No limits: the search runs until stopped
+    pub fn new() -> Self {
+        SearchLimits {
+            max_depth: UNLIMITED_DEPTH,
+            move_time: None,
+        }
+    }
+
+    pub fn depth(mut self, max_depth: u8) -> Self {
+        self.max_depth = max_depth.max(1);
+        self
+    }
+
+    pub fn move_time(mut self, move_time: Duration) -> Self {
+        self.move_time = Some(move_time);
+        self
+    }
+
+    pub fn get_depth(&self) -> u8 {
+        self.max_depth
+    }
+
+    pub fn get_move_time(&self) -> Option<Duration> {
+        self.move_time
+    }
+}
+
+impl Default for SearchLimits {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+
+// The entry point for GUIs and bots. The search threads are created once,
+// with the engine, and each start_search runs on them in the background.
+// Only one search runs at a time: starting a new one stops the previous
+// one, whose handle then returns what it had found
+pub struct Engine {
+    pool: Arc<Mutex<SearchThreadPool>>,
+    // The copy reload_model loads models into, and the search threads'
+    // copies are made from
+    evaluator: Box<dyn Evaluator>,
+    current: Option<SearchProgress>,
+    deterministic: bool,
+    variant: Variant,
+}
+
+impl Engine {
+    // Fails when the evaluator cannot be copied into other threads
+    pub fn new(evaluator: &dyn Evaluator, threads: usize, hash_bits: usize) -> Result<Self, String> {
+        let pool = SearchThreadPool::new(evaluator, threads, hash_bits)?;
+        let evaluator = evaluator.clone_for_thread()
+            .ok_or_else(|| "The evaluator cannot be shared between search threads".to_string())?;
+        Ok(Engine {
+            pool: Arc::new(Mutex::new(pool)),
+            evaluator,
+            current: None,
+            deterministic: false,
+            variant: Variant::Standard,
+        })
+    }
+
+    // Applies from the next start_search on, see SearchThreadPool
+    pub fn set_deterministic(&mut self, deterministic: bool) {
+        self.deterministic = deterministic;
+    }
+
+    pub fn is_deterministic(&self) -> bool {
+        self.deterministic
+    }
+
+    // Applies from the next start_search on, e.g. after a UCI_Variant
+    // option, see Variant::from_uci_option
+    pub fn set_variant(&mut self, variant: Variant) {
+        self.variant = variant;
+    }
+
+    pub fn get_variant(&self) -> Variant {
+        self.variant
+    }
+
+    // Stops the search in progress, loads the model at path once and gives
+    // every search thread a copy of it, for the next start_search. On
+    // failure the threads keep the model they had
+    pub fn reload_model(&mut self, path: &str) -> Result<(), String> {
+        if let Some(current) = self.current.take() {
+            current.stop();
+        }
+        // Waits for the stopped search to release the threads
+        let mut pool = self.pool.lock().unwrap_or_else(|e| e.into_inner());
+        self.evaluator.reload_model(path)?;
+        pool.set_evaluator(self.evaluator.as_ref())
+    }
+
+    // Returns as soon as the search has started. The handle can be sent to,
+    // or shared with, other threads
+    pub fn start_search(&mut self, board: &Board, limits: SearchLimits) -> SearchHandle {
+        if let Some(previous) = self.current.take() {
+            previous.stop();
+        }
+
+        let pool = Arc::clone(&self.pool);
+        let board = board.clone();
+        let deterministic = self.deterministic;
+        let variant = self.variant;
+        let (progress_sender, progress_receiver) = mpsc::channel();
+
+        // The pool stays locked until the search is over, which is what
+        // keeps searches from overlapping
+        let search = thread::spawn(move || {
+            let mut pool = pool.lock().unwrap_or_else(|e| e.into_inner());
+            pool.set_deterministic(deterministic);
+            pool.set_variant(variant);
+            let deadline = limits.move_time.map(|move_time| Instant::now() + move_time);
+            let _ = progress_sender.send(pool.start(&board, limits.max_depth));
+            match deadline {
+                Some(deadline) => pool.wait_until(deadline),
+                None => pool.wait(),
+            }
+        });
+
+        // Only fails when the search thread panicked before starting, in
+        // which case there is nothing to stop or read
+        let progress = progress_receiver.recv().unwrap_or_default();
+        self.current = Some(progress.clone());
+        SearchHandle {
+            progress,
+            search: Some(search),
+        }
+    }
+}
+
+
+// A running search. Dropping the handle stops the search and waits for it
+pub struct SearchHandle {
+    progress: SearchProgress,
+    search: Option<JoinHandle<Vec<ThreadResult>>>,
+}
+
+impl SearchHandle {
+    // Returns at once; await_result gives the outcome
+    pub fn stop(&self) {
+        self.progress.stop();
+    }
+
+    // True once every search thread is done
+    pub fn is_finished(&self) -> bool {
+        self.search.as_ref().is_none_or(|search| search.is_finished())
+    }
+
+    // The move of the deepest iteration finished so far
+    pub fn get_best_move(&self) -> Option<ChessMove> {
+        self.progress.get_best().and_then(|result| result.best_move)
+    }
+
+    pub fn get_current_result(&self) -> Option<ThreadResult> {
+        self.progress.get_best()
+    }
+
+    // Blocks until the search ends on its own, by its limits or by a stop,
+    // and returns the deepest result. None when no iteration finished or a
+    // search thread panicked
+    pub fn await_result(mut self) -> Option<ThreadResult> {
+        let results = self.search.take()?.join().ok()?;
+        results.into_iter().next().filter(|result| result.depth > 0)
+    }
+}
+
+impl Drop for SearchHandle {
+    fn drop(&mut self) {
+        if let Some(search) = self.search.take() {
+            self.stop();
+            let _ = search.join();
+        }
+    }
+}
@@ -1,3 +1,9 @@
 pub mod searcher;
 
-pub mod transposition_table;
\ No newline at end of file
+pub mod score;
+
+pub mod transposition_table;
+
+pub mod thread_pool;
+
+pub mod engine;
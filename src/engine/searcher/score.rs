@@ -0,0 +1,159 @@
+use std::{fmt::{self, Display}, ops::Neg};
+
+use crate::engine::{board::board::Board,
+    definitions::{Side, MAX_POSITION_SCORE, MIN_POSITION_SCORE},
+    evaluator::evaluator::{EvalError, Evaluator},
+    move_generator::chess_move::ChessMove};
+
+
+// Longest mate the mate scores can express
+pub const MAX_MATE_PLIES: i32 = 1000;
+
+
+// A position's score from the point of view of the side to move: positive
+// is good for whoever is about to play. Negating it gives the score for
+// the other side, which is all the negamax recursion needs. Mate scores
+// follow the same rule, so being mated is always negative. A mate with a
+// known distance, e.g. from a tablebase, sits that many plies short of
+// Score::MATE
+#[derive(Clone, Copy, PartialEq, PartialOrd, Default, Debug)]
+pub struct Score(f32);
+
+impl Score {
+    pub const ZERO: Score = Score(0.0);
+    pub const MATE: Score = Score(MAX_POSITION_SCORE);
+    pub const MATED: Score = Score(MIN_POSITION_SCORE);
+
+    pub fn new(value: f32) -> Self {
+        Score(value)
+    }
+
+    // The side to move mates in that many plies
+    pub fn mate_in(plies: i32) -> Self {
+        Score(MAX_POSITION_SCORE - plies.clamp(0, MAX_MATE_PLIES) as f32)
+    }
+
+    // The side to move is mated in that many plies
+    pub fn mated_in(plies: i32) -> Self {
+        -Self::mate_in(plies)
+    }
+
+    // Converts a score where positive favours White
+    pub fn from_white(value: f32, side_to_move: Side) -> Self {
+        match side_to_move {
+            Side::White => Score(value),
+            Side::Black => Score(-value),
+        }
+    }
+
+    pub fn get_value(&self) -> f32 {
+        self.0
+    }
+
+    // The smallest score above this one; a window from a score to its
+    // next_up is a null window
+    pub fn next_up(self) -> Self {
+        Score(self.0.next_up())
+    }
+
+    // The same score with positive favouring White, e.g. for output to
+    // GUIs and training files
+    pub fn to_white(self, side_to_move: Side) -> f32 {
+        match side_to_move {
+            Side::White => self.0,
+            Side::Black => -self.0,
+        }
+    }
+
+    // The score seen from the position one ply up: negated, with a mate
+    // one ply further away
+    pub fn to_parent(self) -> Self {
+        match self.get_mate_plies() {
+            Some(plies) if self.0 > 0.0 => Self::mated_in(plies + 1),
+            Some(plies) => Self::mate_in(1 - plies),
+            None => -self,
+        }
+    }
+
+    pub fn is_mate(&self) -> bool {
+        self.0.abs() >= MAX_POSITION_SCORE - MAX_MATE_PLIES as f32
+    }
+
+    // Plies to mate, negative when the side to move is the one mated
+    pub fn get_mate_plies(&self) -> Option<i32> {
+        if !self.is_mate() {
+            return None;
+        }
+        let plies = (MAX_POSITION_SCORE - self.0.abs()) as i32;
+        Some(if self.0 > 0.0 { plies } else { -plies })
+    }
+}
+
+impl Neg for Score {
+    type Output = Score;
+
+    fn neg(self) -> Self::Output {
+        Score(-self.0)
+    }
+}
+
+impl Display for Score {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self.get_mate_plies() {
+            Some(plies) if self.0 > 0.0 => write!(f, "mate in {plies}"),
+            Some(plies) => write!(f, "mated in {}", -plies),
+            None => write!(f, "{}", self.0),
+        }
+    }
+}
+
+
+// Wraps an evaluator whose scores favour White, such as a model trained on
+// white-relative targets, so that it scores for the side to move like
+// every other evaluator
+pub struct WhiteRelativeEvaluator {
+    inner: Box<dyn Evaluator>,
+}
+
+impl WhiteRelativeEvaluator {
+    pub fn new(inner: Box<dyn Evaluator>) -> Self {
+        WhiteRelativeEvaluator { inner }
+    }
+}
+
+impl Evaluator for WhiteRelativeEvaluator {
+    fn evaluate_board(&mut self, board: &Board) -> Result<f32, EvalError> {
+        let value = self.inner.evaluate_board(board)?;
+        Ok(Score::from_white(value, board.get_active_side()).get_value())
+    }
+
+    fn evaluate_batch(&mut self, boards: &[Board]) -> Result<Vec<f32>, EvalError> {
+        Ok(boards.iter()
+            .zip(self.inner.evaluate_batch(boards)?)
+            .map(|(board, value)| Score::from_white(value, board.get_active_side()).get_value())
+            .collect())
+    }
+
+    fn evaluate_after_move(&mut self, board: &Board, mv: ChessMove) -> Result<f32, EvalError> {
+        let value = self.inner.evaluate_after_move(board, mv)?;
+        Ok(Score::from_white(value, board.get_opponent()).get_value())
+    }
+
+    fn on_make_move(&mut self, board: &Board, mv: &ChessMove) {
+        self.inner.on_make_move(board, mv);
+    }
+
+    fn on_undo_move(&mut self) {
+        self.inner.on_undo_move();
+    }
+
+    fn clone_for_thread(&self) -> Option<Box<dyn Evaluator>> {
+        Some(Box::new(WhiteRelativeEvaluator {
+            inner: self.inner.clone_for_thread()?,
+        }))
+    }
+
+    fn reload_model(&mut self, path: &str) -> Result<(), String> {
+        self.inner.reload_model(path)
+    }
+}
@@ -1,31 +1,290 @@
+use std::{collections::HashMap, sync::atomic::{AtomicBool, Ordering}};
 
-use crate::engine::{board::board::Board,
-    definitions::{Side, MAX_POSITION_SCORE, MIN_POSITION_SCORE},
-    evaluator::evaluator::Evaluator,
+use crate::engine::{board::{board::Board, game_result::DrawRules},
+    definitions::{Side, ZobristKey},
+    evaluator::{classical_evaluator::ClassicalEvaluator, evaluator::{EvalError, Evaluator, PolicyEvaluator},
+        piece_square_tables::taper},
     move_generator::{chess_move::ChessMove, move_generator::MoveGenerator},
-    searcher::transposition_table::{Bound, TranspositionTable, TranspositionTableEntry}};
+    rules::rules::Rules,
+    searcher::{score::Score,
+        transposition_table::{Bound, TranspositionTable, TranspositionTableEntry}},
+    tablebase::tablebase::Tablebase};
+
+
+// Node types of the search, given to search_node as a type parameter so
+// the differences between them are settled at compile time. PV nodes are
+// searched with an open window and their score can become the principal
+// variation's; the other nodes only need to show that their score falls
+// on one side of a null window
+trait NodeType {
+    const ROOT: bool;
+    const PV: bool;
+}
+
+struct Root;
+struct Pv;
+struct NonPv;
+
+impl NodeType for Root {
+    const ROOT: bool = true;
+    const PV: bool = true;
+}
+
+impl NodeType for Pv {
+    const ROOT: bool = false;
+    const PV: bool = true;
+}
+
+impl NodeType for NonPv {
+    const ROOT: bool = false;
+    const PV: bool = false;
+}
 
 
 pub struct SearchResult {
     pub best_move: Option<ChessMove>,
-    pub score: f32
+    pub score: Score
+}
+
+// A fast evaluator, e.g. the classical one, for the leaves that decide
+// little. Leaves of PV nodes, whose scores can become the principal
+// variation's, and leaves fewer than switch_depth plies from the root keep
+// the main evaluator; the non-PV leaves further down, the bulk of a deep
+// search, get the fast one. Its score is multiplied by scale to match the
+// main evaluator's units
+pub struct HybridEvaluation<'a> {
+    pub evaluator: &'a mut dyn Evaluator,
+    pub scale: f32,
+    pub switch_depth: u8,
+}
+
+// Skips evaluation at leaves whose material and piece-square estimate,
+// read from the board's incremental sums, is so far outside the window that
+// the evaluator could not bring it back. Margins are in centipawns and
+// tapered by phase, as material says less in the middlegame; scale converts
+// the estimate to the evaluator's units
+#[derive(Clone, Copy, Debug)]
+pub struct LazyEvaluation {
+    scale: f32,
+    mg_margin: i32,
+    eg_margin: i32,
+}
+
+impl LazyEvaluation {
+    pub fn new() -> Self {
+        LazyEvaluation {
+            scale: 1.0,
+            mg_margin: 500,
+            eg_margin: 300,
+        }
+    }
+
+    pub fn scale(mut self, scale: f32) -> Self {
+        self.scale = scale;
+        self
+    }
+
+    pub fn margins(mut self, mg_margin: i32, eg_margin: i32) -> Self {
+        self.mg_margin = mg_margin;
+        self.eg_margin = eg_margin;
+        self
+    }
+
+    // The estimate when it is outside the window by more than the margin
+    pub fn probe(&self, board: &Board, alpha: Score, beta: Score) -> Option<Score> {
+        let phase = board.game_phase();
+        let (mg, eg) = board.pst_scores();
+        let estimate = match board.get_active_side() {
+            Side::White => taper(mg, eg, phase),
+            Side::Black => -taper(mg, eg, phase),
+        } as f32 * self.scale;
+        let margin = taper(self.mg_margin, self.eg_margin, phase) as f32 * self.scale;
+
+        (estimate + margin <= alpha.get_value() || estimate - margin >= beta.get_value())
+            .then_some(Score::new(estimate))
+    }
+}
+
+impl Default for LazyEvaluation {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+// Orders the moves of nodes at least min_depth from the horizon by the
+// policy's priors, most likely first. Shallower nodes are too many for a
+// model call each to pay off
+pub struct PolicyOrdering<'a> {
+    pub evaluator: &'a mut dyn PolicyEvaluator,
+    pub min_depth: u8,
 }
 
 pub struct Searcher<'a> {
     pub evaluator: &'a mut dyn Evaluator,
     pub movegen: &'a MoveGenerator,
-    pub transposition_table: &'a mut TranspositionTable
+    pub transposition_table: &'a TranspositionTable,
+    pub draw_rules: DrawRules,
+    // Leaves evaluated per evaluate_batch call one ply above the horizon;
+    // 0 or 1 evaluates every leaf on its own
+    pub leaf_batch_size: usize,
+    pub hybrid: Option<HybridEvaluation<'a>>,
+    pub lazy: Option<LazyEvaluation>,
+    pub policy: Option<PolicyOrdering<'a>>,
+    lazy_skips: usize,
+    // Positions it covers are scored from it instead of being searched
+    pub tablebase: Option<&'a dyn Tablebase>,
+    // A variant's rules; standard chess when unset
+    pub rules: Option<&'a dyn Rules>,
+    // Once set, the search unwinds without storing anything and its result
+    // is meaningless
+    stop: Option<&'a AtomicBool>,
+    // Every search starts from an empty table, see set_deterministic
+    deterministic: bool,
+    nodes: u64,
+    // Scores the leaves the evaluator failed on, created on the first
+    // failure
+    fallback: Option<ClassicalEvaluator>,
+    eval_errors: usize,
+    last_eval_error: Option<EvalError>,
+    // Distance from the root of the node being searched, and a move buffer
+    // per ply reused from node to node
+    ply: usize,
+    move_buffers: Vec<Vec<ChessMove>>,
 }
 
 impl<'a> Searcher<'a> {
     pub fn new(
         evaluator: &'a mut dyn Evaluator,
         movegen: &'a MoveGenerator,
-        transposition_table: &'a mut TranspositionTable) -> Searcher<'a> {
+        transposition_table: &'a TranspositionTable) -> Searcher<'a> {
         Searcher {
             evaluator,
             movegen,
             transposition_table,
+            draw_rules: DrawRules::Claimable,
+            leaf_batch_size: 0,
+            hybrid: None,
+            lazy: None,
+            policy: None,
+            lazy_skips: 0,
+            tablebase: None,
+            rules: None,
+            stop: None,
+            deterministic: false,
+            nodes: 0,
+            fallback: None,
+            eval_errors: 0,
+            last_eval_error: None,
+            ply: 0,
+            move_buffers: Vec::new(),
+        }
+    }
+
+    pub fn set_draw_rules(&mut self, draw_rules: DrawRules) {
+        self.draw_rules = draw_rules;
+    }
+
+    // Worth enabling for model-backed evaluators such as the CNN, where one
+    // call over many positions costs far less than one call per position.
+    // Pruning then only happens between batches at the last ply
+    pub fn set_leaf_batch_size(&mut self, leaf_batch_size: usize) {
+        self.leaf_batch_size = leaf_batch_size;
+    }
+
+    // A deeper switch_depth calls the main evaluator more often; 0 leaves
+    // it only the PV leaves
+    pub fn set_hybrid_evaluator(&mut self, evaluator: &'a mut dyn Evaluator,
+        scale: f32, switch_depth: u8) {
+        self.hybrid = Some(HybridEvaluation { evaluator, scale, switch_depth });
+    }
+
+    pub fn clear_hybrid_evaluator(&mut self) {
+        self.hybrid = None;
+    }
+
+    // Worth enabling with model-backed evaluators, for which the estimate
+    // is free next to an inference call
+    pub fn set_lazy_evaluation(&mut self, lazy: LazyEvaluation) {
+        self.lazy = Some(lazy);
+    }
+
+    pub fn clear_lazy_evaluation(&mut self) {
+        self.lazy = None;
+    }
+
+    // Leaves scored from the lazy estimate without calling the evaluator
+    pub fn get_lazy_skips(&self) -> usize {
+        self.lazy_skips
+    }
+
+    pub fn set_policy_ordering(&mut self, evaluator: &'a mut dyn PolicyEvaluator, min_depth: u8) {
+        self.policy = Some(PolicyOrdering { evaluator, min_depth });
+    }
+
+    pub fn clear_policy_ordering(&mut self) {
+        self.policy = None;
+    }
+
+    pub fn set_tablebase(&mut self, tablebase: &'a dyn Tablebase) {
+        self.tablebase = Some(tablebase);
+    }
+
+    pub fn clear_tablebase(&mut self) {
+        self.tablebase = None;
+    }
+
+    // Positions the variant declares won score as mates, and moves it
+    // forbids are not searched
+    pub fn set_rules(&mut self, rules: &'a dyn Rules) {
+        self.rules = Some(rules);
+    }
+
+    pub fn clear_rules(&mut self) {
+        self.rules = None;
+    }
+
+    // Lets another thread abort the search, e.g. when a UCI stop arrives
+    pub fn set_stop_flag(&mut self, stop: &'a AtomicBool) {
+        self.stop = Some(stop);
+    }
+
+    pub fn clear_stop_flag(&mut self) {
+        self.stop = None;
+    }
+
+    pub fn is_stopped(&self) -> bool {
+        self.stop.is_some_and(|stop| stop.load(Ordering::Relaxed))
+    }
+
+    // Makes search depend on nothing but the position and depth, so that
+    // the same search always visits the same nodes and returns the same
+    // move, for debugging and comparing versions: each search clears the
+    // transposition table first instead of reusing what earlier searches
+    // stored. The rest of the search is deterministic already; zobrist
+    // keys come from a fixed seed and moves with equal ordering scores
+    // keep the order they were generated in. Model-backed evaluators are
+    // only as deterministic as their inference, and a stop flag ends the
+    // search whenever it is raised
+    pub fn set_deterministic(&mut self, deterministic: bool) {
+        self.deterministic = deterministic;
+    }
+
+    pub fn is_deterministic(&self) -> bool {
+        self.deterministic
+    }
+
+    // Nodes visited since the last call to search, or since the searcher
+    // was created
+    pub fn get_nodes(&self) -> u64 {
+        self.nodes
+    }
+
+    // Prepares for a new search: resets the node count and, in
+    // deterministic mode, clears the transposition table
+    pub fn new_search(&mut self) {
+        self.nodes = 0;
+        if self.deterministic {
+            self.transposition_table.clear();
         }
     }
 
@@ -33,70 +292,94 @@ impl<'a> Searcher<'a> {
         self.evaluator = evaluator;
     }
 
+    // Evaluator calls that failed and were scored by the fallback instead
+    pub fn get_eval_errors(&self) -> usize {
+        self.eval_errors
+    }
+
+    pub fn get_last_eval_error(&self) -> Option<&EvalError> {
+        self.last_eval_error.as_ref()
+    }
+
     pub fn search(&mut self, board: &Board, depth: u8) -> Option<ChessMove> {
+        self.new_search();
         let mut board_clone = board.clone();
         let result = self.search_move(&mut board_clone,
                                         depth,
-                                        MIN_POSITION_SCORE,
-                                        MAX_POSITION_SCORE);
+                                        Score::MATED,
+                                        Score::MATE);
         return result.best_move;
     }
 
+    // Searches board as the root, always trying its moves so that a move is
+    // found even when the position is in the table or drawn by the rules
     pub fn search_move(&mut self, board: &mut Board, depth: u8,
-        mut alpha: f32, beta: f32) -> SearchResult {
+        alpha: Score, beta: Score) -> SearchResult {
+        self.search_node::<Root>(board, depth, alpha, beta)
+    }
+
+    // Table cutoffs are taken at non-PV nodes only, apart from exact
+    // scores, so that bounds from other windows do not cut the principal
+    // variation short
+    fn search_node<N: NodeType>(&mut self, board: &mut Board, depth: u8,
+        alpha: Score, beta: Score) -> SearchResult {
+
+        if self.is_stopped() {
+            return SearchResult {
+                best_move: None,
+                score: Score::ZERO,
+            };
+        }
+        self.nodes += 1;
+
+        // Before the table, whose entries may come from a standard search
+        if let Some(winner) = self.rules.and_then(|rules| rules.variant_winner(board)) {
+            return SearchResult {
+                best_move: board.game_history.last().map(|m| m.mv),
+                score: if winner == board.get_active_side() { Score::MATE } else { Score::MATED },
+            };
+        }
 
-        let alpha_og = alpha;
         let zobrist = board.game_state.zobrist_key;
 
-        if let Some(entry) = self.transposition_table.retrieve(zobrist) {
-            if entry.depth >= depth {
-                match entry.flag {
-                    Bound::Exact => return SearchResult {
-                        best_move: entry.best_move,
-                        score: entry.score,
-                    },
-                    Bound::LowerBound if entry.score >= beta => return SearchResult {
-                        best_move: entry.best_move,
-                        score: entry.score,
-                    },
-                    Bound::UpperBound if entry.score <= alpha => return SearchResult {
-                        best_move: entry.best_move,
-                        score: entry.score,
-                    },
-                    _ => {}
-                }
+        let entry = match N::ROOT {
+            true => None,
+            false => self.transposition_table.retrieve(zobrist),
+        };
+        if let Some(entry) = entry && entry.depth >= depth {
+            match entry.flag {
+                Bound::Exact => return SearchResult {
+                    best_move: entry.best_move,
+                    score: entry.score,
+                },
+                Bound::LowerBound if !N::PV && entry.score >= beta => return SearchResult {
+                    best_move: entry.best_move,
+                    score: entry.score,
+                },
+                Bound::UpperBound if !N::PV && entry.score <= alpha => return SearchResult {
+                    best_move: entry.best_move,
+                    score: entry.score,
+                },
+                _ => {}
             }
         }
 
-        let last_played_move = match board.game_history.len() {
-            0 => None,
-            _ => Some(board.game_history
-                .get_ref(board.game_history.len() - 1))
-                .map(|m| m.mv.clone())
-        };
+        let last_played_move = board.game_history.last().map(|m| m.mv);
 
-        if board.draw_by_fifty_move_rule() ||
-            board.draw_by_threefold_repetition() ||
-            board.draw_by_insufficient_material() {
+        if !N::ROOT && board.draw_by_rules(self.draw_rules) {
             return SearchResult {
                 best_move: last_played_move,
-                score: 0.0,
+                score: Score::ZERO,
             };
         }
 
-        if board.game_history.len() > 0 {
+        if !board.game_history.is_empty() {
             let last_move = last_played_move.unwrap();
+            // The side to move has been mated
             if last_move.is_checkmate {
-                return match board.get_active_side() {
-                    // don't forget that the side switches after a move
-                    Side::White => SearchResult {
-                        best_move: last_played_move,
-                        score: MIN_POSITION_SCORE,
-                    },
-                    Side::Black => SearchResult {
-                        best_move: last_played_move,
-                        score: MAX_POSITION_SCORE,
-                    },
+                return SearchResult {
+                    best_move: last_played_move,
+                    score: Score::MATED,
                 };
             }
         }
@@ -104,37 +387,79 @@ impl<'a> Searcher<'a> {
         if depth == 0 {
             return SearchResult {
                 best_move: last_played_move,
-                score: self.evaluator.evaluate_board(board),
+                score: self.evaluate_leaf(board, alpha, beta, N::PV),
             };
         }
 
-        let moves = self.movegen.generate_moves(board);
+        let mut moves = self.take_move_buffer();
+        self.movegen.generate_moves_into(board, &mut moves);
+        if let Some(rules) = self.rules {
+            moves.retain(|mv| rules.is_legal_move(board, *mv));
+        }
+        self.order_by_policy(board, depth, &mut moves);
+        let result = self.search_moves::<N>(board, &moves, depth, alpha, beta);
+        self.move_buffers[self.ply] = moves;
+        result
+    }
+
+    // The rest of search_node once the node's moves are generated
+    fn search_moves<N: NodeType>(&mut self, board: &mut Board, moves: &[ChessMove], depth: u8,
+        mut alpha: Score, beta: Score) -> SearchResult {
+        let alpha_og = alpha;
+        let zobrist = board.game_state.zobrist_key;
+
+        // Checkmate was caught above, so no moves means stalemate
+        if moves.is_empty() {
+            let last_played_move = board.game_history.last().map(|m| m.mv);
+            return SearchResult {
+                best_move: last_played_move,
+                score: Score::ZERO,
+            };
+        }
+
+        if depth == 1 && self.leaf_batch_size > 1 {
+            if let Some(mate) = Self::mating_move(moves) {
+                return mate;
+            }
+            let best_result = self.search_leaf_batches(board, moves, alpha, beta, N::PV);
+            self.store_result(zobrist, depth, alpha_og, beta, &best_result);
+            return best_result;
+        }
 
         let mut best_result: SearchResult = SearchResult {
             best_move: moves.first().cloned(),
-            score: MIN_POSITION_SCORE,
+            score: Score::MATED,
         };
 
-        for mv in moves {
+        for (index, &mv) in moves.iter().enumerate() {
             if mv.is_checkmate {
-                return match board.get_active_side() {
-                    Side::White => SearchResult {
-                        best_move: Some(mv),
-                        score: MAX_POSITION_SCORE,
-                    },
-                    Side::Black => SearchResult {
-                        best_move: Some(mv),
-                        score: MIN_POSITION_SCORE,
-                    },
+                return SearchResult {
+                    best_move: Some(mv),
+                    score: Score::mate_in(1),
                 };
             }
             board.make_move(mv);
+            self.evaluator.on_make_move(board, &mv);
+            if let Some(hybrid) = self.hybrid.as_mut() {
+                hybrid.evaluator.on_make_move(board, &mv);
+            }
 
-            let mut result = self.search_move(board,
-                                            depth - 1,
-                                            -beta, -alpha);
-            result.score = -result.score;
+            self.ply += 1;
+            let mut result = match self.probe_tablebase(board) {
+                Some(score) => SearchResult { best_move: Some(mv), score },
+                None => self.search_child::<N>(board, depth - 1, alpha, beta, index == 0),
+            };
+            self.ply -= 1;
+            result.score = result.score.to_parent();
             board.undo_move();
+            self.evaluator.on_undo_move();
+            if let Some(hybrid) = self.hybrid.as_mut() {
+                hybrid.evaluator.on_undo_move();
+            }
+
+            if self.is_stopped() {
+                return best_result;
+            }
 
             if result.score > best_result.score {
                 best_result.score = result.score;
@@ -149,9 +474,118 @@ impl<'a> Searcher<'a> {
             }
         }
 
-        let flag = if best_result.score <= alpha_og {
+        self.store_result(zobrist, depth, alpha_og, beta, &best_result);
+        best_result
+    }
+
+    // A child of a node of type N, with the window seen from the parent.
+    // The first child of a PV node is a PV node. Its other children are
+    // first searched as non-PV nodes with a null window just above alpha,
+    // and only searched again with the full window when they beat alpha.
+    // Children of non-PV nodes are non-PV nodes in the same null window
+    fn search_child<N: NodeType>(&mut self, board: &mut Board, depth: u8,
+        alpha: Score, beta: Score, first: bool) -> SearchResult {
+        if !N::PV {
+            return self.search_node::<NonPv>(board, depth, -beta, -alpha);
+        }
+        if first {
+            return self.search_node::<Pv>(board, depth, -beta, -alpha);
+        }
+
+        let result = self.search_node::<NonPv>(board, depth, -alpha.next_up(), -alpha);
+        let score = result.score.to_parent();
+        if score > alpha && score < beta && !self.is_stopped() {
+            return self.search_node::<Pv>(board, depth, -beta, -alpha);
+        }
+        result
+    }
+
+    // The current ply's buffer, taken out while its node uses it and put
+    // back afterwards, so each ply allocates only until the buffer has
+    // grown to fit its positions
+    fn take_move_buffer(&mut self) -> Vec<ChessMove> {
+        if self.move_buffers.len() <= self.ply {
+            self.move_buffers.resize_with(self.ply + 1, Vec::new);
+        }
+        std::mem::take(&mut self.move_buffers[self.ply])
+    }
+
+    // Keeps the generator's order when the policy fails or is not used
+    fn order_by_policy(&mut self, board: &Board, depth: u8, moves: &mut [ChessMove]) {
+        let Some(policy) = self.policy.as_mut().filter(|policy| depth >= policy.min_depth) else {
+            return;
+        };
+        let Ok((_, priors)) = policy.evaluator.evaluate_with_policy(board) else {
+            return;
+        };
+        let priors: HashMap<ChessMove, f32> = priors.into_iter().collect();
+        moves.sort_by(|a, b| {
+            let prior = |mv: &ChessMove| priors.get(mv).copied().unwrap_or(0.0);
+            prior(b).total_cmp(&prior(a))
+        });
+    }
+
+    // Tablebases hold standard chess results, which a variant's wins change
+    fn probe_tablebase(&self, board: &Board) -> Option<Score> {
+        if self.rules.is_some_and(|rules| !rules.is_standard()) {
+            return None;
+        }
+        self.tablebase?.probe_score(board)
+    }
+
+    // Evaluators score for the side to move, the same convention as Score
+    fn evaluate_leaf(&mut self, board: &Board, alpha: Score, beta: Score, pv: bool) -> Score {
+        if let Some(score) = self.lazy_score(board, alpha, beta) {
+            return score;
+        }
+        if let Some(score) = self.hybrid_score(board, pv, self.ply) {
+            return score;
+        }
+        match self.evaluator.evaluate_board(board) {
+            Ok(score) => Score::new(score),
+            Err(error) => Score::new(self.fallback_scores(std::slice::from_ref(board), error)[0]),
+        }
+    }
+
+    // The fast evaluator's score for a leaf ply plies from the root, when
+    // the leaf is its to score. A failing fast evaluator leaves the leaf to
+    // the main one
+    fn hybrid_score(&mut self, board: &Board, pv: bool, ply: usize) -> Option<Score> {
+        let hybrid = self.hybrid.as_mut()
+            .filter(|hybrid| !pv && ply >= hybrid.switch_depth as usize)?;
+        let score = hybrid.evaluator.evaluate_board(board).ok()?;
+        Some(Score::new(hybrid.scale * score))
+    }
+
+    fn lazy_score(&mut self, board: &Board, alpha: Score, beta: Score) -> Option<Score> {
+        let score = self.lazy?.probe(board, alpha, beta)?;
+        self.lazy_skips += 1;
+        Some(score)
+    }
+
+    fn evaluate_leaves(&mut self, boards: &[Board]) -> Vec<f32> {
+        match self.evaluator.evaluate_batch(boards) {
+            Ok(scores) => scores,
+            Err(error) => self.fallback_scores(boards, error),
+        }
+    }
+
+    // Inference can fail mid-search, e.g. when the GPU runs out of memory,
+    // and a search should not die with it. The classical evaluator scores
+    // those leaves instead; its scale differs from a model's, so the result
+    // is rougher but the search carries on
+    fn fallback_scores(&mut self, boards: &[Board], error: EvalError) -> Vec<f32> {
+        self.eval_errors += 1;
+        self.last_eval_error = Some(error);
+        let fallback = self.fallback.get_or_insert_with(ClassicalEvaluator::new);
+        boards.iter().map(|board| fallback.evaluate(board) as f32).collect()
+    }
+
+    fn store_result(&mut self, zobrist: ZobristKey, depth: u8,
+        alpha: Score, beta: Score, result: &SearchResult) {
+        let flag = if result.score <= alpha {
             Bound::UpperBound
-        } else if best_result.score >= beta {
+        } else if result.score >= beta {
             Bound::LowerBound
         } else {
             Bound::Exact
@@ -162,11 +596,90 @@ impl<'a> Searcher<'a> {
             TranspositionTableEntry {
                 zobrist,
                 depth,
-                score: best_result.score,
+                score: result.score,
                 flag,
-                best_move: best_result.best_move,
+                best_move: result.best_move,
             },
         );
+    }
+
+    fn mating_move(moves: &[ChessMove]) -> Option<SearchResult> {
+        let mv = *moves.iter().find(|mv| mv.is_checkmate)?;
+        Some(SearchResult {
+            best_move: Some(mv),
+            score: Score::mate_in(1),
+        })
+    }
+
+    // Depth-1 node whose children are scored leaf_batch_size at a time.
+    // Children the variant declares won score as mates, as in search_node,
+    // children that are drawn by the rules score 0, children in the
+    // tablebase get its score and those the lazy estimate settles keep the
+    // estimate, all without being evaluated. The children of a non-PV node
+    // are left to the hybrid evaluator as in evaluate_leaf; a PV node's are
+    // all searched with its window, so they keep the main evaluator
+    fn search_leaf_batches(&mut self, board: &mut Board, moves: &[ChessMove],
+        mut alpha: Score, beta: Score, pv: bool) -> SearchResult {
+        let mut best_result = SearchResult {
+            best_move: moves.first().cloned(),
+            score: Score::MATED,
+        };
+
+        let mut pending_moves = Vec::with_capacity(self.leaf_batch_size);
+        let mut pending_boards = Vec::with_capacity(self.leaf_batch_size);
+
+        for (index, mv) in moves.iter().enumerate() {
+            // Children are counted here, as they are never searched
+            self.nodes += 1;
+            board.make_move(*mv);
+            let won = self.rules.and_then(|rules| rules.variant_winner(board))
+                .map(|winner| if winner == board.get_active_side() { Score::MATE } else { Score::MATED });
+            let drawn = board.draw_by_rules(self.draw_rules);
+            let known = self.probe_tablebase(board);
+            let estimate = match won.is_some() || drawn || known.is_some() {
+                true => None,
+                false => self.lazy_score(board, -beta, -alpha)
+                    .or_else(|| self.hybrid_score(board, pv, self.ply + 1)),
+            };
+            board.undo_move();
+
+            let mut scored = Vec::new();
+            if let Some(score) = won {
+                scored.push((*mv, score.to_parent()));
+            } else if drawn {
+                scored.push((*mv, Score::ZERO));
+            } else if let Some(score) = known {
+                scored.push((*mv, score.to_parent()));
+            } else if let Some(score) = estimate {
+                scored.push((*mv, -score));
+            } else {
+                pending_moves.push(*mv);
+                pending_boards.push(board.make_move_copy(*mv));
+            }
+
+            let is_last = index + 1 == moves.len();
+            if pending_boards.len() >= self.leaf_batch_size || (is_last && !pending_boards.is_empty()) {
+                let scores = self.evaluate_leaves(&pending_boards);
+                // Leaf scores are from the opponent's point of view
+                scored.extend(pending_moves.drain(..).zip(scores.into_iter().map(|score| -Score::new(score))));
+                pending_boards.clear();
+            }
+
+            for (mv, score) in scored {
+                if score > best_result.score {
+                    best_result.score = score;
+                    best_result.best_move = Some(mv);
+                }
+                if score > alpha {
+                    alpha = score;
+                }
+            }
+
+            if alpha >= beta {
+                break;
+            }
+        }
+
         best_result
     }
 }
@@ -0,0 +1,328 @@
+use std::{sync::{atomic::{AtomicBool, Ordering}, mpsc::{self, Receiver, RecvTimeoutError, Sender}, Arc, Mutex},
+    thread::{self, JoinHandle}, time::Instant};
+
+use crate::engine::{board::board::Board,
+    evaluator::evaluator::Evaluator,
+    move_generator::{chess_move::ChessMove, move_generator::MoveGenerator},
+    rules::variant::Variant,
+    searcher::{score::Score, searcher::Searcher, transposition_table::TranspositionTable}};
+
+
+// The deepest iteration a thread finished before it stopped
+#[derive(Clone, Copy, Debug)]
+pub struct ThreadResult {
+    pub thread: usize,
+    pub best_move: Option<ChessMove>,
+    pub score: Score,
+    // 0 when not even the first iteration finished
+    pub depth: u8,
+    pub nodes: u64,
+}
+
+// The stop flag of one search and the deepest iteration any of its threads
+// has finished so far. Each start makes a new one, so a stale copy can
+// neither stop nor read a later search
+#[derive(Clone)]
+pub struct SearchProgress {
+    stop: Arc<AtomicBool>,
+    best: Arc<Mutex<Option<ThreadResult>>>,
+}
+
+impl SearchProgress {
+    pub fn new() -> Self {
+        SearchProgress {
+            stop: Arc::new(AtomicBool::new(false)),
+            best: Arc::new(Mutex::new(None)),
+        }
+    }
+
+    pub fn stop(&self) {
+        self.stop.store(true, Ordering::Relaxed);
+    }
+
+    pub fn is_stopped(&self) -> bool {
+        self.stop.load(Ordering::Relaxed)
+    }
+
+    // None until the first iteration finishes
+    pub fn get_best(&self) -> Option<ThreadResult> {
+        *self.best.lock().unwrap_or_else(|e| e.into_inner())
+    }
+
+    // Keeps the result unless an earlier one is at least as deep
+    fn update(&self, result: ThreadResult) {
+        let mut best = self.best.lock().unwrap_or_else(|e| e.into_inner());
+        if best.is_none_or(|best| best.depth < result.depth) {
+            *best = Some(result);
+        }
+    }
+}
+
+impl Default for SearchProgress {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+
+enum Job {
+    Search { board: Box<Board>, max_depth: u8, deterministic: bool, variant: Variant, progress: SearchProgress },
+    Evaluator(Box<dyn Evaluator>),
+    Quit,
+}
+
+struct Worker {
+    sender: Sender<Job>,
+    handle: Option<JoinHandle<()>>,
+}
+
+
+// Search threads created once and reused between searches, so a UCI
+// frontend does not pay for spawning threads and building move generators,
+// evaluators and hash tables on every "go". Each thread keeps its own copy
+// of the board and its own evaluator from clone_for_thread, and all of them
+// share one transposition table (Lazy SMP): the threads search the same
+// position, and each cuts off on what the others stored. start returns at
+// once, leaving the caller free to read commands while the threads search;
+// stop and wait end the search
+pub struct SearchThreadPool {
+    workers: Vec<Worker>,
+    progress: SearchProgress,
+    results: Receiver<ThreadResult>,
+    transposition_table: Arc<TranspositionTable>,
+    // The variant whose rules the table's scores were found under
+    table_variant: Variant,
+    searching: bool,
+    deterministic: bool,
+    variant: Variant,
+}
+
+impl SearchThreadPool {
+    // Fails when the evaluator cannot be copied into other threads
+    pub fn new(evaluator: &dyn Evaluator, threads: usize, hash_bits: usize) -> Result<Self, String> {
+        let (result_sender, results) = mpsc::channel();
+        let transposition_table = Arc::new(TranspositionTable::new(hash_bits));
+        let mut workers = Vec::new();
+
+        for index in 0..threads.max(1) {
+            let evaluator = evaluator.clone_for_thread()
+                .ok_or_else(|| "The evaluator cannot be shared between search threads".to_string())?;
+            let (sender, jobs) = mpsc::channel();
+            let result_sender = result_sender.clone();
+            let transposition_table = Arc::clone(&transposition_table);
+            let handle = thread::Builder::new()
+                .name(format!("search-{index}"))
+                .spawn(move || run_worker(index, evaluator, transposition_table, jobs, result_sender))
+                .map_err(|e| format!("Failed to spawn search thread {index}: {e}"))?;
+            workers.push(Worker {
+                sender,
+                handle: Some(handle),
+            });
+        }
+
+        Ok(SearchThreadPool {
+            workers,
+            progress: SearchProgress::new(),
+            results,
+            transposition_table,
+            table_variant: Variant::Standard,
+            searching: false,
+            deterministic: false,
+            variant: Variant::Standard,
+        })
+    }
+
+    pub fn get_threads(&self) -> usize {
+        self.workers.len()
+    }
+
+    pub fn is_searching(&self) -> bool {
+        self.searching
+    }
+
+    // From the next start on, only the first thread searches, in the
+    // Searcher's deterministic mode, so that the same position and depth
+    // always give the same move and node count. The other threads report
+    // depth 0 at once
+    pub fn set_deterministic(&mut self, deterministic: bool) {
+        self.deterministic = deterministic;
+    }
+
+    // The variant searched from the next start on
+    pub fn set_variant(&mut self, variant: Variant) {
+        self.variant = variant;
+    }
+
+    pub fn get_variant(&self) -> Variant {
+        self.variant
+    }
+
+    // Gives every thread a new copy of evaluator, e.g. once its model was
+    // reloaded, as the copies made before keep the old model. A search
+    // still running is stopped and its results dropped, and the hash table
+    // is cleared of the old scores. Fails, leaving the threads as they
+    // were, when the evaluator cannot be copied
+    pub fn set_evaluator(&mut self, evaluator: &dyn Evaluator) -> Result<(), String> {
+        let copies = (0..self.workers.len())
+            .map(|_| evaluator.clone_for_thread())
+            .collect::<Option<Vec<_>>>()
+            .ok_or_else(|| "The evaluator cannot be shared between search threads".to_string())?;
+
+        if self.searching {
+            self.stop();
+            self.wait();
+        }
+        self.transposition_table.clear();
+        for (worker, copy) in self.workers.iter().zip(copies) {
+            let _ = worker.sender.send(Job::Evaluator(copy));
+        }
+        Ok(())
+    }
+
+    // Every thread searches the position with iterative deepening up to
+    // max_depth. A search still running is stopped and its results dropped.
+    // The hash table is cleared when the variant changed since the last
+    // search, as the same position scores differently under other rules.
+    // The returned progress can be read and stopped from other threads
+    pub fn start(&mut self, board: &Board, max_depth: u8) -> SearchProgress {
+        if self.searching {
+            self.stop();
+            self.wait();
+        }
+        if self.table_variant != self.variant {
+            self.transposition_table.clear();
+            self.table_variant = self.variant;
+        }
+        self.progress = SearchProgress::new();
+        for worker in &self.workers {
+            let _ = worker.sender.send(Job::Search {
+                board: Box::new(board.clone()),
+                max_depth,
+                deterministic: self.deterministic,
+                variant: self.variant,
+                progress: self.progress.clone(),
+            });
+        }
+        self.searching = true;
+        self.progress.clone()
+    }
+
+    // Asks the threads to finish; wait collects what they found
+    pub fn stop(&self) {
+        self.progress.stop();
+    }
+
+    // Blocks until every thread is done, then returns their results,
+    // deepest first and in thread order among equal depths, so the first
+    // holds the move to play. Empty when no search was started
+    pub fn wait(&mut self) -> Vec<ThreadResult> {
+        self.collect_results(None)
+    }
+
+    // As wait, but stops the search once the deadline has passed
+    pub fn wait_until(&mut self, deadline: Instant) -> Vec<ThreadResult> {
+        self.collect_results(Some(deadline))
+    }
+
+    fn collect_results(&mut self, deadline: Option<Instant>) -> Vec<ThreadResult> {
+        if !self.searching {
+            return Vec::new();
+        }
+        let mut results = Vec::new();
+        while results.len() < self.workers.len() {
+            let received = match deadline {
+                Some(deadline) if !self.progress.is_stopped() => {
+                    match self.results.recv_timeout(deadline.saturating_duration_since(Instant::now())) {
+                        Err(RecvTimeoutError::Timeout) => {
+                            self.stop();
+                            continue;
+                        }
+                        received => received.ok(),
+                    }
+                }
+                _ => self.results.recv().ok(),
+            };
+            match received {
+                Some(result) => results.push(result),
+                None => break,
+            }
+        }
+        results.sort_by(|a, b| b.depth.cmp(&a.depth).then(a.thread.cmp(&b.thread)));
+        self.searching = false;
+        results
+    }
+}
+
+impl Drop for SearchThreadPool {
+    fn drop(&mut self) {
+        self.stop();
+        for worker in &self.workers {
+            let _ = worker.sender.send(Job::Quit);
+        }
+        for worker in &mut self.workers {
+            if let Some(handle) = worker.handle.take() {
+                let _ = handle.join();
+            }
+        }
+    }
+}
+
+
+fn run_worker(index: usize, mut evaluator: Box<dyn Evaluator>, transposition_table: Arc<TranspositionTable>,
+    jobs: Receiver<Job>, results: Sender<ThreadResult>) {
+    let movegen = MoveGenerator::new();
+
+    while let Ok(job) = jobs.recv() {
+        let (mut board, max_depth, deterministic, variant, progress) = match job {
+            Job::Search { board, max_depth, deterministic, variant, progress } =>
+                (board, max_depth, deterministic, variant, progress),
+            Job::Evaluator(replacement) => {
+                evaluator = replacement;
+                continue;
+            }
+            Job::Quit => break,
+        };
+        let mut result = ThreadResult {
+            thread: index,
+            best_move: None,
+            score: Score::ZERO,
+            depth: 0,
+            nodes: 0,
+        };
+        if deterministic && index > 0 {
+            if results.send(result).is_err() {
+                break;
+            }
+            continue;
+        }
+
+        let mut searcher = Searcher::new(evaluator.as_mut(), &movegen, &transposition_table);
+        searcher.set_stop_flag(&progress.stop);
+        searcher.set_deterministic(deterministic);
+        searcher.set_rules(variant.get_rules());
+        searcher.new_search();
+        // Odd threads start one ply deeper, so that the threads are not
+        // all on the same iteration
+        let first_depth = (1 + (index % 2) as u8).min(max_depth.max(1));
+        for depth in first_depth..=max_depth {
+            let iteration = searcher.search_move(&mut board, depth, Score::MATED, Score::MATE);
+            if searcher.is_stopped() {
+                break;
+            }
+            result.best_move = iteration.best_move;
+            result.score = iteration.score;
+            result.depth = depth;
+            result.nodes = searcher.get_nodes();
+            progress.update(result);
+        }
+        result.nodes = searcher.get_nodes();
+
+        // The first thread reaching max_depth ends the search for all
+        if !searcher.is_stopped() {
+            progress.stop();
+        }
+        if results.send(result).is_err() {
+            break;
+        }
+    }
+}
@@ -1,5 +1,8 @@
-use crate::engine::{definitions::ZobristKey,
-    move_generator::chess_move::ChessMove};
+use std::sync::atomic::{AtomicU64, Ordering};
+
+use crate::engine::{definitions::{Piece, Square, ZobristKey},
+    move_generator::chess_move::{ChessMove, ChessMoveFlags},
+    searcher::score::Score};
 
 
 #[derive(Clone, Copy)]
@@ -13,14 +16,29 @@ pub enum Bound {
 pub struct TranspositionTableEntry {
     pub zobrist: ZobristKey,
     pub depth: u8,
-    pub score: f32,
+    pub score: Score,
     pub flag: Bound,
     pub best_move: Option<ChessMove>,
 
 }
 
+// An entry packed into two words, with the key stored xored with both.
+// Threads read and write slots without locking; a slot torn by two writes
+// at once no longer matches its key and reads as empty
+#[derive(Default)]
+struct Slot {
+    key: AtomicU64,
+    data: AtomicU64,
+    best_move: AtomicU64,
+}
+
+// Set in the data word of every stored entry, so empty slots never match
+const OCCUPIED: u64 = 1 << 42;
+
+// Shared by every search thread, which is what makes the thread pool's
+// threads help each other: what one thread finds, the others cut off on
 pub struct TranspositionTable {
-    entries: Vec<Option<TranspositionTableEntry>>,
+    entries: Vec<Slot>,
     mask: usize, // for fast indexing if size is a large power of two
 }
 
@@ -28,28 +46,105 @@ impl TranspositionTable {
     pub fn new(size_bits: usize) -> Self {
         let size = 1 << size_bits;
         TranspositionTable {
-            entries: vec![None; size],
+            entries: (0..size).map(|_| Slot::default()).collect(),
             mask: size - 1,
         }
     }
 
+    pub fn clear(&self) {
+        for slot in &self.entries {
+            slot.key.store(0, Ordering::Relaxed);
+            slot.data.store(0, Ordering::Relaxed);
+            slot.best_move.store(0, Ordering::Relaxed);
+        }
+    }
+
     fn index(&self, zobrist: u64) -> usize {
         (zobrist as usize) & self.mask
     }
 
-    pub fn store(&mut self, zobrist: u64, entry: TranspositionTableEntry) {
-        let idx = self.index(zobrist);
-        let replace = match self.entries[idx] {
+    pub fn store(&self, zobrist: u64, entry: TranspositionTableEntry) {
+        let slot = &self.entries[self.index(zobrist)];
+        let replace = match Self::load(slot) {
             None => true,
-            Some(existing) => entry.depth >= existing.depth,
+            Some((_, data, _)) => entry.depth >= unpack_depth(data),
         };
         if replace {
-            self.entries[idx] = Some(entry);
+            let data = pack_data(&entry);
+            let best_move = entry.best_move.map_or(0, pack_move);
+            slot.data.store(data, Ordering::Relaxed);
+            slot.best_move.store(best_move, Ordering::Relaxed);
+            slot.key.store(zobrist ^ data ^ best_move, Ordering::Relaxed);
         }
     }
 
     pub fn retrieve(&self, zobrist: u64) -> Option<TranspositionTableEntry> {
-        let idx = self.index(zobrist);
-        self.entries[idx].filter(|e| e.zobrist == zobrist)
+        let slot = &self.entries[self.index(zobrist)];
+        let (key, data, best_move) = Self::load(slot).filter(|&(key, _, _)| key == zobrist)?;
+        Some(TranspositionTableEntry {
+            zobrist: key,
+            depth: unpack_depth(data),
+            score: Score::new(f32::from_bits(data as u32)),
+            flag: match (data >> 40) & 3 {
+                0 => Bound::Exact,
+                1 => Bound::LowerBound,
+                _ => Bound::UpperBound,
+            },
+            best_move: unpack_move(best_move),
+        })
+    }
+
+    // The slot's key and words, None when it is empty or torn
+    fn load(slot: &Slot) -> Option<(ZobristKey, u64, u64)> {
+        let data = slot.data.load(Ordering::Relaxed);
+        let best_move = slot.best_move.load(Ordering::Relaxed);
+        let key = slot.key.load(Ordering::Relaxed) ^ data ^ best_move;
+        (data & OCCUPIED != 0).then_some((key, data, best_move))
+    }
+}
+
+
+// Score bits, then the depth and the bound
+fn pack_data(entry: &TranspositionTableEntry) -> u64 {
+    let flag = match entry.flag {
+        Bound::Exact => 0,
+        Bound::LowerBound => 1,
+        Bound::UpperBound => 2,
+    };
+    entry.score.get_value().to_bits() as u64 | (entry.depth as u64) << 32 | flag << 40 | OCCUPIED
+}
+
+fn unpack_depth(data: u64) -> u8 {
+    (data >> 32) as u8
+}
+
+// Bit 0 marks a move, so that no move packs to 0
+fn pack_move(mv: ChessMove) -> u64 {
+    let promotion = mv.promotion.map_or(7, |piece| piece as u64);
+    1 | (mv.from as u64) << 1
+        | (mv.to as u64) << 7
+        | (mv.piece as u64) << 13
+        | promotion << 16
+        | (mv.flags.bits() as u64) << 19
+        | (mv.is_check as u64) << 27
+        | (mv.is_checkmate as u64) << 28
+}
+
+fn unpack_move(packed: u64) -> Option<ChessMove> {
+    if packed & 1 == 0 {
+        return None;
     }
+    let promotion = match (packed >> 16) & 7 {
+        7 => None,
+        piece => Some(Piece::try_from(piece as usize).ok()?),
+    };
+    Some(ChessMove {
+        piece: Piece::try_from(((packed >> 13) & 7) as usize).ok()?,
+        from: Square::try_from(((packed >> 1) & 63) as usize).ok()?,
+        to: Square::try_from(((packed >> 7) & 63) as usize).ok()?,
+        promotion,
+        is_check: (packed >> 27) & 1 != 0,
+        is_checkmate: (packed >> 28) & 1 != 0,
+        flags: ChessMoveFlags::from_bits_truncate((packed >> 19) as u8),
+    })
 }
@@ -0,0 +1,276 @@
+use std::{collections::HashSet,
+    fmt::{self, Display},
+    fs::File,
+    io::{self, BufReader, BufWriter, Read, Write},
+    path::{Path, PathBuf}};
+
+use crate::engine::{bitboard::BitboardExt,
+    board::{board::Board, board_builder::BoardBuilder, fen::FenError},
+    definitions::{Piece, Side, Square, ZobristKey}};
+
+
+const MAGIC: &[u8; 4] = b"KCBP";
+const VERSION: u32 = 1;
+
+// Bytes per record:
+//   0..8    occupancy bitboard, little endian
+//   8..24   one nibble per occupied square, lowest square first:
+//           side << 3 | piece
+//   24      bit 0 side to move, bits 1-4 castling rights
+//   25      en passant square, 0xFF for none
+//   26      half-move clock
+//   27      result for the side to move: 1, 0 or -1
+//   28..30  search score in centipawns for the side to move
+//   30..32  ply of the position in its game
+pub const RECORD_SIZE: usize = 32;
+
+// Mates are stored as the largest scores
+pub const MAX_RECORD_SCORE: i16 = 32000;
+
+const NO_SQUARE: u8 = 0xFF;
+
+
+#[derive(Debug)]
+pub enum BinpackError {
+    ReadError(io::Error),
+    BadMagic,
+    UnsupportedVersion(u32),
+    CorruptRecord(String),
+    InvalidRecord(FenError),
+}
+
+impl Display for BinpackError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::ReadError(error) =>
+                    write!(f, "Failed to read binpack: {error}"),
+            Self::BadMagic =>
+                    write!(f, "Not a binpack file"),
+            Self::UnsupportedVersion(version) =>
+                    write!(f, "Unsupported binpack version: {version}"),
+            Self::CorruptRecord(message) =>
+                    write!(f, "Corrupt binpack record: {message}"),
+            Self::InvalidRecord(error) =>
+                    write!(f, "Invalid position in binpack: {error}"),
+        }
+    }
+}
+
+
+// One training sample: a position, how the search scored it and how the
+// game ended
+#[derive(Clone)]
+pub struct BinpackRecord {
+    pub board: Board,
+    // Centipawns for the side to move
+    pub score: i16,
+    // 1 when the side to move went on to win, 0 for a draw, -1 for a loss
+    pub result: i8,
+    pub ply: u16,
+}
+
+impl BinpackRecord {
+    pub fn encode(&self) -> [u8; RECORD_SIZE] {
+        let mut bytes = [0; RECORD_SIZE];
+        let occupancy = self.board.get_full_occupancy();
+        bytes[0..8].copy_from_slice(&occupancy.to_le_bytes());
+
+        // A legal position has at most 32 pieces, two per byte
+        for (index, square) in occupancy.squares().take(32).enumerate() {
+            let (side, piece) = self.board.piece_on(square).unwrap();
+            let nibble = (side as u8) << 3 | piece as u8;
+            bytes[8 + index / 2] |= nibble << (4 * (index % 2));
+        }
+
+        let state = &self.board.game_state;
+        bytes[24] = self.board.get_active_side() as u8 | (state.castling & 0xF) << 1;
+        bytes[25] = state.en_passant.unwrap_or(NO_SQUARE);
+        bytes[26] = state.half_move_clock;
+        bytes[27] = self.result as u8;
+        bytes[28..30].copy_from_slice(&self.score.to_le_bytes());
+        bytes[30..32].copy_from_slice(&self.ply.to_le_bytes());
+        bytes
+    }
+
+    pub fn decode(bytes: &[u8; RECORD_SIZE]) -> Result<Self, BinpackError> {
+        let invalid = |message: &str| BinpackError::CorruptRecord(message.to_string());
+
+        let occupancy = u64::from_le_bytes(bytes[0..8].try_into().unwrap());
+        let mut builder = BoardBuilder::new();
+        for (index, square) in occupancy.squares().take(32).enumerate() {
+            let nibble = (bytes[8 + index / 2] >> (4 * (index % 2))) & 0xF;
+            let side = Side::try_from((nibble >> 3) as usize).map_err(|_| invalid("bad side"))?;
+            let piece = Piece::try_from((nibble & 0x7) as usize)
+                .ok()
+                .filter(|piece| *piece != Piece::None)
+                .ok_or_else(|| invalid("bad piece"))?;
+            builder = builder.piece(side, piece, square);
+        }
+
+        let side = if bytes[24] & 1 == 0 { Side::White } else { Side::Black };
+        let en_passant = match bytes[25] {
+            NO_SQUARE => None,
+            square => Some(Square::try_from(square as usize).map_err(|_| invalid("bad en passant square"))?),
+        };
+        let ply = u16::from_le_bytes([bytes[30], bytes[31]]);
+        let board = builder
+            .side_to_move(side)
+            .castling((bytes[24] >> 1) & 0xF)
+            .en_passant(en_passant)
+            .half_move_clock(bytes[26])
+            .full_move_number(1 + ply / 2)
+            .build()
+            .map_err(BinpackError::InvalidRecord)?;
+
+        Ok(BinpackRecord {
+            board,
+            score: i16::from_le_bytes([bytes[28], bytes[29]]),
+            result: bytes[27] as i8,
+            ply,
+        })
+    }
+}
+
+
+// Writes records into numbered shard files, <name>_0000.binpack and up,
+// each starting with the magic and version. Positions already written are
+// skipped when deduplication is on
+pub struct BinpackWriter {
+    directory: PathBuf,
+    name: String,
+    // Records per shard; 0 keeps everything in one file
+    shard_size: usize,
+    deduplicate: bool,
+    seen: HashSet<ZobristKey>,
+    shard: Option<BufWriter<File>>,
+    shard_paths: Vec<PathBuf>,
+    records_in_shard: usize,
+    records_written: usize,
+    duplicates_skipped: usize,
+}
+
+impl BinpackWriter {
+    pub fn new<P: AsRef<Path>>(directory: P, name: &str) -> Self {
+        BinpackWriter {
+            directory: directory.as_ref().to_path_buf(),
+            name: name.to_string(),
+            shard_size: 0,
+            deduplicate: true,
+            seen: HashSet::new(),
+            shard: None,
+            shard_paths: Vec::new(),
+            records_in_shard: 0,
+            records_written: 0,
+            duplicates_skipped: 0,
+        }
+    }
+
+    pub fn shard_size(mut self, records: usize) -> Self {
+        self.shard_size = records;
+        self
+    }
+
+    pub fn deduplicate(mut self, enabled: bool) -> Self {
+        self.deduplicate = enabled;
+        self
+    }
+
+    // False when the position was a duplicate and was not written
+    pub fn write(&mut self, record: &BinpackRecord) -> io::Result<bool> {
+        if self.deduplicate && !self.seen.insert(record.board.game_state.zobrist_key) {
+            self.duplicates_skipped += 1;
+            return Ok(false);
+        }
+
+        let shard_full = self.shard_size > 0 && self.records_in_shard >= self.shard_size;
+        if self.shard.is_none() || shard_full {
+            self.open_shard()?;
+        }
+        if let Some(shard) = self.shard.as_mut() {
+            shard.write_all(&record.encode())?;
+        }
+        self.records_in_shard += 1;
+        self.records_written += 1;
+        Ok(true)
+    }
+
+    pub fn flush(&mut self) -> io::Result<()> {
+        match self.shard.as_mut() {
+            Some(shard) => shard.flush(),
+            None => Ok(()),
+        }
+    }
+
+    fn open_shard(&mut self) -> io::Result<()> {
+        self.flush()?;
+        std::fs::create_dir_all(&self.directory)?;
+        let path = self.directory.join(format!("{}_{:04}.binpack", self.name, self.shard_paths.len()));
+        let mut shard = BufWriter::new(File::create(&path)?);
+        shard.write_all(MAGIC)?;
+        shard.write_all(&VERSION.to_le_bytes())?;
+
+        self.shard = Some(shard);
+        self.shard_paths.push(path);
+        self.records_in_shard = 0;
+        Ok(())
+    }
+
+    pub fn get_shard_paths(&self) -> &[PathBuf] {
+        &self.shard_paths
+    }
+
+    pub fn get_records_written(&self) -> usize {
+        self.records_written
+    }
+
+    pub fn get_duplicates_skipped(&self) -> usize {
+        self.duplicates_skipped
+    }
+}
+
+impl Drop for BinpackWriter {
+    fn drop(&mut self) {
+        let _ = self.flush();
+    }
+}
+
+
+// Iterates over the records of one shard
+pub struct BinpackReader<R: Read> {
+    reader: R,
+}
+
+impl BinpackReader<BufReader<File>> {
+    pub fn open<P: AsRef<Path>>(path: P) -> Result<Self, BinpackError> {
+        let file = File::open(path).map_err(BinpackError::ReadError)?;
+        Self::new(BufReader::new(file))
+    }
+}
+
+impl<R: Read> BinpackReader<R> {
+    pub fn new(mut reader: R) -> Result<Self, BinpackError> {
+        let mut header = [0; 8];
+        reader.read_exact(&mut header).map_err(BinpackError::ReadError)?;
+        if &header[0..4] != MAGIC {
+            return Err(BinpackError::BadMagic);
+        }
+        let version = u32::from_le_bytes(header[4..8].try_into().unwrap());
+        if version != VERSION {
+            return Err(BinpackError::UnsupportedVersion(version));
+        }
+        Ok(BinpackReader { reader })
+    }
+}
+
+impl<R: Read> Iterator for BinpackReader<R> {
+    type Item = Result<BinpackRecord, BinpackError>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let mut bytes = [0; RECORD_SIZE];
+        match self.reader.read_exact(&mut bytes) {
+            Ok(()) => Some(BinpackRecord::decode(&bytes)),
+            Err(error) if error.kind() == io::ErrorKind::UnexpectedEof => None,
+            Err(error) => Some(Err(BinpackError::ReadError(error))),
+        }
+    }
+}
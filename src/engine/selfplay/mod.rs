@@ -0,0 +1,3 @@
+pub mod binpack;
+
+pub mod self_play;
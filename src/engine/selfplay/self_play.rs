@@ -0,0 +1,162 @@
+use std::io;
+
+use rand::{Rng, SeedableRng};
+use rand_chacha::ChaCha8Rng;
+
+use crate::engine::{board::{board::Board, game_result::GameResult},
+    definitions::{Side, FEN_STARTING_POSITION},
+    evaluator::evaluator::Evaluator,
+    move_generator::move_generator::MoveGenerator,
+    searcher::{score::Score, searcher::Searcher, transposition_table::TranspositionTable},
+    selfplay::binpack::{BinpackRecord, BinpackWriter, MAX_RECORD_SCORE}};
+
+
+#[derive(Clone, Copy, Default, Debug)]
+pub struct SelfPlayStats {
+    pub games_played: usize,
+    pub white_wins: usize,
+    pub black_wins: usize,
+    pub draws: usize,
+    pub positions_written: usize,
+    pub duplicates_skipped: usize,
+}
+
+
+// Plays the engine against itself at a fixed depth and labels every
+// searched position with its score and the game's result. The first plies
+// are played at random so that games with the same seed still differ from
+// one another
+pub struct SelfPlay<'a> {
+    evaluator: &'a mut dyn Evaluator,
+    movegen: &'a MoveGenerator,
+    depth: u8,
+    random_plies: usize,
+    // Games still going after this many plies are scored as draws
+    max_plies: usize,
+    skip_in_check: bool,
+    hash_bits: usize,
+    rng: ChaCha8Rng,
+}
+
+impl<'a> SelfPlay<'a> {
+    pub fn new(evaluator: &'a mut dyn Evaluator, movegen: &'a MoveGenerator) -> Self {
+        SelfPlay {
+            evaluator,
+            movegen,
+            depth: 4,
+            random_plies: 8,
+            max_plies: 400,
+            skip_in_check: true,
+            hash_bits: 20,
+            rng: ChaCha8Rng::seed_from_u64(0),
+        }
+    }
+
+    pub fn depth(mut self, depth: u8) -> Self {
+        self.depth = depth;
+        self
+    }
+
+    pub fn random_plies(mut self, plies: usize) -> Self {
+        self.random_plies = plies;
+        self
+    }
+
+    pub fn max_plies(mut self, plies: usize) -> Self {
+        self.max_plies = plies;
+        self
+    }
+
+    pub fn skip_in_check(mut self, enabled: bool) -> Self {
+        self.skip_in_check = enabled;
+        self
+    }
+
+    // Size of each game's transposition table, as a power of two
+    pub fn hash_bits(mut self, bits: usize) -> Self {
+        self.hash_bits = bits;
+        self
+    }
+
+    // Runs with different seeds produce different games, e.g. for several
+    // processes writing their own shards
+    pub fn seed(mut self, seed: u64) -> Self {
+        self.rng = ChaCha8Rng::seed_from_u64(seed);
+        self
+    }
+
+    // One game, with its records and how it ended
+    pub fn play_game(&mut self) -> (Vec<BinpackRecord>, GameResult) {
+        let mut board = Board::try_from_fen(FEN_STARTING_POSITION).unwrap();
+        let transposition_table = TranspositionTable::new(self.hash_bits);
+        let mut positions: Vec<(Board, i16, u16)> = Vec::new();
+
+        let mut result = GameResult::Ongoing;
+        for ply in 0..self.max_plies {
+            result = board.game_result(self.movegen);
+            if result.is_over() {
+                break;
+            }
+
+            let moves = self.movegen.generate_moves(&mut board);
+            if ply < self.random_plies {
+                let mv = moves[self.rng.random_range(0..moves.len())];
+                board.make_move(mv);
+                continue;
+            }
+
+            let mut search_board = board.clone();
+            let mut searcher = Searcher::new(&mut *self.evaluator, self.movegen, &transposition_table);
+            let searched = searcher.search_move(&mut search_board, self.depth, Score::MATED, Score::MATE);
+            let mv = searched.best_move
+                .filter(|mv| moves.contains(mv))
+                .unwrap_or(moves[0]);
+
+            if !(self.skip_in_check && board.in_check()) {
+                positions.push((board.clone(), record_score(searched.score), ply as u16));
+            }
+            board.make_move(mv);
+        }
+
+        let winner = result.winner();
+        let records = positions.into_iter()
+            .map(|(board, score, ply)| {
+                let result = match winner {
+                    Some(side) if side == board.get_active_side() => 1,
+                    Some(_) => -1,
+                    None => 0,
+                };
+                BinpackRecord { board, score, result, ply }
+            })
+            .collect();
+        (records, result)
+    }
+
+    pub fn run(&mut self, games: usize, writer: &mut BinpackWriter) -> io::Result<SelfPlayStats> {
+        let mut stats = SelfPlayStats::default();
+        for _ in 0..games {
+            let (records, result) = self.play_game();
+            stats.games_played += 1;
+            match result.winner() {
+                Some(Side::White) => stats.white_wins += 1,
+                Some(Side::Black) => stats.black_wins += 1,
+                None => stats.draws += 1,
+            }
+
+            for record in &records {
+                match writer.write(record)? {
+                    true => stats.positions_written += 1,
+                    false => stats.duplicates_skipped += 1,
+                }
+            }
+        }
+        writer.flush()?;
+        Ok(stats)
+    }
+}
+
+
+fn record_score(score: Score) -> i16 {
+    let max = MAX_RECORD_SCORE as f32;
+    score.get_value().clamp(-max, max) as i16
+}
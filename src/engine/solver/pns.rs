@@ -0,0 +1,245 @@
+use crate::engine::{board::{board::Board, game_result::DrawRules},
+    move_generator::{chess_move::ChessMove, move_generator::MoveGenerator}};
+
+
+// Proof and disproof numbers saturate here, meaning proven impossible
+const INFINITY: u32 = u32::MAX;
+
+// Each node holds a move and its proof numbers, so a million of them stay
+// well under 100 MB
+const DEFAULT_MAX_NODES: usize = 1_000_000;
+
+
+#[derive(Clone, PartialEq, Debug)]
+pub enum MateResult {
+    // The side to move mates in that many moves at most, whatever the
+    // defence. line is a mating line with the defender's longest
+    // resistance
+    Mate { moves: u32, line: Vec<ChessMove> },
+    // No forced mate within the moves asked for
+    NoMate,
+    // The node limit was reached first
+    Unknown,
+}
+
+
+struct Node {
+    mv: Option<ChessMove>,
+    parent: Option<usize>,
+    children: Vec<usize>,
+    proof: u32,
+    disproof: u32,
+    // The side trying to mate is to move, making this an OR node, where one
+    // proven child proves the node. At the others every child must be
+    attacker_to_move: bool,
+    ply: u32,
+    expanded: bool,
+}
+
+
+// Proof-number search for forced mates. Where alpha-beta needs every ply
+// of a long mate within its depth, and a model-backed eval to see it
+// coming, proof-number search only follows the replies that are hardest
+// to refute, so forced sequences dozens of plies deep are found by trying
+// little more than the forcing moves. It proves or refutes a mate within
+// the limit, and never scores a position, which is what checking puzzles
+// and study solutions needs
+pub struct MateSolver<'a> {
+    movegen: &'a MoveGenerator,
+    max_nodes: usize,
+    nodes: Vec<Node>,
+    max_plies: u32,
+}
+
+impl<'a> MateSolver<'a> {
+    pub fn new(movegen: &'a MoveGenerator) -> Self {
+        Self {
+            movegen,
+            max_nodes: DEFAULT_MAX_NODES,
+            nodes: Vec::new(),
+            max_plies: 0,
+        }
+    }
+
+    pub fn max_nodes(mut self, max_nodes: usize) -> Self {
+        self.max_nodes = max_nodes.max(1);
+        self
+    }
+
+    // Nodes in the tree of the last solve
+    pub fn get_nodes(&self) -> usize {
+        self.nodes.len()
+    }
+
+    // Looks for a mate by the side to move in at most mate_in moves
+    pub fn solve(&mut self, board: &Board, mate_in: u32) -> MateResult {
+        self.max_plies = (2 * mate_in).saturating_sub(1);
+        self.nodes.clear();
+
+        let mut board = board.clone();
+        let root = self.new_node(&mut board, None, None, true, 0);
+        self.nodes.push(root);
+
+        while !self.is_solved(0) && self.nodes.len() < self.max_nodes {
+            let mut index = 0;
+            let mut depth = 0;
+            while self.nodes[index].expanded {
+                index = self.most_proving_child(index);
+                board.make_move(self.nodes[index].mv.unwrap());
+                depth += 1;
+            }
+
+            self.expand(index, &mut board);
+            self.update_ancestors(index);
+            for _ in 0..depth {
+                board.undo_move();
+            }
+        }
+
+        match (self.nodes[0].proof, self.nodes[0].disproof) {
+            (0, _) => {
+                let line = self.mating_line();
+                MateResult::Mate {
+                    moves: (line.len() as u32).div_ceil(2),
+                    line,
+                }
+            }
+            (_, 0) => MateResult::NoMate,
+            _ => MateResult::Unknown,
+        }
+    }
+
+    fn is_solved(&self, index: usize) -> bool {
+        self.nodes[index].proof == 0 || self.nodes[index].disproof == 0
+    }
+
+    // A leaf for the position on board. Positions where the game is over,
+    // or the attacker has run out of moves, are solved on the spot; at the
+    // others the defender's number of replies is how hard the node is to
+    // prove
+    fn new_node(&self, board: &mut Board, mv: Option<ChessMove>, parent: Option<usize>,
+        attacker_to_move: bool, ply: u32) -> Node {
+        let replies = self.movegen.generate_legal_moves(board).len();
+        let (proof, disproof) = if replies == 0 {
+            // Checkmate proves the node only when the defender is mated
+            match board.in_check() && !attacker_to_move {
+                true => (0, INFINITY),
+                false => (INFINITY, 0),
+            }
+        } else if board.draw_by_rules(DrawRules::Claimable) || ply >= self.max_plies {
+            (INFINITY, 0)
+        } else if attacker_to_move {
+            (1, 1)
+        } else {
+            (replies as u32, 1)
+        };
+
+        Node {
+            mv,
+            parent,
+            children: Vec::new(),
+            proof,
+            disproof,
+            attacker_to_move,
+            ply,
+            expanded: false,
+        }
+    }
+
+    fn expand(&mut self, index: usize, board: &mut Board) {
+        let (attacker_to_move, ply) = (self.nodes[index].attacker_to_move, self.nodes[index].ply);
+
+        for mv in self.movegen.generate_legal_moves(board) {
+            board.make_move(mv);
+            let child = self.new_node(board, Some(mv), Some(index), !attacker_to_move, ply + 1);
+            board.undo_move();
+
+            self.nodes.push(child);
+            let child_index = self.nodes.len() - 1;
+            self.nodes[index].children.push(child_index);
+            // The rest of the replies cannot change a solved node
+            if self.is_solved(child_index) && self.solves_parent(child_index) {
+                break;
+            }
+        }
+        self.nodes[index].expanded = true;
+    }
+
+    // A proven move solves an OR node, a refuting reply an AND node
+    fn solves_parent(&self, child: usize) -> bool {
+        match self.nodes[child].attacker_to_move {
+            true => self.nodes[child].disproof == 0,
+            false => self.nodes[child].proof == 0,
+        }
+    }
+
+    fn most_proving_child(&self, index: usize) -> usize {
+        let children = self.nodes[index].children.iter().copied();
+        match self.nodes[index].attacker_to_move {
+            true => children.min_by_key(|&child| self.nodes[child].proof),
+            false => children.min_by_key(|&child| self.nodes[child].disproof),
+        }
+        .unwrap()
+    }
+
+    fn update_ancestors(&mut self, mut index: usize) {
+        loop {
+            self.update_numbers(index);
+            match self.nodes[index].parent {
+                Some(parent) => index = parent,
+                None => break,
+            }
+        }
+    }
+
+    fn update_numbers(&mut self, index: usize) {
+        let node = &self.nodes[index];
+        if !node.expanded {
+            return;
+        }
+        let proofs = node.children.iter().map(|&child| self.nodes[child].proof);
+        let disproofs = node.children.iter().map(|&child| self.nodes[child].disproof);
+
+        // A node without children is an expanded leaf, solved when created
+        let (proof, disproof) = match node.attacker_to_move {
+            true => (proofs.min().unwrap_or(node.proof), disproofs.fold(0, u32::saturating_add)),
+            false => (proofs.fold(0, u32::saturating_add), disproofs.min().unwrap_or(node.disproof)),
+        };
+        self.nodes[index].proof = proof;
+        self.nodes[index].disproof = disproof;
+    }
+
+    // Plies until mate from a proven node, with the attacker taking the
+    // quickest mate and the defender holding out longest
+    fn mate_plies(&self, index: usize) -> u32 {
+        let node = &self.nodes[index];
+        let proven = node.children.iter()
+            .filter(|&&child| self.nodes[child].proof == 0)
+            .map(|&child| self.mate_plies(child) + 1);
+        match node.attacker_to_move {
+            true => proven.min().unwrap_or(0),
+            false => proven.max().unwrap_or(0),
+        }
+    }
+
+    fn mating_line(&self) -> Vec<ChessMove> {
+        let mut line = Vec::new();
+        let mut index = 0;
+
+        while !self.nodes[index].children.is_empty() {
+            let proven = self.nodes[index].children.iter()
+                .copied()
+                .filter(|&child| self.nodes[child].proof == 0);
+            let next = match self.nodes[index].attacker_to_move {
+                true => proven.min_by_key(|&child| self.mate_plies(child)),
+                false => proven.max_by_key(|&child| self.mate_plies(child)),
+            };
+            let Some(next) = next else {
+                break;
+            };
+            line.push(self.nodes[next].mv.unwrap());
+            index = next;
+        }
+        line
+    }
+}
@@ -0,0 +1,331 @@
+use std::{fs, io::Read, path::Path};
+
+use flate2::read::ZlibDecoder;
+
+use crate::engine::{bitboard::BitboardExt,
+    board::board::Board,
+    definitions::{NrOf, Piece, Side, Square},
+    tablebase::tablebase::{Tablebase, Wdl}};
+
+
+// The endings read so far, by file name and the strong side's piece
+const MATERIALS: [(&str, Piece); 5] = [
+    ("kqk", Piece::Queen),
+    ("krk", Piece::Rook),
+    ("kbk", Piece::Bishop),
+    ("knk", Piece::Knight),
+    ("kpk", Piece::Pawn),
+];
+
+// King pairs left once the board's symmetries are taken out
+const KING_PAIRS: usize = 462;
+const NO_KING_PAIR: u16 = u16::MAX;
+// Pawn squares on files a to d, ranks 2 to 7
+const PAWN_SLICES: usize = 24;
+
+// The two low bits of an entry; the bits above count moves
+const DRAW: u8 = 0;
+const WHITE_MATES: u8 = 1;
+const BLACK_MATES: u8 = 2;
+const FORBIDDEN: u8 = 3;
+
+// Compressed blocks start with the id of the compressor that wrote them
+const STORED_BLOCK: u8 = 0;
+const ZLIB_BLOCK: u8 = 1;
+// A zlib stream's first byte, for blocks written without the id
+const ZLIB_HEADER: u8 = 0x78;
+
+// Words before the block offsets in compressed files
+const HEADER_WORDS: usize = 10;
+
+
+// Distance-to-mate tables in the Gaviota format, read from a folder of
+// uncompressed .gtb files or zlib-compressed .gtb.cp1 ones. Only the
+// three-piece endings are indexed so far. Gaviota tables usually come
+// LZMA-compressed (.gtb.cp4), which is not read: they have to be
+// recompressed as cp1 first. The small three-piece tables are decoded
+// whole when loaded, so probes never touch the files
+pub struct GaviotaTablebase {
+    // Per piece type, the entries with White and with Black to move. The
+    // files only hold endings where White has the piece
+    tables: [Option<[Vec<u8>; NrOf::SIDES]>; NrOf::PIECE_TYPES],
+    // Per black king and white king square, the index of the pair
+    king_pairs: Vec<u16>,
+}
+
+impl GaviotaTablebase {
+    // Fails when no table is found or one cannot be read
+    pub fn new(folder: &str) -> Result<Self, String> {
+        let folder = Path::new(folder);
+        let mut tables: [Option<[Vec<u8>; NrOf::SIDES]>; NrOf::PIECE_TYPES] = Default::default();
+
+        for (name, piece) in MATERIALS {
+            let size = match piece {
+                Piece::Pawn => PAWN_SLICES * NrOf::SQUARES * NrOf::SQUARES,
+                _ => KING_PAIRS * NrOf::SQUARES,
+            };
+            tables[piece as usize] = read_table(folder, name, size)
+                .map_err(|e| format!("Failed to read Gaviota table {name}: {e}"))?;
+        }
+
+        if tables.iter().all(Option::is_none) {
+            return Err(format!("No three-piece Gaviota tables found in {}", folder.display()));
+        }
+
+        Ok(GaviotaTablebase {
+            tables,
+            king_pairs: king_pair_indices(),
+        })
+    }
+
+    // The side that mates, if any, and the plies to mate. None when the
+    // position is not a three-piece ending with a loaded table
+    fn probe(&self, board: &Board) -> Option<(Option<Side>, u32)> {
+        let occupancy = board.get_full_occupancy();
+        if occupancy.popcount() != 3 {
+            return None;
+        }
+        let strong_side = match board.get_side_occupancy(Side::White).popcount() {
+            2 => Side::White,
+            _ => Side::Black,
+        };
+        let king_mask = board.get_pieces(Side::White, Piece::King) | board.get_pieces(Side::Black, Piece::King);
+        let square = (occupancy & !king_mask).lsb()?;
+        let (_, piece) = board.piece_on(square)?;
+        let table = self.tables[piece as usize].as_ref()?;
+
+        // Endings where Black has the piece are looked up with the colours
+        // swapped and the board mirrored
+        let reversed = strong_side == Side::Black;
+        let orient = |square: Square| match reversed {
+            false => square as usize,
+            true => square as usize ^ 56,
+        };
+        let white_king = orient(board.get_king_square(strong_side));
+        let black_king = orient(board.get_king_square(strong_side.opponent()));
+        let side = match reversed {
+            false => board.get_active_side(),
+            true => board.get_active_side().opponent(),
+        };
+
+        let index = match piece {
+            Piece::Pawn => pawn_index(white_king, black_king, orient(square))?,
+            _ => self.piece_index(white_king, black_king, orient(square))?,
+        };
+        let (result, plies) = unpack(side, *table[side as usize].get(index)?)?;
+        let winner = match result {
+            WHITE_MATES => Some(strong_side),
+            BLACK_MATES => Some(strong_side.opponent()),
+            _ => None,
+        };
+        Some((winner, plies))
+    }
+
+    // The kings are brought to the a1-d1-d4 triangle, the black king below
+    // the diagonal when the white one is on it
+    fn piece_index(&self, white_king: usize, black_king: usize, piece: usize) -> Option<usize> {
+        let flip = flip_type(black_king, white_king);
+        let pair = self.king_pairs[flip_square(black_king, flip) * NrOf::SQUARES + flip_square(white_king, flip)];
+        if pair == NO_KING_PAIR {
+            return None;
+        }
+        Some(pair as usize * NrOf::SQUARES + flip_square(piece, flip))
+    }
+}
+
+impl Tablebase for GaviotaTablebase {
+    fn get_max_pieces(&self) -> u32 {
+        3
+    }
+
+    fn probe_wdl(&self, board: &Board) -> Option<Wdl> {
+        if board.get_full_occupancy().popcount() == 2 {
+            return Some(Wdl::Draw);
+        }
+        let (winner, _) = self.probe(board)?;
+        Some(match winner {
+            None => Wdl::Draw,
+            Some(side) if side == board.get_active_side() => Wdl::Win,
+            Some(_) => Wdl::Loss,
+        })
+    }
+
+    fn probe_dtm(&self, board: &Board) -> Option<i32> {
+        if board.get_full_occupancy().popcount() == 2 {
+            return Some(0);
+        }
+        let (winner, plies) = self.probe(board)?;
+        Some(match winner {
+            None => 0,
+            Some(side) if side == board.get_active_side() => plies as i32,
+            Some(_) => -(plies as i32),
+        })
+    }
+}
+
+
+// Both sides' entries of one ending, or None when neither file is there.
+// Uncompressed files hold White's entries, then Black's
+fn read_table(folder: &Path, name: &str, size: usize) -> Result<Option<[Vec<u8>; NrOf::SIDES]>, String> {
+    let plain = folder.join(format!("{name}.gtb"));
+    let compressed = folder.join(format!("{name}.gtb.cp1"));
+
+    let entries = if plain.exists() {
+        fs::read(&plain).map_err(|e| e.to_string())?
+    } else if compressed.exists() {
+        read_compressed(&fs::read(&compressed).map_err(|e| e.to_string())?, size)?
+    } else {
+        return Ok(None);
+    };
+
+    if entries.len() != NrOf::SIDES * size {
+        return Err(format!("Expected {} entries, found {}", NrOf::SIDES * size, entries.len()));
+    }
+    let (white, black) = entries.split_at(size);
+    Ok(Some([white.to_vec(), black.to_vec()]))
+}
+
+// Compressed files start with ten little-endian words, of which the third
+// is the block size and the ninth where the blocks begin. The offsets of
+// the blocks and of their end follow; each side's entries fill whole
+// blocks, White's first
+fn read_compressed(bytes: &[u8], size: usize) -> Result<Vec<u8>, String> {
+    let word = |index: usize| bytes.get(4 * index..4 * index + 4)
+        .map(|word| u32::from_le_bytes(word.try_into().unwrap()) as usize)
+        .ok_or_else(|| "File ends inside the header".to_string());
+
+    let block_size = word(2)?;
+    let data_start = word(8)?;
+    if block_size == 0 || data_start < 4 * (HEADER_WORDS + 2) || data_start % 4 != 0 {
+        return Err("Invalid header".to_string());
+    }
+    let offsets = (HEADER_WORDS..data_start / 4).map(word).collect::<Result<Vec<_>, _>>()?;
+    if offsets[0] != data_start {
+        return Err("Invalid block offsets".to_string());
+    }
+
+    let blocks = NrOf::SIDES * size.div_ceil(block_size);
+    if offsets.len() != blocks + 1 {
+        return Err(format!("Expected {blocks} blocks, found {}", offsets.len() - 1));
+    }
+
+    let mut entries = Vec::with_capacity(NrOf::SIDES * size);
+    for bounds in offsets.windows(2) {
+        let block = bytes.get(bounds[0]..bounds[1])
+            .ok_or_else(|| "A block lies outside the file".to_string())?;
+        entries.extend(decode_block(block)?);
+    }
+    Ok(entries)
+}
+
+fn decode_block(block: &[u8]) -> Result<Vec<u8>, String> {
+    let zlib = match block.split_first() {
+        Some((&STORED_BLOCK, stored)) => return Ok(stored.to_vec()),
+        Some((&ZLIB_BLOCK, zlib)) => zlib,
+        Some((&ZLIB_HEADER, _)) => block,
+        Some((id, _)) => return Err(format!("Unsupported block compression {id}, only zlib (cp1) is read")),
+        None => return Err("Empty block".to_string()),
+    };
+    let mut entries = Vec::new();
+    ZlibDecoder::new(zlib).read_to_end(&mut entries).map_err(|e| e.to_string())?;
+    Ok(entries)
+}
+
+// The result (DRAW, WHITE_MATES or BLACK_MATES) and plies to mate of an
+// entry, None for positions that cannot occur. Entries count moves, not
+// plies, so the side to move tells an odd count from an even one. Counts
+// past 63 moves borrow the DRAW and FORBIDDEN bits
+fn unpack(side: Side, entry: u8) -> Option<(u8, u32)> {
+    if entry == DRAW {
+        return Some((DRAW, 0));
+    }
+    if entry == FORBIDDEN {
+        return None;
+    }
+
+    let bits = entry & 3;
+    let count = (entry >> 2) as u32;
+    let (mover_mates, mover_mated) = match side {
+        Side::White => (WHITE_MATES, BLACK_MATES),
+        Side::Black => (BLACK_MATES, WHITE_MATES),
+    };
+
+    Some(match bits {
+        _ if bits == mover_mates => (mover_mates, 2 * (count + 1) - 1),
+        _ if bits == mover_mated => (mover_mated, 2 * count),
+        // Black's long mates never need a count of 63, which stands for
+        // Black's longest losses instead
+        DRAW if side == Side::Black && count == 63 => (mover_mated, 2 * (count + 1 + 63)),
+        DRAW => (mover_mates, 2 * (count + 1 + 63) - 1),
+        _ => (mover_mated, 2 * (count + 63)),
+    })
+}
+
+// The flips that bring the black king to the a1-d1-d4 triangle, and the
+// white king below the diagonal when the black one is on it: bit 0 mirrors
+// the files, bit 1 the ranks and bit 2 the a1-h8 diagonal
+fn flip_type(black_king: usize, white_king: usize) -> usize {
+    let mut flip = 0;
+    let (mut black_king, mut white_king) = (black_king, white_king);
+    if black_king % 8 > 3 {
+        flip |= 1;
+        (black_king, white_king) = (black_king ^ 7, white_king ^ 7);
+    }
+    if black_king / 8 > 3 {
+        flip |= 2;
+        (black_king, white_king) = (black_king ^ 56, white_king ^ 56);
+    }
+    if black_king / 8 > black_king % 8
+        || (black_king / 8 == black_king % 8 && white_king / 8 > white_king % 8) {
+        flip |= 4;
+    }
+    flip
+}
+
+fn flip_square(square: usize, flip: usize) -> usize {
+    let mut square = square;
+    if flip & 1 != 0 {
+        square ^= 7;
+    }
+    if flip & 2 != 0 {
+        square ^= 56;
+    }
+    if flip & 4 != 0 {
+        square = (square % 8) * 8 + square / 8;
+    }
+    square
+}
+
+// Numbers the king pairs that flip_type leaves unchanged in the order
+// Gaviota does, black king first; kings next to each other are skipped
+fn king_pair_indices() -> Vec<u16> {
+    let mut indices = vec![NO_KING_PAIR; NrOf::SQUARES * NrOf::SQUARES];
+    let mut next = 0;
+    for black_king in 0..NrOf::SQUARES {
+        for white_king in 0..NrOf::SQUARES {
+            let (black_square, white_square) = (Square::try_from(black_king).unwrap(), Square::try_from(white_king).unwrap());
+            if Square::distance(black_square, white_square) <= 1 {
+                continue;
+            }
+            let flip = flip_type(black_king, white_king);
+            let normal = flip_square(black_king, flip) * NrOf::SQUARES + flip_square(white_king, flip);
+            if indices[normal] == NO_KING_PAIR {
+                indices[normal] = next;
+                next += 1;
+            }
+        }
+    }
+    debug_assert_eq!(next as usize, KING_PAIRS);
+    indices
+}
+
+// Pawns are brought to files a to d, and numbered from a7 down to d2
+fn pawn_index(white_king: usize, black_king: usize, pawn: usize) -> Option<usize> {
+    if !(8..56).contains(&pawn) {
+        return None;
+    }
+    let flip = if pawn % 8 > 3 { 7 } else { 0 };
+    let slice_square = (pawn ^ flip ^ 56) - 8;
+    let slice = (slice_square + (slice_square & 3)) >> 1;
+    Some((slice * NrOf::SQUARES + (white_king ^ flip)) * NrOf::SQUARES + (black_king ^ flip))
+}
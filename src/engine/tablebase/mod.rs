@@ -0,0 +1,5 @@
+pub mod tablebase;
+
+pub mod three_piece_tablebase;
+
+pub mod gaviota_tablebase;
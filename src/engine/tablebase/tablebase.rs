@@ -0,0 +1,99 @@
+use crate::engine::{bitboard::BitboardExt, board::board::Board,
+    evaluator::endgame::KNOWN_WIN, searcher::score::Score};
+
+
+// Game-theoretic result for the side to move
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum Wdl {
+    Loss,
+    Draw,
+    Win,
+}
+
+
+// A source of perfect endgame knowledge. Backends that only know the
+// result, such as Syzygy WDL tables, return None from probe_dtm
+pub trait Tablebase: Send + Sync {
+    // Positions with more pieces, kings included, are never probed
+    fn get_max_pieces(&self) -> u32;
+
+    fn probe_wdl(&self, board: &Board) -> Option<Wdl>;
+
+    // Plies to mate with best play, positive when the side to move mates
+    // and negative when it is mated. Some(0) is a draw only when probe_wdl
+    // says so; a side already mated gets Some(0) as well
+    fn probe_dtm(&self, board: &Board) -> Option<i32>;
+
+    fn can_probe(&self, board: &Board) -> bool {
+        board.get_full_occupancy().popcount() <= self.get_max_pieces()
+            && board.game_state.castling == 0
+    }
+
+    // The most exact score available: a mate distance when the backend has
+    // one, otherwise a known win or loss without one
+    fn probe_score(&self, board: &Board) -> Option<Score> {
+        if !self.can_probe(board) {
+            return None;
+        }
+        let wdl = self.probe_wdl(board)?;
+        Some(match (wdl, self.probe_dtm(board)) {
+            (Wdl::Draw, _) => Score::ZERO,
+            (Wdl::Win, Some(plies)) => Score::mate_in(plies),
+            (Wdl::Loss, Some(plies)) => Score::mated_in(-plies),
+            (Wdl::Win, None) => Score::new(KNOWN_WIN),
+            (Wdl::Loss, None) => Score::new(-KNOWN_WIN),
+        })
+    }
+}
+
+
+// Several backends probed in order, e.g. a WDL backend for the result and a
+// DTM backend for exact mate distances where it has them
+pub struct Tablebases {
+    backends: Vec<Box<dyn Tablebase>>,
+}
+
+impl Tablebases {
+    pub fn new() -> Self {
+        Tablebases {
+            backends: Vec::new(),
+        }
+    }
+
+    pub fn with(mut self, backend: Box<dyn Tablebase>) -> Self {
+        self.add(backend);
+        self
+    }
+
+    pub fn add(&mut self, backend: Box<dyn Tablebase>) {
+        self.backends.push(backend);
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.backends.is_empty()
+    }
+}
+
+impl Default for Tablebases {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Tablebase for Tablebases {
+    fn get_max_pieces(&self) -> u32 {
+        self.backends.iter().map(|backend| backend.get_max_pieces()).max().unwrap_or(0)
+    }
+
+    fn probe_wdl(&self, board: &Board) -> Option<Wdl> {
+        self.backends.iter()
+            .filter(|backend| backend.can_probe(board))
+            .find_map(|backend| backend.probe_wdl(board))
+    }
+
+    fn probe_dtm(&self, board: &Board) -> Option<i32> {
+        self.backends.iter()
+            .filter(|backend| backend.can_probe(board))
+            .find_map(|backend| backend.probe_dtm(board))
+    }
+}
@@ -0,0 +1,270 @@
+use crate::engine::{bitboard::BitboardExt,
+    board::board::Board,
+    definitions::{NrOf, Piece, Side, Square, SQUARE_BITBOARDS},
+    move_generator::attacks::{king_attacks, pawn_attacks, queen_attacks, rook_attacks},
+    tablebase::tablebase::{Tablebase, Wdl}};
+
+
+// Positions per table: side to move, both kings and the extra piece
+const TABLE_SIZE: usize = NrOf::SIDES * NrOf::SQUARES * NrOf::SQUARES * NrOf::SQUARES;
+
+// Table entries. Wins hold the plies to mate plus one and losses the same
+// negated, so that 0 is left for draws and illegal positions
+type Dtm = i16;
+const DRAW: Dtm = 0;
+
+
+// Distance-to-mate tables for every three-piece ending, built in memory by
+// retrograde analysis. Building them takes a few seconds, all in new, so
+// that probes from search never wait. Bishop and knight endings are drawn
+// and need no table. It reads no tablebase files, so it knows nothing
+// beyond three pieces; GaviotaTablebase reads them
+pub struct ThreePieceTablebase {
+    queen: Vec<Dtm>,
+    rook: Vec<Dtm>,
+    pawn: Vec<Dtm>,
+}
+
+impl ThreePieceTablebase {
+    pub fn new() -> Self {
+        let queen = generate(Piece::Queen, &[]);
+        let rook = generate(Piece::Rook, &[]);
+        let pawn = generate(Piece::Pawn, &[&queen, &rook]);
+        ThreePieceTablebase { queen, rook, pawn }
+    }
+
+    fn table(&self, piece: Piece) -> Option<&[Dtm]> {
+        match piece {
+            Piece::Queen => Some(&self.queen),
+            Piece::Rook => Some(&self.rook),
+            Piece::Pawn => Some(&self.pawn),
+            _ => None,
+        }
+    }
+
+    // The entry for the position, or None when it is not a three-piece
+    // ending that can be won
+    fn probe(&self, board: &Board) -> Option<Dtm> {
+        let occupancy = board.get_full_occupancy();
+        if occupancy.popcount() != 3 {
+            return None;
+        }
+        let strong_side = match board.get_side_occupancy(Side::White).popcount() {
+            2 => Side::White,
+            _ => Side::Black,
+        };
+        let king_mask = board.get_pieces(Side::White, Piece::King) | board.get_pieces(Side::Black, Piece::King);
+        let square = (occupancy & !king_mask).lsb()?;
+        let (_, piece) = board.piece_on(square)?;
+
+        // Tables are built with White as the strong side
+        let orient = |square: Square| match strong_side {
+            Side::White => square as usize,
+            Side::Black => square as usize ^ 56,
+        };
+        let strong_to_move = board.get_active_side() == strong_side;
+        let table = self.table(piece)?;
+        Some(table[index(strong_to_move, orient(board.get_king_square(strong_side)),
+            orient(board.get_king_square(strong_side.opponent())), orient(square))])
+    }
+}
+
+impl Default for ThreePieceTablebase {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Tablebase for ThreePieceTablebase {
+    fn get_max_pieces(&self) -> u32 {
+        3
+    }
+
+    fn probe_wdl(&self, board: &Board) -> Option<Wdl> {
+        match board.get_full_occupancy().popcount() {
+            // Bare kings, or a lone minor piece
+            2 => Some(Wdl::Draw),
+            3 => Some(match self.probe(board) {
+                Some(dtm) if dtm > 0 => Wdl::Win,
+                Some(dtm) if dtm < 0 => Wdl::Loss,
+                _ => Wdl::Draw,
+            }),
+            _ => None,
+        }
+    }
+
+    fn probe_dtm(&self, board: &Board) -> Option<i32> {
+        if board.get_full_occupancy().popcount() == 2 {
+            return Some(0);
+        }
+        Some(match self.probe(board).unwrap_or(DRAW) {
+            dtm if dtm > 0 => dtm as i32 - 1,
+            dtm if dtm < 0 => dtm as i32 + 1,
+            _ => 0,
+        })
+    }
+}
+
+
+fn index(white_to_move: bool, white_king: usize, black_king: usize, piece: usize) -> usize {
+    (((piece * NrOf::SQUARES + black_king) * NrOf::SQUARES + white_king) << 1) | white_to_move as usize
+}
+
+fn square(index: usize) -> Square {
+    Square::try_from(index).unwrap()
+}
+
+fn piece_attacks(piece: Piece, from: Square, occupancy: u64) -> u64 {
+    match piece {
+        Piece::Queen => queen_attacks(from, occupancy),
+        Piece::Rook => rook_attacks(from, occupancy),
+        Piece::Pawn => pawn_attacks(from, Side::White),
+        _ => 0,
+    }
+}
+
+// Whether the kings and White's piece on those squares, with that side to
+// move, form a reachable position
+fn is_legal(white_to_move: bool, white_king: usize, black_king: usize, piece_square: usize,
+    piece: Piece) -> bool {
+    if white_king == black_king || white_king == piece_square || black_king == piece_square
+        || Square::distance(square(white_king), square(black_king)) <= 1 {
+        return false;
+    }
+    if piece == Piece::Pawn && !(8..56).contains(&piece_square) {
+        return false;
+    }
+    // Black cannot have been left in check
+    let occupancy = SQUARE_BITBOARDS[white_king] | SQUARE_BITBOARDS[black_king];
+    !(white_to_move && piece_attacks(piece, square(piece_square), occupancy).contains_square(square(black_king)))
+}
+
+// The white moves from a position, as table lookups of the positions they
+// lead to. Promotions look up the queen and rook tables
+fn white_successors(table: &[Dtm], promotions: &[&[Dtm]], piece: Piece, white_king: usize,
+    black_king: usize, piece_square: usize) -> Vec<Dtm> {
+    let mut successors = Vec::new();
+    let occupancy = SQUARE_BITBOARDS[white_king] | SQUARE_BITBOARDS[black_king] | SQUARE_BITBOARDS[piece_square];
+
+    let king_targets = king_attacks(square(white_king)) & !king_attacks(square(black_king))
+        & !SQUARE_BITBOARDS[piece_square];
+    for to in king_targets.squares() {
+        successors.push(table[index(false, to as usize, black_king, piece_square)]);
+    }
+
+    if piece == Piece::Pawn {
+        let push = piece_square + NrOf::FILES;
+        if occupancy.contains_square(square(push)) {
+            return successors;
+        }
+        if push >= 56 {
+            for promoted in promotions {
+                successors.push(promoted[index(false, white_king, black_king, push)]);
+            }
+            return successors;
+        }
+        successors.push(table[index(false, white_king, black_king, push)]);
+        let double_push = push + NrOf::FILES;
+        if piece_square < 16 && !occupancy.contains_square(square(double_push)) {
+            successors.push(table[index(false, white_king, black_king, double_push)]);
+        }
+    } else {
+        let targets = piece_attacks(piece, square(piece_square), occupancy) & !occupancy;
+        for to in targets.squares() {
+            successors.push(table[index(false, white_king, black_king, to as usize)]);
+        }
+    }
+    successors
+}
+
+// The black king's moves, or None when one of them takes the piece, which
+// draws
+fn black_successors(table: &[Dtm], piece: Piece, white_king: usize, black_king: usize,
+    piece_square: usize) -> Option<Vec<Dtm>> {
+    // The king does not block attacks along the line it moves on
+    let occupancy = SQUARE_BITBOARDS[white_king] | SQUARE_BITBOARDS[piece_square];
+    let guarded = king_attacks(square(white_king)) | piece_attacks(piece, square(piece_square), occupancy);
+    let targets = king_attacks(square(black_king)) & !guarded;
+
+    if targets.contains_square(square(piece_square)) {
+        return None;
+    }
+    Some(targets.squares()
+        .map(|to| table[index(true, white_king, to as usize, piece_square)])
+        .collect())
+}
+
+// Mates are found first, then every pass resolves the positions one ply
+// further from mate, until a pass finds nothing. What is left is drawn
+fn generate(piece: Piece, promotions: &[&[Dtm]]) -> Vec<Dtm> {
+    let mut table = vec![DRAW; TABLE_SIZE];
+    let positions: Vec<(usize, bool, usize, usize, usize)> = (0..TABLE_SIZE)
+        .filter_map(|i| {
+            let (white_to_move, white_king, black_king, piece_square) =
+                (i & 1 == 1, (i >> 1) % NrOf::SQUARES, (i >> 7) % NrOf::SQUARES, i >> 13);
+            is_legal(white_to_move, white_king, black_king, piece_square, piece)
+                .then_some((i, white_to_move, white_king, black_king, piece_square))
+        })
+        .collect();
+
+    for &(i, white_to_move, white_king, black_king, piece_square) in &positions {
+        if white_to_move {
+            continue;
+        }
+        let occupancy = SQUARE_BITBOARDS[white_king] | SQUARE_BITBOARDS[black_king];
+        let in_check = piece_attacks(piece, square(piece_square), occupancy).contains_square(square(black_king));
+        if in_check && black_successors(&table, piece, white_king, black_king, piece_square)
+            .is_some_and(|moves| moves.is_empty()) {
+            table[i] = -1;
+        }
+    }
+
+    // Promotions can resolve positions after passes that found nothing, up
+    // to the longest mate in the promoted tables
+    let promotion_plies = promotions.iter()
+        .flat_map(|promoted| promoted.iter())
+        .map(|dtm| dtm.abs())
+        .max()
+        .unwrap_or(0);
+
+    let mut plies: Dtm = 1;
+    let mut idle_passes = 0;
+    loop {
+        let white_turn = plies % 2 == 1;
+        let mut resolved = Vec::new();
+
+        for &(i, white_to_move, white_king, black_king, piece_square) in &positions {
+            if table[i] != DRAW || white_to_move != white_turn {
+                continue;
+            }
+            if white_to_move {
+                let successors = white_successors(&table, promotions, piece, white_king, black_king, piece_square);
+                // Promotions land in tables that are already complete, so any
+                // of their losses counts, not just those one ply closer
+                let best = successors.iter().filter(|dtm| **dtm < 0).map(|dtm| -dtm).min();
+                if let Some(best) = best.filter(|best| *best <= plies) {
+                    resolved.push((i, best + 1));
+                }
+            } else {
+                // Lost once every reply can be mated. Taking the piece gives
+                // no successors, like stalemate, and the position stays drawn
+                let successors = black_successors(&table, piece, white_king, black_king, piece_square)
+                    .unwrap_or_default();
+                if !successors.is_empty() && successors.iter().all(|dtm| *dtm > 0) {
+                    let longest = *successors.iter().max().unwrap();
+                    resolved.push((i, -(longest + 1)));
+                }
+            }
+        }
+
+        idle_passes = if resolved.is_empty() { idle_passes + 1 } else { 0 };
+        if idle_passes >= 2 && plies > promotion_plies {
+            break;
+        }
+        for (i, dtm) in resolved {
+            table[i] = dtm;
+        }
+        plies += 1;
+    }
+    table
+}
@@ -1,10 +1,59 @@
 pub mod engine;
+
+#[cfg(feature = "wasm")]
+pub mod wasm;
  
 pub use engine::board::board::Board;
+pub use engine::board::board_builder::BoardBuilder;
+pub use engine::board::game_result::{DrawRules, GameResult, InsufficientMaterialRules};
+pub use engine::board::position_key::PositionKey;
+pub use engine::board::position_command::parse_position_command;
+pub use engine::board::polyglot::PolyglotKeys;
+pub use engine::board::zobrist::ZOBRIST_SEED;
+pub use engine::board::epd::{parse_epd_records, Epd};
 pub use engine::move_generator::move_generator::MoveGenerator;
+pub use engine::move_generator::move_formatter::{MoveFormatter, NotationStyle};
+pub use engine::rules::rules::{Rules, StandardRules};
+pub use engine::rules::king_of_the_hill::KingOfTheHill;
+pub use engine::rules::variant::{Variant, UCI_VARIANT_OPTION};
+pub use engine::pgn::game::{Game, GameMove, PgnResult};
+pub use engine::pgn::writer::PgnWriter;
+pub use engine::pgn::reader::{parse_game, parse_pgn, PgnReader};
+pub use engine::analysis::game_analysis::{AnalyzedMove, ClassificationThresholds, GameAnalysis,
+    GameAnalyzer, MoveClass, PositionEval};
+pub use engine::analysis::accuracy::{game_accuracy, move_accuracy, win_percent};
+pub use engine::analysis::eval_series::{eval_series, eval_series_to_json, EvalPoint,
+    EVAL_SERIES_CSV_HEADER};
 
-pub use crate::engine::evaluator::evaluator::Evaluator;
+pub use crate::engine::evaluator::evaluator::{EvalError, Evaluator, PolicyEvaluator};
+#[cfg(feature = "nn")]
 pub use crate::engine::evaluator::cnn_evaluator::CNNEvaluator;
+#[cfg(feature = "nn")]
 pub use crate::engine::evaluator::halfka_evaluator::HalfkaEvaluator;
+#[cfg(feature = "nn")]
+pub use crate::engine::evaluator::policy_value_evaluator::PolicyValueEvaluator;
+pub use crate::engine::evaluator::classical_evaluator::ClassicalEvaluator;
+pub use crate::engine::evaluator::ensemble_evaluator::EnsembleEvaluator;
+pub use crate::engine::evaluator::batch_server::{BatchClient, BatchServer};
+pub use crate::engine::evaluator::bench::{EvalBenchReport, BENCH_FENS};
+pub use crate::engine::evaluator::calibration::{CalibratedEvaluator, Calibration};
+pub use crate::engine::evaluator::endgame::{EndgameEvaluator, Endgames, MaterialSignature};
+pub use crate::engine::evaluator::features::FeatureDump;
+pub use crate::engine::evaluator::nnue::{NnueEvaluator, NnueNetwork};
+#[cfg(feature = "nn")]
+pub use crate::engine::evaluator::inference_config::{InferenceConfig, InferenceDevice, OptimizationLevel};
+#[cfg(feature = "nn")]
+pub use crate::engine::evaluator::model_validation::{architecture_hash, ModelError};
 pub use crate::engine::searcher::transposition_table::TranspositionTable;
-pub use crate::engine::searcher::searcher::Searcher;
\ No newline at end of file
+pub use crate::engine::searcher::searcher::{HybridEvaluation, LazyEvaluation, Searcher};
+pub use crate::engine::searcher::score::{Score, WhiteRelativeEvaluator};
+pub use crate::engine::searcher::thread_pool::{SearchProgress, SearchThreadPool, ThreadResult};
+pub use crate::engine::searcher::engine::{Engine, SearchHandle, SearchLimits};
+pub use crate::engine::solver::pns::{MateResult, MateSolver};
+pub use crate::engine::tablebase::tablebase::{Tablebase, Tablebases, Wdl};
+pub use crate::engine::tablebase::three_piece_tablebase::ThreePieceTablebase;
+pub use crate::engine::tablebase::gaviota_tablebase::GaviotaTablebase;
+pub use crate::engine::selfplay::binpack::{BinpackReader, BinpackRecord, BinpackWriter};
+pub use crate::engine::selfplay::self_play::{SelfPlay, SelfPlayStats};
+pub use crate::engine::move_generator::perft::{divide, hashed_perft, parallel_perft,
+    perft, print_divide, PerftTable};
\ No newline at end of file
@@ -1,20 +1,48 @@
 use king_crab::Board;
 use king_crab::MoveGenerator;
 
+use king_crab::ClassicalEvaluator;
 use king_crab::CNNEvaluator;
+use king_crab::Evaluator;
 use king_crab::HalfkaEvaluator;
 use king_crab::Searcher;
 use king_crab::TranspositionTable;
+use king_crab::BENCH_FENS;
+use king_crab::GameAnalyzer;
+use king_crab::{MateResult, MateSolver};
+use king_crab::{eval_series, eval_series_to_json, EVAL_SERIES_CSV_HEADER};
+use king_crab::PgnReader;
+use king_crab::PgnWriter;
 
 use std::env;
+use std::fs::File;
+use std::io::BufReader;
 use std::process;
  
  fn main() {
 
    let args: Vec<String> = env::args().collect();
 
+   if args.get(1).map(String::as_str) == Some("bench") {
+        run_bench(&args);
+        return;
+   }
+
+   if args.get(1).map(String::as_str) == Some("analyze") {
+        run_analysis(&args);
+        return;
+   }
+
+   if args.get(1).map(String::as_str) == Some("mate") {
+        run_mate(&args);
+        return;
+   }
+
    if args.len() < 2 {
         eprintln!("Usage: {} <cnn_model_path> <cnn_depth> <halfka_model_path> <halfka_depth> [fen]", args[0]);
+        eprintln!("       {} bench <classical|cnn|halfka> [model_path] [seconds]", args[0]);
+        eprintln!("       {} mate <fen> <moves> [max_nodes]", args[0]);
+        eprintln!("       {} analyze <pgn_path> [depth] [pgn|json|series-json|series-csv]", args[0]);
         process::exit(1);
    }
 
@@ -40,12 +68,12 @@ use std::process;
 
    let move_generator = MoveGenerator::new();
 
-   let mut transposition_table = TranspositionTable::new(20);
+   let transposition_table = TranspositionTable::new(20);
 
    let mut searcher = Searcher::new(
       &mut evaluator1,
       &move_generator, 
-      &mut transposition_table);
+      &transposition_table);
 
    let time1 = std::time::Instant::now();
    let result1 = searcher.search(&board, cnn_depth);
@@ -73,4 +101,138 @@ use std::process;
    println!("Search time with Halfka at depth {}: {} ms",
             halfka_depth,
             time2.elapsed().as_millis());
- }
\ No newline at end of file
+ }
+
+
+// bench <classical|cnn|halfka> [model_path] [seconds]
+fn run_bench(args: &[String]) {
+   let kind = args.get(2).map(String::as_str).unwrap_or_else(|| bench_usage(&args[0]));
+   let (mut evaluator, seconds_arg): (Box<dyn Evaluator>, usize) = match kind {
+        "classical" => (Box::new(ClassicalEvaluator::new()), 3),
+        "cnn" | "halfka" => {
+            let path = args.get(3).unwrap_or_else(|| bench_usage(&args[0]));
+            let evaluator: Result<Box<dyn Evaluator>, String> = match kind {
+                "cnn" => CNNEvaluator::new(path).map(|e| Box::new(e) as Box<dyn Evaluator>),
+                _ => HalfkaEvaluator::new(path).map(|e| Box::new(e) as Box<dyn Evaluator>),
+            };
+            match evaluator {
+                Ok(evaluator) => (evaluator, 4),
+                Err(e) => {
+                    eprintln!("Failed to load {}: {}", path, e);
+                    process::exit(1);
+                }
+            }
+        }
+        _ => bench_usage(&args[0]),
+   };
+   let seconds = args.get(seconds_arg)
+       .and_then(|s| s.parse::<f64>().ok())
+       .unwrap_or(5.0);
+
+   let positions: Vec<Board> = BENCH_FENS.iter()
+       .map(|fen| Board::try_from_fen(fen).unwrap())
+       .collect();
+
+   println!("Benchmarking {} evaluation for {} s", kind, seconds);
+   println!("{}", evaluator.bench(&positions, seconds));
+}
+
+fn bench_usage(program: &str) -> ! {
+   eprintln!("Usage: {} bench <classical|cnn|halfka> [model_path] [seconds]", program);
+   process::exit(1);
+}
+
+
+// analyze <pgn_path> [depth] [pgn|json|series-json|series-csv]
+// Analyses every game of the file with the classical evaluator and prints
+// each one as annotated PGN or as one line of JSON. The series formats give
+// only the eval of every position, for plotting: one JSON array per game,
+// or CSV rows for all games under a single header
+fn run_analysis(args: &[String]) {
+   let path = args.get(2).unwrap_or_else(|| analysis_usage(&args[0]));
+   let depth = args.get(3)
+       .and_then(|s| s.parse::<u8>().ok())
+       .unwrap_or(4);
+   let format = args.get(4).map(String::as_str).unwrap_or("pgn");
+   if !["pgn", "json", "series-json", "series-csv"].contains(&format) {
+        analysis_usage(&args[0]);
+   }
+
+   let file = File::open(path).unwrap_or_else(|e| {
+        eprintln!("Failed to open {}: {}", path, e);
+        process::exit(1);
+   });
+
+   let move_generator = MoveGenerator::new();
+   let mut evaluator = ClassicalEvaluator::new();
+   let mut analyzer = GameAnalyzer::new(&mut evaluator, &move_generator).depth(depth);
+   let writer = PgnWriter::new();
+
+   if format == "series-csv" {
+        println!("game,{}", EVAL_SERIES_CSV_HEADER);
+   }
+   for (index, game) in PgnReader::new(BufReader::new(file), &move_generator).enumerate() {
+        let mut game = match game {
+            Ok(game) => game,
+            Err(e) => {
+                eprintln!("Skipping game: {}", e);
+                continue;
+            }
+        };
+        let analysis = analyzer.analyze(&game);
+        match format {
+            "json" => println!("{}", analysis.to_json()),
+            "series-json" => println!("{}", eval_series_to_json(&eval_series(&analysis))),
+            "series-csv" => {
+                for point in eval_series(&analysis) {
+                    println!("{},{}", index + 1, point.to_csv());
+                }
+            }
+            _ => {
+                analysis.annotate(&mut game);
+                println!("{}", writer.write(&game, &move_generator));
+            }
+        }
+   }
+}
+
+fn analysis_usage(program: &str) -> ! {
+   eprintln!("Usage: {} analyze <pgn_path> [depth] [pgn|json|series-json|series-csv]", program);
+   process::exit(1);
+}
+
+
+// mate <fen> <moves> [max_nodes]
+// Proves or refutes a mate by the side to move within the given moves
+fn run_mate(args: &[String]) {
+   let (Some(fen), Some(moves)) = (args.get(2), args.get(3).and_then(|s| s.parse::<u32>().ok())) else {
+        mate_usage(&args[0]);
+   };
+   let board = Board::try_from_fen(fen).unwrap_or_else(|e| {
+        eprintln!("Invalid FEN {}: {}", fen, e);
+        process::exit(1);
+   });
+
+   let move_generator = MoveGenerator::new();
+   let mut solver = MateSolver::new(&move_generator);
+   if let Some(max_nodes) = args.get(4).and_then(|s| s.parse::<usize>().ok()) {
+        solver = solver.max_nodes(max_nodes);
+   }
+
+   match solver.solve(&board, moves) {
+        MateResult::Mate { moves, line } => {
+            let mut board = board.clone();
+            for mv in line {
+                board.make_move(mv);
+            }
+            println!("Mate in {}: {}", moves, board.movetext(&move_generator));
+        }
+        MateResult::NoMate => println!("No mate in {}", moves),
+        MateResult::Unknown => println!("Unknown: node limit reached after {} nodes", solver.get_nodes()),
+   }
+}
+
+fn mate_usage(program: &str) -> ! {
+   eprintln!("Usage: {} mate <fen> <moves> [max_nodes]", program);
+   process::exit(1);
+}
@@ -0,0 +1,100 @@
+use wasm_bindgen::prelude::*;
+
+use crate::engine::{board::{board::Board, position_command::parse_position_command},
+    definitions::FEN_STARTING_POSITION,
+    evaluator::classical_evaluator::ClassicalEvaluator,
+    move_generator::move_generator::MoveGenerator,
+    searcher::{searcher::Searcher, transposition_table::TranspositionTable}};
+
+
+// 2^18 entries, a few megabytes, which browsers grant without asking
+const HASH_BITS: usize = 18;
+
+
+// The engine for browser GUIs, with the classical evaluator and a single
+// search thread. Moves go in and out in UCI notation, e.g. "e2e4", "e7e8q"
+#[wasm_bindgen]
+pub struct WasmEngine {
+    board: Board,
+    movegen: MoveGenerator,
+    evaluator: ClassicalEvaluator,
+    transposition_table: TranspositionTable,
+}
+
+#[wasm_bindgen]
+impl WasmEngine {
+    #[wasm_bindgen(constructor)]
+    pub fn new() -> WasmEngine {
+        WasmEngine {
+            board: Board::try_from_fen(FEN_STARTING_POSITION).unwrap(),
+            movegen: MoveGenerator::new(),
+            evaluator: ClassicalEvaluator::new(),
+            transposition_table: TranspositionTable::new(HASH_BITS),
+        }
+    }
+
+    // Takes the arguments of a UCI position command, e.g. "startpos moves
+    // e2e4 e7e5" or "fen <fen> moves ...". The position is unchanged when
+    // the command is rejected
+    pub fn set_position(&mut self, command: &str) -> Result<(), JsError> {
+        self.board = parse_position_command(command, &self.movegen)
+            .map_err(|e| JsError::new(&e.to_string()))?;
+        Ok(())
+    }
+
+    pub fn play_move(&mut self, uci: &str) -> Result<(), JsError> {
+        self.board.push_uci(&self.movegen, uci)
+            .map_err(|e| JsError::new(&e.to_string()))?;
+        Ok(())
+    }
+
+    pub fn get_fen(&self) -> String {
+        self.board.to_fen()
+    }
+
+    pub fn legal_moves(&mut self) -> Vec<String> {
+        self.movegen.generate_legal_moves(&mut self.board)
+            .iter()
+            .map(|mv| mv.to_uci())
+            .collect()
+    }
+
+    // Deepens one ply at a time up to depth. With a time limit above 0 no
+    // new iteration is started once half of it has gone, as the next one
+    // would most likely not finish in time; browsers give a single thread
+    // no way to interrupt a search already running. Undefined when the
+    // game is over
+    pub fn best_move(&mut self, depth: u8, time_limit_ms: f64) -> Option<String> {
+        let start = now_ms();
+        let mut searcher = Searcher::new(&mut self.evaluator, &self.movegen, &self.transposition_table);
+        let mut best_move = None;
+
+        for iteration in 1..=depth.max(1) {
+            best_move = searcher.search(&self.board, iteration).or(best_move);
+            if time_limit_ms > 0.0 && now_ms() - start >= time_limit_ms / 2.0 {
+                break;
+            }
+        }
+        best_move.map(|mv| mv.to_uci())
+    }
+}
+
+impl Default for WasmEngine {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+
+#[cfg(target_arch = "wasm32")]
+fn now_ms() -> f64 {
+    js_sys::Date::now()
+}
+
+// std::time is unavailable in the browser, so native builds, which are
+// only used for trying the bindings out, have their own clock
+#[cfg(not(target_arch = "wasm32"))]
+fn now_ms() -> f64 {
+    use std::time::{SystemTime, UNIX_EPOCH};
+    SystemTime::now().duration_since(UNIX_EPOCH).map_or(0.0, |elapsed| elapsed.as_secs_f64() * 1000.0)
+}